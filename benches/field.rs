@@ -0,0 +1,79 @@
+use bunkeys::field::{Field, Fp521, MODULUS_521};
+use bunkeys::polynom::Polynom;
+use bunkeys::shamir_secret_sharing::get_polynom_constant_value;
+use criterion::{criterion_group, criterion_main, Criterion};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use std::hint::black_box;
+
+// Baseline: the BigUint arithmetic Fp521 used before the fixed-width limbs
+
+fn biguint_calculate_y(coefficients: &[BigUint], x: &BigUint) -> BigUint {
+    let modulus = MODULUS_521.clone();
+    let mut y = BigUint::ZERO;
+    let mut x_pow = BigUint::from(1u8);
+    for coef in coefficients {
+        y = (y + coef * &x_pow) % &modulus;
+        x_pow = (x_pow * x) % &modulus;
+    }
+    y
+}
+
+fn biguint_constant_value(points: &[(BigUint, BigUint)]) -> BigUint {
+    let modulus = MODULUS_521.clone();
+    let mut y = BigUint::ZERO;
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut numerator = BigUint::from(1u8);
+        let mut denominator = BigUint::from(1u8);
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                numerator = (numerator * (&modulus - xj)) % &modulus;
+                denominator = (denominator * ((xi + &modulus - xj) % &modulus)) % &modulus;
+            }
+        }
+        let lagrange_coef = yi * numerator % &modulus * denominator.modinv(&modulus).unwrap() % &modulus;
+        y = (y + lagrange_coef) % &modulus;
+    }
+    y
+}
+
+fn field_operations(c: &mut Criterion) {
+    let a = Fp521::random(&mut OsRng);
+    let b = Fp521::random(&mut OsRng);
+    let (a_big, b_big) = (a.to_biguint(), b.to_biguint());
+
+    let mut group = c.benchmark_group("fp521");
+    group.bench_function("mul/limbs", |bench| bench.iter(|| black_box(&a).mul(black_box(&b))));
+    group.bench_function("mul/biguint", |bench| {
+        bench.iter(|| black_box(&a_big) * black_box(&b_big) % &*MODULUS_521)
+    });
+    group.bench_function("inv/limbs", |bench| bench.iter(|| black_box(&a).inv()));
+    group.bench_function("inv/biguint", |bench| bench.iter(|| black_box(&a_big).modinv(&MODULUS_521)));
+    group.finish();
+}
+
+fn shamir_operations(c: &mut Criterion) {
+    let threshold = 5;
+    let polynom = Polynom::new_random_of_degree_with_constant_term(threshold - 1, Fp521::random(&mut OsRng), &mut OsRng);
+    let points = polynom.get_firsts_n_points(threshold as u8);
+    let coefficients: Vec<BigUint> = polynom.coefficients.iter().map(Fp521::to_biguint).collect();
+    let big_points: Vec<(BigUint, BigUint)> = points.iter().map(|(x, y)| (x.to_biguint(), y.to_biguint())).collect();
+    let x = Fp521::from_index(200);
+    let x_big = x.to_biguint();
+
+    let mut group = c.benchmark_group("shamir");
+    group.bench_function("calculate_y/limbs", |bench| bench.iter(|| polynom.calculate_y(black_box(x))));
+    group.bench_function("calculate_y/biguint", |bench| {
+        bench.iter(|| biguint_calculate_y(&coefficients, black_box(&x_big)))
+    });
+    group.bench_function("constant_value/limbs", |bench| {
+        bench.iter(|| get_polynom_constant_value(black_box(&points)).unwrap())
+    });
+    group.bench_function("constant_value/biguint", |bench| {
+        bench.iter(|| biguint_constant_value(black_box(&big_points)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, field_operations, shamir_operations);
+criterion_main!(benches);
@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use age::x25519::{Identity, Recipient};
+use crate::policy::PolicyShare;
+use crate::share_encoding::{EncodedShare, ShareFormat, MAX_LABEL_BYTES};
+
+/// Extension of the encrypted share files
+pub const SHARE_FILE_EXTENSION: &str = "age";
+
+/// Prefix of a custodian identity typed instead of the path of an identity file
+const IDENTITY_PREFIX: &str = "AGE-SECRET-KEY-";
+
+/// Format the shares are sealed in, the most compact one
+const SEALED_SHARE_FORMAT: ShareFormat = ShareFormat::Hex;
+
+/// A custodian's X25519 public key (`age1...`), named by the comment following it in the recipients file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareRecipient {
+    pub key: Recipient,
+    pub name: Option<String>,
+}
+
+/// Parses a recipients file, one `age1...` key per line followed by an optional `# name` comment.
+/// Empty and comment-only lines are skipped.
+pub fn parse_recipients(text: &str) -> Result<Vec<ShareRecipient>, String> {
+    let recipients = text
+        .lines()
+        .filter_map(|line| {
+            let (key, name) = match line.split_once('#') {
+                Some((key, name)) => (key.trim(), Some(name.trim().to_string()).filter(|n| !n.is_empty())),
+                None => (line.trim(), None),
+            };
+            (!key.is_empty()).then_some((key, name))
+        })
+        .map(|(key, name)| {
+            let key = key
+                .parse::<Recipient>()
+                .map_err(|e| format!("Invalid recipient '{}': {}", key, e))?;
+            if let Some(name) = &name
+                && name.len() > MAX_LABEL_BYTES
+            {
+                return Err(format!("Recipient name '{}' is longer than {} bytes", name, MAX_LABEL_BYTES));
+            }
+            Ok(ShareRecipient { key, name })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if recipients.is_empty() {
+        return Err("The recipients file holds no age1 public key".to_string());
+    }
+    Ok(recipients)
+}
+
+pub fn load_recipients(path: &Path) -> Result<Vec<ShareRecipient>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read recipients file: {}", e))?;
+    parse_recipients(&text)
+}
+
+/// Encrypts the text of a share to a single recipient, as an ASCII armored age file
+pub fn seal_share(share_text: &str, recipient: &ShareRecipient) -> Result<String, String> {
+    age::encrypt_and_armor(&recipient.key, share_text.as_bytes()).map_err(|e| format!("Failed to encrypt share: {}", e))
+}
+
+/// Writes a sealed share next to the store: `store.enc` -> `store-share-<suffix>.age`
+fn write_sealed_share(store_path: &Path, suffix: &str, share_text: &str, recipient: &ShareRecipient) -> Result<PathBuf, String> {
+    let stem = store_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "store".to_string());
+    let suffix: String = suffix
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    let path = store_path.with_file_name(format!("{}-share-{}.{}", stem, suffix, SHARE_FILE_EXTENSION));
+    fs::write(&path, seal_share(share_text, recipient)?)
+        .map_err(|e| format!("Failed to write share file {}: {}", path.to_string_lossy(), e))?;
+    Ok(path)
+}
+
+/// Seals share i to recipient i, labelling it with the recipient name, and writes the files next to the store
+pub fn seal_shares(store_path: &Path, shares: &[EncodedShare], recipients: &[ShareRecipient]) -> Result<Vec<(String, PathBuf)>, String> {
+    if shares.len() != recipients.len() {
+        return Err(format!(
+            "The split has {} shares but the recipients file lists {} keys",
+            shares.len(),
+            recipients.len()
+        ));
+    }
+    shares
+        .iter()
+        .zip(recipients)
+        .map(|(share, recipient)| {
+            let share = EncodedShare { label: recipient.name.clone().or(share.label.clone()), ..share.clone() };
+            let suffix = match &share.label {
+                Some(label) => format!("{}-{}", share.share.x, label),
+                None => share.share.x.to_string(),
+            };
+            let path = write_sealed_share(store_path, &suffix, &share.encode(SEALED_SHARE_FORMAT), recipient)?;
+            Ok((share.name(), path))
+        })
+        .collect()
+}
+
+/// Seals every member share to the recipient named after the member, and writes the files next to the store
+pub fn seal_policy_shares(
+    store_path: &Path,
+    shares: &[PolicyShare],
+    recipients: &[ShareRecipient],
+) -> Result<Vec<(String, PathBuf)>, String> {
+    shares
+        .iter()
+        .map(|share| {
+            let member = share.share.label.as_deref().unwrap_or_default();
+            let recipient = recipients
+                .iter()
+                .find(|recipient| recipient.name.as_deref() == Some(member))
+                .ok_or_else(|| format!("No recipient named '{}' in the recipients file", member))?;
+            let path: Vec<String> = share.path.iter().map(|index| index.to_string()).collect();
+            let suffix = format!("{}-{}-{}", path.join("."), share.share.share.x, member);
+            let path = write_sealed_share(store_path, &suffix, &share.encode(SEALED_SHARE_FORMAT), recipient)?;
+            Ok((share.name(), path))
+        })
+        .collect()
+}
+
+/// Custodian identity given either as an `AGE-SECRET-KEY-1...` string or as the path of an identity file
+fn parse_identity(text: &str) -> Result<Identity, String> {
+    if text.starts_with(IDENTITY_PREFIX) {
+        return text.parse::<Identity>().map_err(|e| format!("Invalid age identity: {}", e));
+    }
+    let file = fs::read_to_string(text).map_err(|e| format!("Failed to read identity file: {}", e))?;
+    file.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with(IDENTITY_PREFIX))
+        .ok_or_else(|| "The identity file holds no AGE-SECRET-KEY".to_string())?
+        .parse::<Identity>()
+        .map_err(|e| format!("Invalid age identity: {}", e))
+}
+
+/// Decrypts a share file with the custodian's identity, returning the share text
+pub fn open_share_file(path: &Path, identity: &str) -> Result<String, String> {
+    let identity = parse_identity(identity)?;
+    let sealed = fs::read(path).map_err(|e| format!("Failed to read share file: {}", e))?;
+    let share = age::decrypt(&identity, &sealed).map_err(|e| format!("Failed to decrypt share file: {}", e))?;
+    String::from_utf8(share).map_err(|_| "The share file does not hold a share".to_string())
+}
+
+/// Share input as displayed: a typed `AGE-SECRET-KEY-1...` identity is masked like a passphrase,
+/// keeping its prefix and length so that the cursor stays in place
+pub fn mask_identity(input: &str) -> String {
+    match input.find(IDENTITY_PREFIX) {
+        Some(start) => {
+            let shown = start + IDENTITY_PREFIX.len();
+            format!("{}{}", &input[..shown], "*".repeat(input[shown..].chars().count()))
+        }
+        None => input.to_string(),
+    }
+}
+
+/// Share text typed by the user, where `<file.age> <identity>` stands for the decrypted file
+pub fn resolve_share_input(input: &str) -> Result<String, String> {
+    let mut parts = input.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(file), identity, None) if file.ends_with(&format!(".{}", SHARE_FILE_EXTENSION)) => {
+            let identity = identity.ok_or("Enter the share file followed by your identity file or AGE-SECRET-KEY")?;
+            open_share_file(Path::new(file), identity)
+        }
+        _ => Ok(input.to_string()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+    use crate::shamir_secret_sharing::{split_secret, ShamirBackend};
+
+    const SECRET: [u8; 32] = [0x42; 32];
+
+    /// Empty directory of its own for each test, under the system temp dir
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bunkeys-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn identity_text(identity: &Identity) -> String {
+        identity.to_string().expose_secret().to_string()
+    }
+
+    fn encoded_shares() -> Vec<EncodedShare> {
+        EncodedShare::wrap_all(split_secret(ShamirBackend::Gf256, &SECRET, 2, 2).unwrap(), 2, &SECRET)
+    }
+
+    #[test]
+    fn parses_recipients_files() {
+        let (alice, bob) = (Identity::generate().to_public(), Identity::generate().to_public());
+        let text = format!("# custodians\n\n{} # alice\n  {}  \n", alice, bob);
+        let recipients = parse_recipients(&text).unwrap();
+        assert_eq!(
+            recipients,
+            [
+                ShareRecipient { key: alice, name: Some("alice".to_string()) },
+                ShareRecipient { key: bob, name: None },
+            ]
+        );
+        assert!(parse_recipients("# nobody\n").is_err());
+        assert!(parse_recipients("age1notakey # alice").unwrap_err().starts_with("Invalid recipient"));
+    }
+
+    #[test]
+    fn seals_and_opens_shares() {
+        let dir = test_dir("seal-shares");
+        let identities = [Identity::generate(), Identity::generate()];
+        let recipients: Vec<ShareRecipient> = identities
+            .iter()
+            .zip(["alice", "bob"])
+            .map(|(identity, name)| ShareRecipient { key: identity.to_public(), name: Some(name.to_string()) })
+            .collect();
+        let shares = encoded_shares();
+        let sealed = seal_shares(&dir.join("store.enc"), &shares, &recipients).unwrap();
+        assert_eq!(sealed[0].0, "1 (alice)");
+        assert_eq!(sealed[0].1, dir.join("store-share-1-alice.age"));
+
+        for ((_, path), (share, identity)) in sealed.iter().zip(shares.iter().zip(&identities)) {
+            let input = format!("{} {}", path.to_string_lossy(), identity_text(identity));
+            let opened = EncodedShare::decode(&resolve_share_input(&input).unwrap()).unwrap();
+            assert_eq!(opened.share, share.share);
+            assert!(opened.label.is_some());
+        }
+
+        // The identity may also be given as the path of an identity file
+        let identity_file = dir.join("alice.txt");
+        fs::write(&identity_file, format!("# created: today\n{}\n", identity_text(&identities[0]))).unwrap();
+        assert!(open_share_file(&sealed[0].1, &identity_file.to_string_lossy()).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_wrong_identity() {
+        let dir = test_dir("wrong-identity");
+        let identity = Identity::generate();
+        let recipient = ShareRecipient { key: identity.to_public(), name: None };
+        let shares = encoded_shares();
+        let sealed = seal_shares(&dir.join("store.enc"), &shares, &[recipient.clone(), recipient.clone()]).unwrap();
+
+        let other = identity_text(&Identity::generate());
+        assert!(open_share_file(&sealed[0].1, &other).unwrap_err().starts_with("Failed to decrypt share file"));
+        assert!(open_share_file(&sealed[0].1, "AGE-SECRET-KEY-1INVALID").unwrap_err().starts_with("Invalid age identity"));
+        assert!(resolve_share_input(&sealed[0].1.to_string_lossy()).is_err());
+        assert!(seal_shares(&dir.join("store.enc"), &shares, &[recipient]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn masks_typed_identities() {
+        assert_eq!(mask_identity("store-share-1.age AGE-SECRET-KEY-1QQ"), "store-share-1.age AGE-SECRET-KEY-***");
+        assert_eq!(mask_identity("store-share-1.age alice.txt"), "store-share-1.age alice.txt");
+        assert_eq!(mask_identity("AGE-SECRET"), "AGE-SECRET");
+    }
+
+    #[test]
+    fn passes_typed_shares_through() {
+        let text = encoded_shares()[0].encode(ShareFormat::Words);
+        assert_eq!(resolve_share_input(&text), Ok(text));
+    }
+}
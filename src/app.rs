@@ -3,7 +3,7 @@ pub mod data;
 mod text_input;
 
 use crossterm::event::KeyEvent;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::Frame;
 use ratatui::prelude::{Color, Line, Span, Style, Stylize};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
@@ -30,6 +30,12 @@ impl App {
         self.state = self.state.handle_key(&mut self.data, key);
     }
 
+    pub fn tick(&mut self) {
+        if let Some(state) = self.state.tick(&mut self.data) {
+            self.state = state;
+        }
+    }
+
     pub fn render(&mut self, frame: &mut Frame) {
         let area = frame.area();
 
@@ -1,5 +1,13 @@
+use std::env;
 use std::path::PathBuf;
+use crate::encrypt::{Cipher, KeySlot};
+use crate::feldman::FeldmanCommitments;
+use crate::policy::PublishedPolicy;
+use crate::sealed_value::{escape_plain, is_sealed, open_value, seal_value, unescape_plain};
+use crate::secret::{SecretBytes, SecretString};
+use crate::split_value::SplitMarker;
 
+#[derive(Default)]
 pub struct AppData {
     // Status
     pub message: Option<String>,
@@ -7,7 +15,19 @@ pub struct AppData {
 
     // Store data
     pub store_path: Option<PathBuf>,
-    pub store_key: Option<Vec<u8>>,
+    /// Key of the slot the store was opened with, the key split by Split Key
+    pub store_key: Option<SecretBytes>,
+    /// Random key encrypting the store contents, wrapped in every key slot
+    pub data_key: Option<SecretBytes>,
+    pub key_slots: Vec<KeySlot>,
+    /// Cipher of the store file, kept when saving unless another one is selected
+    pub store_cipher: Cipher,
+    /// Plaintext label written in the store container header
+    pub store_label: Option<String>,
+    /// Copy of the store file from before the last key rotation, deleted once the new key is recorded
+    pub rotation_backup: Option<PathBuf>,
+    /// Entry values are kept sealed under subkeys of the data key, decrypted only when revealed or edited
+    pub seal_values: bool,
     pub sections: Vec<Section>,
 }
 #[derive(Default)]
@@ -18,22 +38,97 @@ pub struct Section {
 #[derive(Default)]
 pub struct Entry {
     pub(crate) key: String,
-    pub(crate) value: String,
+    pub(crate) value: SecretString,
 }
 
-impl Default for AppData {
-    fn default() -> Self {
-        AppData {
-            message: None,
-            error: None,
-            store_path: None,
-            store_key: None,
-            sections: Vec::new(),
+impl AppData {
+    /// Key encrypting the store contents, None until a store is loaded or initialized
+    pub fn data_key(&self) -> Option<&[u8]> {
+        self.data_key.as_ref().map(SecretBytes::expose)
+    }
+    /// Plaintext of an entry value, opening it if it is sealed
+    pub fn reveal_value(&self, section: usize, entry: usize) -> Result<SecretString, String> {
+        Self::open(self.data_key(), &self.sections[section].entries[entry].value)
+    }
+    /// Sets an entry value, sealing it when values are sealed
+    pub fn set_value(&mut self, section: usize, entry: usize, value: SecretString) -> Result<(), String> {
+        let value = if self.seal_values { Self::seal(self.data_key(), value)? } else { Self::plain(value) };
+        self.sections[section].entries[entry].value = value;
+        Ok(())
+    }
+    /// Seals every value, or opens them all back, keeping the sections untouched on failure
+    pub fn set_seal_values(&mut self, seal: bool) -> Result<(), String> {
+        let data_key = self.data_key();
+        self.sections = self.map_values(|value| {
+            let value = Self::open(data_key, value)?;
+            if seal { Self::seal(data_key, value) } else { Ok(Self::plain(value)) }
+        })?;
+        self.seal_values = seal;
+        Ok(())
+    }
+    /// Sections with every value opened, to be written without encryption
+    pub fn unsealed_sections(&self) -> Result<Vec<Section>, String> {
+        self.map_values(|value| Self::open(self.data_key(), value).map(Self::plain))
+    }
+    /// Sections with the sealed values sealed again under another data key
+    pub fn resealed_sections(&self, data_key: &[u8]) -> Result<Vec<Section>, String> {
+        self.map_values(|value| {
+            if is_sealed(value.expose()) {
+                Self::seal(Some(data_key), Self::open(self.data_key(), value)?)
+            } else {
+                Ok(value.clone())
+            }
+        })
+    }
+    /// Maps every entry value but the split markers, which the split flow stores as they are.
+    /// A typed value reading as a marker is sealed or escaped, so whatever parses as one is a real marker.
+    fn map_values(
+        &self,
+        f: impl Fn(&SecretString) -> Result<SecretString, String>,
+    ) -> Result<Vec<Section>, String> {
+        self.sections
+            .iter()
+            .map(|section| {
+                let entries = section
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        let value = match SplitMarker::parse(entry.value.expose()) {
+                            Some(_) => entry.value.clone(),
+                            None => f(&entry.value)?,
+                        };
+                        Ok(Entry { key: entry.key.clone(), value })
+                    })
+                    .collect::<Result<_, String>>()?;
+                Ok(Section { name: section.name.clone(), entries })
+            })
+            .collect()
+    }
+    fn open(data_key: Option<&[u8]>, value: &SecretString) -> Result<SecretString, String> {
+        match data_key {
+            Some(data_key) if is_sealed(value.expose()) => open_value(data_key, value.expose()),
+            None if is_sealed(value.expose()) => Err("No store key available to open the value".to_string()),
+            _ => Ok(SecretString::from(unescape_plain(value.expose()))),
         }
     }
-}
-
-impl AppData {
+    /// Plaintext value as kept in the store, escaped if it starts like a sealed one
+    fn plain(value: SecretString) -> SecretString {
+        escape_plain(value.expose()).map_or(value, SecretString::new)
+    }
+    /// Empty values hold no secret and are left as they are
+    fn seal(data_key: Option<&[u8]>, value: SecretString) -> Result<SecretString, String> {
+        if value.is_empty() {
+            return Ok(value);
+        }
+        let data_key = data_key.ok_or_else(|| "No store key available to seal the value".to_string())?;
+        seal_value(data_key, value.expose()).map(SecretString::new)
+    }
+    /// Whether any value of the store is sealed
+    pub fn has_sealed_values(&self) -> bool {
+        self.sections
+            .iter()
+            .any(|section| section.entries.iter().any(|entry| is_sealed(entry.value.expose())))
+    }
     pub fn get_store_path_string_as_enc(&self) -> Option<String> {
         self.store_path.clone().map(|p| {
             let mut path = p.to_string_lossy().to_string();
@@ -54,4 +149,25 @@ impl AppData {
             path
         })
     }
+    /// Store file, `store.enc` in the working directory when none was loaded
+    pub fn store_path_or_default(&self) -> PathBuf {
+        self.store_path
+            .clone()
+            .unwrap_or_else(|| env::current_dir().unwrap().join("store.enc"))
+    }
+    /// Feldman commitments file published next to the store, `store.vss` in the current directory by default
+    pub fn commitments_path(&self) -> PathBuf {
+        FeldmanCommitments::path_for_store(&self.store_path_or_default())
+    }
+    pub fn policy_path(&self) -> PathBuf {
+        PublishedPolicy::path_for_store(&self.store_path_or_default())
+    }
+    /// Custodians' age public keys, next to the store
+    pub fn recipients_path(&self) -> PathBuf {
+        self.store_path_or_default().with_file_name("recipients.txt")
+    }
+    /// Request code of a remote unlock, next to the store
+    pub fn unlock_request_path(&self) -> PathBuf {
+        self.store_path_or_default().with_file_name("unlock-request.txt")
+    }
 }
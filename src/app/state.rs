@@ -3,17 +3,47 @@ use enum_dispatch::enum_dispatch;
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use crate::app::data::AppData;
+use crate::app::state::ceremony::CeremonyState;
+use crate::app::state::combine_value::CombineValueState;
+use crate::app::state::derive_key::DeriveKeyState;
 use crate::app::state::edit_store::EditStoreState;
+use crate::app::state::extend_shares::ExtendSharesState;
 use crate::app::state::init_store::InitStoreState;
+use crate::app::state::key_slots::KeySlotsState;
 use crate::app::state::load_store::LoadStoreState;
 use crate::app::state::main_menu::MainMenuState;
+use crate::app::state::passphrase_store::PassphraseStoreState;
+use crate::app::state::policy_split::PolicySplitState;
+use crate::app::state::recovery_drill::RecoveryDrillState;
+use crate::app::state::seal_shares::SealSharesState;
+use crate::app::state::renew_shares::RenewSharesState;
+use crate::app::state::rotate_key::RotateKeyState;
 use crate::app::state::save_store::SaveStoreState;
+use crate::app::state::slip39_shares::Slip39SharesState;
+use crate::app::state::split_key::SplitKeyState;
+use crate::app::state::split_value::SplitValueState;
+use crate::app::state::verify_share::VerifyShareState;
 
 pub mod init_store;
 pub mod load_store;
 pub mod edit_store;
 pub mod main_menu;
 pub mod save_store;
+pub mod split_key;
+pub mod slip39_shares;
+pub mod verify_share;
+pub mod extend_shares;
+pub mod renew_shares;
+pub mod policy_split;
+pub mod ceremony;
+pub mod recovery_drill;
+pub mod seal_shares;
+pub mod split_value;
+pub mod combine_value;
+pub mod derive_key;
+pub mod passphrase_store;
+pub mod key_slots;
+pub mod rotate_key;
 
 
 #[enum_dispatch(AppState)]
@@ -22,6 +52,10 @@ pub trait AppStateEvents {
     fn get_footer(&self, data: &AppData) -> &'static str;
     fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState;
     fn render(&self, data: &AppData, frame: &mut Frame, area: Rect);
+    /// Called between key presses, for states waiting on background work: returns the next state once it is done
+    fn tick(&self, _data: &mut AppData) -> Option<AppState> {
+        None
+    }
 }
 
 #[enum_dispatch]
@@ -32,6 +66,21 @@ pub enum AppState {
     LoadStore(LoadStoreState),
     EditStore(EditStoreState),
     SaveStore(SaveStoreState),
+    SplitKey(SplitKeyState),
+    Slip39Shares(Slip39SharesState),
+    VerifyShare(VerifyShareState),
+    ExtendShares(ExtendSharesState),
+    RenewShares(RenewSharesState),
+    PolicySplit(PolicySplitState),
+    Ceremony(CeremonyState),
+    RecoveryDrill(RecoveryDrillState),
+    SealShares(SealSharesState),
+    SplitValue(SplitValueState),
+    CombineValue(CombineValueState),
+    DeriveKey(DeriveKeyState),
+    PassphraseStore(PassphraseStoreState),
+    KeySlots(KeySlotsState),
+    RotateKey(RotateKeyState),
 }
 
 impl Default for AppState {
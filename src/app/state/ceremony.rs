@@ -0,0 +1,319 @@
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::policy::PolicyShare;
+use crate::share_encoding::{EncodedShare, ShareFormat};
+use crossterm::event::{KeyCode, KeyEvent};
+use rand::rngs::OsRng;
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Number of words a custodian re-types to prove they recorded their share
+const CONFIRMATION_WORDS: usize = 3;
+
+/// How the shares of a new split are handed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum DistributionMode {
+    #[default]
+    ShowAll,
+    Ceremony,
+    CeremonyWithCheck,
+    Sealed,
+}
+
+impl DistributionMode {
+    pub fn to_string(self) -> (&'static str, &'static str) {
+        match self {
+            DistributionMode::ShowAll => ("Show all shares", "List every share on a single screen"),
+            DistributionMode::Ceremony => ("Ceremony", "Show the shares one custodian at a time"),
+            DistributionMode::CeremonyWithCheck => (
+                "Ceremony with check",
+                "One custodian at a time, each re-types a few words of their share",
+            ),
+            DistributionMode::Sealed => (
+                "Encrypt to recipients",
+                "Write each share to a file only its custodian's age X25519 key opens",
+            ),
+        }
+    }
+}
+
+/// A share to hand over, encoded in every format
+#[derive(Debug, Clone, PartialEq)]
+pub struct CeremonyShare {
+    pub name: String,
+    pub label: Option<String>,
+    encodings: Vec<(ShareFormat, String)>,
+}
+
+impl CeremonyShare {
+    fn encode(&self, format: ShareFormat) -> &str {
+        self.encodings.iter().find(|(f, _)| *f == format).map(|(_, encoded)| encoded.as_str()).unwrap_or_default()
+    }
+}
+
+impl From<&EncodedShare> for CeremonyShare {
+    fn from(share: &EncodedShare) -> Self {
+        CeremonyShare {
+            name: format!("{}/{}", share.share.x, share.share_count),
+            label: share.label.clone(),
+            encodings: ShareFormat::iter().map(|format| (format, share.encode(format))).collect(),
+        }
+    }
+}
+
+impl From<&PolicyShare> for CeremonyShare {
+    fn from(share: &PolicyShare) -> Self {
+        CeremonyShare {
+            name: share.name(),
+            label: share.share.label.clone(),
+            encodings: ShareFormat::iter().map(|format| (format, share.encode(format))).collect(),
+        }
+    }
+}
+
+/// Words of an encoded share, hex and base32 being cut in groups of 4 characters
+fn confirmation_words(encoded: &str) -> Vec<String> {
+    if encoded.contains(' ') {
+        return encoded.split_whitespace().map(str::to_string).collect();
+    }
+    let chars: Vec<char> = encoded.chars().collect();
+    chars.chunks(4).map(|chunk| chunk.iter().collect()).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppCeremonyStep {
+    /// Blank screen while the next custodian takes the seat
+    Handover(TextInput),
+    Reveal,
+    Confirm {
+        /// 0-based positions of the words to re-type
+        positions: Vec<usize>,
+        input: TextInput,
+    },
+    Summary,
+}
+
+/// Walks through the shares of a split one custodian at a time,
+/// so that nobody sees a share other than their own
+#[derive(Debug, Clone, PartialEq)]
+pub struct CeremonyState {
+    /// Menu entry to go back to
+    action: MainMenuAction,
+    check: bool,
+    format: ShareFormat,
+    shares: Vec<CeremonyShare>,
+    /// Custodian of each share handed over so far, the last one holds the current share
+    custodians: Vec<String>,
+    step: AppCeremonyStep,
+}
+
+impl CeremonyState {
+    pub fn new(action: MainMenuAction, mode: DistributionMode, shares: Vec<CeremonyShare>) -> Self {
+        let input = TextInput::new(shares[0].label.clone().unwrap_or_default());
+        Self {
+            action,
+            check: mode == DistributionMode::CeremonyWithCheck,
+            format: ShareFormat::default(),
+            shares,
+            custodians: Vec::new(),
+            step: AppCeremonyStep::Handover(input),
+        }
+    }
+    fn with_step(&self, step: AppCeremonyStep) -> Self {
+        Self {
+            step,
+            ..self.clone()
+        }
+    }
+
+    fn current(&self) -> &CeremonyShare {
+        &self.shares[self.custodians.len().saturating_sub(1)]
+    }
+
+    fn menu(&self, data: &AppData) -> AppState {
+        MainMenuState::return_to(self.action.clone(), data)
+    }
+
+    fn try_reveal(&self, data: &mut AppData, input: &TextInput) -> AppState {
+        let custodian = input.get_text().trim();
+        if custodian.is_empty() {
+            data.error = Some("Enter the name of the custodian receiving this share".to_string());
+            return self.clone().into();
+        }
+        let mut state = self.with_step(AppCeremonyStep::Reveal);
+        state.custodians.push(custodian.to_string());
+        state.into()
+    }
+
+    fn acknowledge(&self) -> AppState {
+        if self.check {
+            let words = confirmation_words(self.current().encode(self.format));
+            let count = CONFIRMATION_WORDS.min(words.len());
+            let mut positions = rand::seq::index::sample(&mut OsRng, words.len(), count).into_vec();
+            positions.sort_unstable();
+            return self.with_step(AppCeremonyStep::Confirm { positions, input: TextInput::default() }).into();
+        }
+        self.next()
+    }
+
+    fn try_confirm(&self, data: &mut AppData, positions: &[usize], input: &TextInput) -> AppState {
+        let words = confirmation_words(self.current().encode(self.format));
+        let typed: Vec<String> = input.get_text().split_whitespace().map(str::to_lowercase).collect();
+        let expected: Vec<String> = positions.iter().map(|&position| words[position].to_lowercase()).collect();
+        if typed != expected {
+            data.error = Some("The words do not match, check the recorded share".to_string());
+            return self.with_step(AppCeremonyStep::Reveal).into();
+        }
+        data.message = Some(format!("Share {} recorded by {}", self.current().name, self.custodians.last().unwrap()));
+        self.next()
+    }
+
+    /// Clears the screen for the next custodian, or ends with the summary
+    fn next(&self) -> AppState {
+        match self.shares.get(self.custodians.len()) {
+            Some(share) => self
+                .with_step(AppCeremonyStep::Handover(TextInput::new(share.label.clone().unwrap_or_default())))
+                .into(),
+            None => self.with_step(AppCeremonyStep::Summary).into(),
+        }
+    }
+}
+
+impl AppStateEvents for CeremonyState {
+    fn get_title(&self, _data: &AppData) -> String {
+        let progress = format!("{}/{}", self.custodians.len().max(1), self.shares.len());
+        match &self.step {
+            AppCeremonyStep::Handover(_) => {
+                format!("Share Ceremony - Next Custodian {}/{}", self.custodians.len() + 1, self.shares.len())
+            }
+            AppCeremonyStep::Reveal => format!("Share Ceremony - Share {}", progress),
+            AppCeremonyStep::Confirm { .. } => format!("Share Ceremony - Check Share {}", progress),
+            AppCeremonyStep::Summary => "Share Ceremony - Summary".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppCeremonyStep::Handover(_) => "[Esc: Abort] [⏎ Enter: Reveal Share]",
+            AppCeremonyStep::Reveal => "[Esc: Abort] [⏎ Enter: I Recorded This Share] [Tab: Change Format]",
+            AppCeremonyStep::Confirm { .. } => "[Esc: Abort] [⏎ Enter: Check Words]",
+            AppCeremonyStep::Summary => "[Esc / ⏎ Enter: Back to menu]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        if key.code == KeyCode::Esc && self.step != AppCeremonyStep::Summary {
+            data.error = Some(format!(
+                "Ceremony aborted after {} of {} share(s), split the key again",
+                self.custodians.len(),
+                self.shares.len()
+            ));
+            return self.menu(data);
+        }
+        match self.step.clone() {
+            AppCeremonyStep::Handover(input) => match key.code {
+                KeyCode::Char(c) => self.with_step(AppCeremonyStep::Handover(input.with_insert_char(c))).into(),
+                KeyCode::Backspace => self.with_step(AppCeremonyStep::Handover(input.with_delete_char())).into(),
+                KeyCode::Left => self.with_step(AppCeremonyStep::Handover(input.with_move_left())).into(),
+                KeyCode::Right => self.with_step(AppCeremonyStep::Handover(input.with_move_right())).into(),
+                KeyCode::Enter => self.try_reveal(data, &input),
+                _ => self.clone().into(),
+            },
+            AppCeremonyStep::Reveal => match key.code {
+                KeyCode::Tab => {
+                    let formats: Vec<_> = ShareFormat::iter().collect();
+                    let current_idx = formats.iter().position(|f| f == &self.format).unwrap();
+                    Self {
+                        format: formats[(current_idx + 1) % formats.len()],
+                        ..self.clone()
+                    }
+                    .into()
+                }
+                KeyCode::Enter => self.acknowledge(),
+                _ => self.clone().into(),
+            },
+            AppCeremonyStep::Confirm { positions, input } => match key.code {
+                KeyCode::Char(c) => self.with_step(AppCeremonyStep::Confirm { positions, input: input.with_insert_char(c) }).into(),
+                KeyCode::Backspace => self.with_step(AppCeremonyStep::Confirm { positions, input: input.with_delete_char() }).into(),
+                KeyCode::Left => self.with_step(AppCeremonyStep::Confirm { positions, input: input.with_move_left() }).into(),
+                KeyCode::Right => self.with_step(AppCeremonyStep::Confirm { positions, input: input.with_move_right() }).into(),
+                KeyCode::Enter => self.try_confirm(data, &positions, &input),
+                _ => self.clone().into(),
+            },
+            AppCeremonyStep::Summary => match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, input) = match &self.step {
+            AppCeremonyStep::Handover(input) => (
+                format!(
+                    "Only the custodian of share {} may look at the screen now. Enter their name:",
+                    self.shares[self.custodians.len()].name
+                ),
+                input,
+            ),
+            AppCeremonyStep::Reveal => {
+                let share = self.current();
+                let text = vec![
+                    Line::from(format!("Share {} for {}", share.name, self.custodians.last().unwrap()))
+                        .style(Style::default().fg(Color::Green)),
+                    Line::from(format!("Format: {}", self.format.name())),
+                    Line::from(""),
+                    Line::from(Span::styled(share.encode(self.format), Style::default().fg(Color::Yellow))),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        "⚠ IMPORTANT: Write this share down, the screen is cleared before the next custodian.",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    )),
+                ];
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+            AppCeremonyStep::Confirm { positions, input } => (
+                format!(
+                    "Type words {} of the share you recorded, separated by spaces:",
+                    positions.iter().map(|position| format!("#{}", position + 1)).collect::<Vec<_>>().join(", ")
+                ),
+                input,
+            ),
+            AppCeremonyStep::Summary => {
+                let mut text = vec![
+                    Line::from(format!("{} share(s) handed over:", self.shares.len()))
+                        .style(Style::default().fg(Color::Green)),
+                    Line::from(""),
+                ];
+                for (share, custodian) in self.shares.iter().zip(&self.custodians) {
+                    text.push(Line::from(format!("Share {} → {}", share.name, custodian)));
+                }
+                text.push(Line::from(""));
+                text.push(Line::from(Span::styled(
+                    "Keep this summary to know whom to ask for each share.",
+                    Style::default().fg(Color::Gray),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::age_shares::{mask_identity, resolve_share_input};
+use crate::app::data::AppData;
+use crate::app::state::edit_store::EditStoreState;
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::share_encoding::EncodedShare;
+use crate::split_value::{recombine_value, SplitMarker};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+/// Collects the shares of a split entry value and puts the recombined value back in the store
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombineValueState {
+    /// Section and entry indices of the value
+    section: usize,
+    entry: usize,
+    /// Editor position to go back to
+    flattened_index: usize,
+    marker: SplitMarker,
+    shares: Vec<EncodedShare>,
+    input: TextInput,
+}
+
+impl CombineValueState {
+    pub fn new(section: usize, entry: usize, flattened_index: usize, marker: SplitMarker) -> Self {
+        Self {
+            section,
+            entry,
+            flattened_index,
+            marker,
+            shares: Vec::new(),
+            input: TextInput::default(),
+        }
+    }
+    fn with_input(&self, input: TextInput) -> Self {
+        Self {
+            input,
+            ..self.clone()
+        }
+    }
+
+    fn try_add_share(&self, data: &mut AppData) -> AppState {
+        if self.input.get_text().is_empty() {
+            return self.clone().into();
+        }
+        let share = match resolve_share_input(self.input.get_text()).and_then(|text| EncodedShare::decode(&text)) {
+            Ok(share) => share,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        if !self.marker.covers(&share) {
+            data.error = Some(format!("Share {} was not split from this value", share.name()));
+            return self.clone().into();
+        }
+        if self.shares.iter().any(|other| other.share.x == share.share.x) {
+            data.error = Some(format!("Share {} was already entered", share.share.x));
+            return self.clone().into();
+        }
+        let mut shares = self.shares.clone();
+        shares.push(share);
+
+        let threshold = self.marker.threshold as usize;
+        if shares.len() < threshold {
+            data.message = Some(format!("Share accepted, {} more needed", threshold - shares.len()));
+            return Self {
+                shares,
+                input: TextInput::default(),
+                ..self.clone()
+            }
+            .into();
+        }
+        match recombine_value(&self.marker, &shares) {
+            Ok(value) => match data.set_value(self.section, self.entry, value) {
+                Ok(()) => {
+                    let key = &data.sections[self.section].entries[self.entry].key;
+                    data.message = Some(format!("Value of '{}' recombined, save the store to keep it", key));
+                    EditStoreState::at(self.flattened_index).into()
+                }
+                Err(e) => {
+                    data.error = Some(e);
+                    self.with_input(TextInput::default()).into()
+                }
+            },
+            Err(e) => {
+                data.error = Some(e);
+                self.with_input(TextInput::default()).into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for CombineValueState {
+    fn get_title(&self, _data: &AppData) -> String {
+        format!("Recombine Value - Enter Share {}/{}", self.shares.len() + 1, self.marker.threshold)
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        "[Esc: Cancel] [⏎ Enter: Add Share]"
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match key.code {
+            KeyCode::Char(c) => self.with_input(self.input.with_insert_char(c)).into(),
+            KeyCode::Backspace => self.with_input(self.input.with_delete_char()).into(),
+            KeyCode::Left => self.with_input(self.input.with_move_left()).into(),
+            KeyCode::Right => self.with_input(self.input.with_move_right()).into(),
+            KeyCode::Enter => self.try_add_share(data),
+            KeyCode::Esc => EditStoreState::at(self.flattened_index).into(),
+            _ => self.clone().into(),
+        }
+    }
+
+    fn render(&self, data: &AppData, frame: &mut Frame, area: Rect) {
+        let key = &data.sections[self.section].entries[self.entry].key;
+        let text = vec![
+            Line::from(format!(
+                "Enter a share of '{}' (words, hex, base32, or '<file.age> <identity file>') - {} collected, {} more needed:",
+                key,
+                self.shares.len(),
+                self.marker.threshold as usize - self.shares.len()
+            )),
+            Line::from(""),
+            Line::from(Span::styled(mask_identity(self.input.get_text()), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + self.input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::app::data::AppData;
+use crate::app::state::key_slots::KeySlotsState;
+use crate::app::state::load_store::LoadStoreState;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::encrypt::{Cipher, Kdf, KeySlot, SlotKind, UnlockInput};
+use crate::secret::{SecretBytes, SecretString};
+use crate::store::{LoadedStore, Store};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// What the derived key is for
+#[derive(Debug, Clone, PartialEq)]
+pub enum DerivedKeyUse {
+    /// Protect a new empty store
+    InitStore,
+    /// Decrypt the store at `data.store_path`
+    LoadStore,
+    /// Add a passphrase slot to the current store
+    AddSlot,
+}
+
+/// What the thread produces
+enum Derived {
+    /// New passphrase slot wrapping `data_key`, with the key opening it
+    Slot {
+        slot: KeySlot,
+        slot_key: SecretBytes,
+        data_key: SecretBytes,
+    },
+    /// Store opened by one of its passphrase slots
+    Store(LoadedStore),
+}
+
+/// Outcome of the derivation, or its error, once the thread is done
+type DerivationResult = Arc<Mutex<Option<Result<Derived, String>>>>;
+
+/// Key derivation running on its own thread, the result is left in the shared slot
+#[derive(Clone)]
+struct DerivationJob {
+    result: DerivationResult,
+}
+
+impl DerivationJob {
+    fn start(work: impl FnOnce() -> Result<Derived, String> + Send + 'static) -> Self {
+        let result: DerivationResult = Arc::new(Mutex::new(None));
+        let slot = result.clone();
+        thread::spawn(move || {
+            let derived = work();
+            *slot.lock().unwrap() = Some(derived);
+        });
+        DerivationJob { result }
+    }
+
+    fn take_result(&self) -> Option<Result<Derived, String>> {
+        self.result.lock().unwrap().take()
+    }
+}
+
+impl PartialEq for DerivationJob {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.result, &other.result)
+    }
+}
+
+impl Debug for DerivationJob {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DerivationJob").finish_non_exhaustive()
+    }
+}
+
+/// Waits for a passphrase to be stretched into a slot key, showing progress meanwhile
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeriveKeyState {
+    /// Derivations being run, one per passphrase slot tried
+    kdfs: Vec<Kdf>,
+    purpose: DerivedKeyUse,
+    started: Instant,
+    job: DerivationJob,
+}
+
+impl DeriveKeyState {
+    /// Stretches a new passphrase and wraps the data key in a passphrase slot with it
+    pub fn new_slot(kdf: Kdf, passphrase: SecretString, data_key: SecretBytes, purpose: DerivedKeyUse) -> Self {
+        let slot_kdf = kdf.clone();
+        let job = DerivationJob::start(move || {
+            let slot_key = SecretBytes::new(slot_kdf.derive_key(passphrase.expose().as_bytes())?);
+            let slot = KeySlot::wrap(SlotKind::Passphrase, slot_kdf, slot_key.expose(), data_key.expose())?;
+            Ok(Derived::Slot { slot, slot_key, data_key })
+        });
+        Self {
+            kdfs: vec![kdf],
+            purpose,
+            started: Instant::now(),
+            job,
+        }
+    }
+
+    /// Loads the store at `path`, trying the passphrase on every passphrase slot
+    pub fn load(kdfs: Vec<Kdf>, passphrase: SecretString, path: PathBuf) -> Self {
+        let job = DerivationJob::start(move || {
+            let unlock = UnlockInput::Passphrase(passphrase.expose().to_string());
+            Store::load(Some(unlock), path).map(Derived::Store)
+        });
+        Self {
+            kdfs,
+            purpose: DerivedKeyUse::LoadStore,
+            started: Instant::now(),
+            job,
+        }
+    }
+
+    fn menu(&self) -> AppState {
+        match self.purpose {
+            DerivedKeyUse::InitStore => MainMenuState::new(MainMenuAction::InitStore).into(),
+            DerivedKeyUse::LoadStore => MainMenuState::new(MainMenuAction::LoadStore).into(),
+            DerivedKeyUse::AddSlot => KeySlotsState::default().into(),
+        }
+    }
+
+    fn use_result(&self, data: &mut AppData, derived: Derived) -> AppState {
+        match derived {
+            Derived::Store(loaded) => LoadStoreState::apply_loaded(data, Ok(loaded)),
+            Derived::Slot { slot, .. } if self.purpose == DerivedKeyUse::AddSlot => {
+                KeySlotsState::add_slot(data, slot);
+                KeySlotsState::at_last(data).into()
+            }
+            Derived::Slot { slot, slot_key, data_key } => {
+                data.store_key = Some(slot_key);
+                data.data_key = Some(data_key);
+                data.key_slots = vec![slot];
+                data.rotation_backup = None;
+                data.store_cipher = Cipher::default();
+                data.sections = Vec::new();
+                data.seal_values = false;
+                data.store_label = None;
+                data.message = Some("Store initialized, it opens with your passphrase".to_string());
+                MainMenuState::new(MainMenuAction::EditStore).into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for DeriveKeyState {
+    fn get_title(&self, _data: &AppData) -> String {
+        "Deriving Key".to_string()
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        "[Esc: Abandon]"
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match key.code {
+            KeyCode::Esc => {
+                data.error = Some("Key derivation abandoned".to_string());
+                self.menu()
+            }
+            _ => self.clone().into(),
+        }
+    }
+
+    fn tick(&self, data: &mut AppData) -> Option<AppState> {
+        match self.job.take_result()? {
+            Ok(derived) => Some(self.use_result(data, derived)),
+            Err(e) => {
+                data.error = Some(e);
+                Some(self.menu())
+            }
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let elapsed = self.started.elapsed();
+        let spinner = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+        let task = match self.kdfs.len() {
+            1 => "Stretching the passphrase into the slot key…".to_string(),
+            slots => format!("Trying the passphrase on {} passphrase slots…", slots),
+        };
+        let mut text = vec![
+            Line::from(Span::styled(
+                format!("{} {} {:.1}s", spinner, task, elapsed.as_secs_f32()),
+                Style::default().fg(Color::Yellow),
+            )),
+            Line::from(""),
+        ];
+        text.extend(self.kdfs.iter().map(|kdf| Line::from(KeySlotsState::describe_kdf(kdf))));
+        text.push(Line::from(Span::styled(
+            "The costs are stored in the file header, this takes as long at every unlock.",
+            Style::default().fg(Color::Gray),
+        )));
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+    }
+}
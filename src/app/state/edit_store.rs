@@ -1,8 +1,13 @@
 use crate::app::data::{AppData, Entry, Section};
 use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::combine_value::CombineValueState;
+use crate::app::state::split_value::SplitValueState;
 use crate::app::state::AppStateEvents;
 use crate::app::text_input::TextInput;
 use crate::app::AppState;
+use crate::sealed_value::{is_sealed, unescape_plain};
+use crate::secret::SecretString;
+use crate::split_value::SplitMarker;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::Rect;
 use ratatui::prelude::Position;
@@ -10,6 +15,7 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{List, ListItem};
 use ratatui::Frame;
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct EditStoreState {
@@ -18,6 +24,8 @@ pub struct EditStoreState {
     is_editing: bool,
     was_created: bool,
     input: TextInput,
+    /// Plaintext of the selected sealed value while it is revealed, dropped on the next key
+    revealed: Option<SecretString>,
 }
 
 enum EditStoreSelection {
@@ -28,17 +36,38 @@ enum EditStoreSelection {
     AddSection,
 }
 impl EditStoreSelection {
-    pub fn get_text(&self, data: &AppData) -> String {
+    /// Text to edit, sealed values are opened
+    pub fn get_text(&self, data: &AppData) -> Result<SecretString, String> {
         match self {
-            EditStoreSelection::Section(si) => data.sections[*si].name.clone(),
-            EditStoreSelection::EntryKey(si, ei) => data.sections[*si].entries[*ei].key.clone(),
-            EditStoreSelection::EntryValue(si, ei) => data.sections[*si].entries[*ei].value.clone(),
-            _ => "".to_string(),
+            EditStoreSelection::Section(si) => Ok(SecretString::from(data.sections[*si].name.as_str())),
+            EditStoreSelection::EntryKey(si, ei) => Ok(SecretString::from(data.sections[*si].entries[*ei].key.as_str())),
+            EditStoreSelection::EntryValue(si, ei) => data.reveal_value(*si, *ei),
+            _ => Ok(SecretString::default()),
         }
     }
 }
 
 impl EditStoreState {
+    /// Editor with the given item selected, when coming back from another screen
+    pub fn at(flattened_index: usize) -> Self {
+        Self {
+            flattened_index,
+            ..Self::default()
+        }
+    }
+
+    /// Value as displayed, split values are shown by their threshold rather than their marker
+    /// and sealed values are hidden
+    fn display_value(value: &SecretString) -> Cow<'_, str> {
+        if is_sealed(value.expose()) {
+            return Cow::Borrowed("🔒 sealed");
+        }
+        match SplitMarker::parse(value.expose()) {
+            Some(marker) => Cow::Owned(format!("🧩 split, {} of {} shares", marker.threshold, marker.share_count)),
+            None => Cow::Borrowed(unescape_plain(value.expose())),
+        }
+    }
+
     pub fn flattened_len(&self, data: &AppData) -> usize {
         data.sections
             .iter()
@@ -63,7 +92,7 @@ impl EditStoreState {
     fn create_entry_line<'l>(
         &'l self,
         key: &'l String,
-        value: &'l String,
+        value: Cow<'l, str>,
         key_selected: bool,
         value_selected: bool,
     ) -> ListItem<'l> {
@@ -133,10 +162,17 @@ impl AppStateEvents for EditStoreState {
                 "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Rename] [⌫: Delete Entry]"
             }
             EditStoreSelection::EntryValue(si, ei) => {
-                if data.sections[si].entries[ei].value.is_empty() {
+                let value = &data.sections[si].entries[ei].value;
+                if value.is_empty() {
                     "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Edit] [⌫: Empty Value]"
+                } else if SplitMarker::parse(value.expose()).is_some() {
+                    "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Recombine] [⌫: Delete Entry]"
+                } else if is_sealed(value.expose()) && self.revealed.is_some() {
+                    "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Edit] [r: Hide] [s: Split] [⌫: Delete Entry]"
+                } else if is_sealed(value.expose()) {
+                    "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Edit] [r: Reveal] [s: Split] [⌫: Delete Entry]"
                 } else {
-                    "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Edit] [⌫: Delete Entry]"
+                    "[Esc: Save And Exit] [↑↓←→: Navigate] [⏎: Edit] [s: Split] [⌫: Delete Entry]"
                 }
             }
             EditStoreSelection::AddEntry(_si) => {
@@ -150,6 +186,7 @@ impl AppStateEvents for EditStoreState {
 
     fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
         let mut new_state = self.clone();
+        new_state.revealed = None;
         let max_index = new_state.flattened_len(data);
         let selected = self.get_selected_item(data);
 
@@ -159,7 +196,7 @@ impl AppStateEvents for EditStoreState {
             }
             KeyCode::Up if { !self.is_editing } => {
                 if new_state.flattened_index <= 1 {
-                    new_state.flattened_index = max_index - 2 + new_state.flattened_index;
+                    new_state.flattened_index += max_index - 2;
                 } else {
                     new_state.flattened_index -= 2;
                 }
@@ -167,14 +204,32 @@ impl AppStateEvents for EditStoreState {
             KeyCode::Down if { !self.is_editing } => {
                 new_state.flattened_index = (new_state.flattened_index + 2) % max_index;
             }
-            KeyCode::Left if { !self.is_editing } => {
-                if new_state.flattened_index % 2 == 1 {
-                    new_state.flattened_index -= 1;
+            KeyCode::Left if { !self.is_editing && new_state.flattened_index % 2 == 1 } => {
+                new_state.flattened_index -= 1;
+            }
+            KeyCode::Right if { !self.is_editing && new_state.flattened_index.is_multiple_of(2) } => {
+                new_state.flattened_index += 1;
+            }
+            KeyCode::Char('r') if { !self.is_editing && self.revealed.is_none() } => {
+                if let EditStoreSelection::EntryValue(si, ei) = selected
+                    && is_sealed(data.sections[si].entries[ei].value.expose())
+                {
+                    match data.reveal_value(si, ei) {
+                        Ok(value) => new_state.revealed = Some(value),
+                        Err(e) => data.error = Some(e),
+                    }
                 }
             }
-            KeyCode::Right if { !self.is_editing } => {
-                if new_state.flattened_index % 2 == 0 {
-                    new_state.flattened_index += 1;
+            KeyCode::Char('s') if { !self.is_editing } => {
+                if let EditStoreSelection::EntryValue(si, ei) = selected {
+                    let value = &data.sections[si].entries[ei].value;
+                    if SplitMarker::parse(value.expose()).is_some() {
+                        data.error = Some("This value is already split, recombine it first".to_string());
+                    } else if value.is_empty() {
+                        data.error = Some("An empty value cannot be split".to_string());
+                    } else {
+                        return SplitValueState::new(si, ei, self.flattened_index).into();
+                    }
                 }
             }
             KeyCode::Backspace if { !self.is_editing } => match selected {
@@ -210,12 +265,17 @@ impl AppStateEvents for EditStoreState {
                             "Emptied entry '{}'.",
                             data.sections[si].entries[ei].key
                         ));
-                        data.sections[si].entries[ei].value = String::new();
+                        data.sections[si].entries[ei].value = SecretString::default();
                     }
                 }
                 _ => {}
             },
             KeyCode::Enter if { !self.is_editing } => {
+                if let EditStoreSelection::EntryValue(si, ei) = selected
+                    && let Some(marker) = SplitMarker::parse(data.sections[si].entries[ei].value.expose())
+                {
+                    return CombineValueState::new(si, ei, self.flattened_index, marker).into();
+                }
                 match selected {
                     EditStoreSelection::Section(si) => {
                         data.message =
@@ -251,8 +311,14 @@ impl AppStateEvents for EditStoreState {
                         data.message = Some("Entry created. Please type in its name".to_string());
                     }
                 }
+                match selected.get_text(data) {
+                    Ok(text) => new_state.input = TextInput::from_secret(text),
+                    Err(e) => {
+                        data.error = Some(e);
+                        return self.clone().into();
+                    }
+                }
                 new_state.is_editing = true;
-                new_state.input = TextInput::new(selected.get_text(data));
             }
 
             KeyCode::Char(c) if { self.is_editing } => {
@@ -282,7 +348,7 @@ impl AppStateEvents for EditStoreState {
                             ));
                             return self.clone().into();
                         }
-                        data.sections[si].name = self.input.get_text().clone();
+                        data.sections[si].name = self.input.get_text().to_string();
                         data.message = Some(format!(
                             "Section '{}' successfully renamed.",
                             self.input.get_text()
@@ -301,14 +367,17 @@ impl AppStateEvents for EditStoreState {
                             ));
                             return self.clone().into();
                         }
-                        data.sections[si].entries[ei].key = self.input.get_text().clone();
+                        data.sections[si].entries[ei].key = self.input.get_text().to_string();
                         data.message = Some(format!(
                             "Entry '{}' successfully renamed.",
                             self.input.get_text()
                         ));
                     }
                     EditStoreSelection::EntryValue(si, ei) => {
-                        data.sections[si].entries[ei].value = self.input.get_text().clone();
+                        if let Err(e) = data.set_value(si, ei, SecretString::from(self.input.get_text())) {
+                            data.error = Some(e);
+                            return self.clone().into();
+                        }
                         data.message = Some(format!(
                             "Entry '{}' successfully updated.",
                             data.sections[si].entries[ei].key
@@ -322,12 +391,10 @@ impl AppStateEvents for EditStoreState {
                 new_state.is_editing = false;
                 if self.was_created {
                     // Delete entry/section if it was created
-                    return new_state
-                        .handle_key(
-                            data,
-                            KeyEvent::new(KeyCode::Backspace, KeyModifiers::empty()),
-                        )
-                        .into();
+                    return new_state.handle_key(
+                        data,
+                        KeyEvent::new(KeyCode::Backspace, KeyModifiers::empty()),
+                    );
                 }
             }
             _ => {}
@@ -370,7 +437,7 @@ impl AppStateEvents for EditStoreState {
                                 area.y + current_line,
                             ));
                         }
-                        self.create_entry_line(&entry.key, &entry.value, true, false)
+                        self.create_entry_line(&entry.key, Self::display_value(&entry.value), true, false)
                     }
                     EditStoreSelection::EntryValue(sj, ej) if sj == si && ej == ei => {
                         if self.is_editing {
@@ -381,11 +448,18 @@ impl AppStateEvents for EditStoreState {
                                 area.y + current_line,
                             ));
                         }
-                        self.create_entry_line(&entry.key, &entry.value, false, true)
+                        let value = match &self.revealed {
+                            Some(revealed) => Cow::Borrowed(revealed.expose()),
+                            None => Self::display_value(&entry.value),
+                        };
+                        self.create_entry_line(&entry.key, value, false, true)
                     }
                     _ => {
                         is_selected = false;
-                        ListItem::new(format!("  📄 {}: {}", entry.key, entry.value))
+                        ListItem::new(Line::from(vec![
+                            Span::raw(format!("  📄 {}: ", entry.key)),
+                            Span::raw(Self::display_value(&entry.value)),
+                        ]))
                     }
                 };
                 if is_selected {
@@ -0,0 +1,243 @@
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::share_encoding::{EncodedShare, ShareFormat};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppExtendSharesStep {
+    EnterShare {
+        shares: Vec<EncodedShare>,
+        input: TextInput,
+    },
+    EnterNewShares {
+        shares: Vec<EncodedShare>,
+        input: TextInput,
+    },
+    ShowShares {
+        format: ShareFormat,
+        new_shares: Vec<EncodedShare>,
+    },
+}
+
+/// Issues shares for new custodians from `threshold` existing shares, without re-splitting the key
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendSharesState {
+    step: AppExtendSharesStep,
+}
+
+impl Default for ExtendSharesState {
+    fn default() -> Self {
+        Self::new_share(Vec::new(), TextInput::default())
+    }
+}
+
+impl ExtendSharesState {
+    fn new_share(shares: Vec<EncodedShare>, input: TextInput) -> Self {
+        Self {
+            step: AppExtendSharesStep::EnterShare { shares, input },
+        }
+    }
+    fn new_new_shares(shares: Vec<EncodedShare>, input: TextInput) -> Self {
+        Self {
+            step: AppExtendSharesStep::EnterNewShares { shares, input },
+        }
+    }
+
+    /// Parses `<index>[: <label>], ...`, e.g. `6: Alice, 7: Bob, 8`
+    fn parse_new_shares(text: &str) -> Result<Vec<(u8, Option<String>)>, String> {
+        let error = || "Invalid new shares. Expected e.g. '6: Alice, 7: Bob, 8'".to_string();
+        text.split(',')
+            .map(|new_share| {
+                let (index, label) = match new_share.split_once(':') {
+                    Some((index, label)) => (index, Some(label.trim().to_string()).filter(|l| !l.is_empty())),
+                    None => (new_share, None),
+                };
+                Ok((index.trim().parse::<u8>().map_err(|_| error())?, label))
+            })
+            .collect()
+    }
+
+    fn try_add_share(&self, data: &mut AppData, mut shares: Vec<EncodedShare>, input: TextInput) -> AppState {
+        if input.get_text().is_empty() {
+            return self.clone().into();
+        }
+        let share = match EncodedShare::decode(input.get_text()) {
+            Ok(share) => share,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        if let Some(first) = shares.first()
+            && let Err(e) = first.check_same_split(&share)
+        {
+            data.error = Some(e);
+            return self.clone().into();
+        }
+        shares.push(share);
+
+        let threshold = shares[0].threshold as usize;
+        if shares.len() < threshold {
+            data.message = Some(format!("Share accepted, {} more needed", threshold - shares.len()));
+            return ExtendSharesState::new_share(shares, TextInput::default()).into();
+        }
+        // Suggest the index following every share seen so far
+        let next_index = shares
+            .iter()
+            .map(|share| share.share.x)
+            .chain([shares[0].share_count])
+            .max()
+            .unwrap()
+            .saturating_add(1);
+        ExtendSharesState::new_new_shares(shares, TextInput::new(format!("{}: ", next_index))).into()
+    }
+
+    fn try_extend(&self, data: &mut AppData, shares: &[EncodedShare], input: &TextInput) -> AppState {
+        let new_shares = match Self::parse_new_shares(input.get_text()) {
+            Ok(new_shares) => new_shares,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        match EncodedShare::extend(shares, &new_shares) {
+            Ok(new_shares) => {
+                data.message = Some(format!("{} new share(s) issued", new_shares.len()));
+                Self {
+                    step: AppExtendSharesStep::ShowShares {
+                        format: ShareFormat::default(),
+                        new_shares,
+                    },
+                }
+                .into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for ExtendSharesState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppExtendSharesStep::EnterShare { shares, .. } => match shares.first() {
+                Some(first) => format!("Extend Shares - Enter Share {}/{}", shares.len() + 1, first.threshold),
+                None => "Extend Shares - Enter Share".to_string(),
+            },
+            AppExtendSharesStep::EnterNewShares { .. } => "Extend Shares - Enter New Shares".to_string(),
+            AppExtendSharesStep::ShowShares { .. } => "Extend Shares - New Shares".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppExtendSharesStep::EnterShare { .. } => "[Esc: Cancel] [⏎ Enter: Add Share]",
+            AppExtendSharesStep::EnterNewShares { .. } => "[Esc: Cancel] [⏎ Enter: Issue Shares]",
+            AppExtendSharesStep::ShowShares { .. } => "[Esc / ⏎ Enter: Back to menu] [Tab: Change Format]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppExtendSharesStep::EnterShare { shares, input } => match key.code {
+                KeyCode::Char(c) => ExtendSharesState::new_share(shares, input.with_insert_char(c)).into(),
+                KeyCode::Backspace => ExtendSharesState::new_share(shares, input.with_delete_char()).into(),
+                KeyCode::Left => ExtendSharesState::new_share(shares, input.with_move_left()).into(),
+                KeyCode::Right => ExtendSharesState::new_share(shares, input.with_move_right()).into(),
+                KeyCode::Enter => self.try_add_share(data, shares, input),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::ExtendShares).into(),
+                _ => self.clone().into(),
+            },
+            AppExtendSharesStep::EnterNewShares { shares, input } => match key.code {
+                KeyCode::Char(c) => ExtendSharesState::new_new_shares(shares, input.with_insert_char(c)).into(),
+                KeyCode::Backspace => ExtendSharesState::new_new_shares(shares, input.with_delete_char()).into(),
+                KeyCode::Left => ExtendSharesState::new_new_shares(shares, input.with_move_left()).into(),
+                KeyCode::Right => ExtendSharesState::new_new_shares(shares, input.with_move_right()).into(),
+                KeyCode::Enter => self.try_extend(data, &shares, &input),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::ExtendShares).into(),
+                _ => self.clone().into(),
+            },
+            AppExtendSharesStep::ShowShares { format, new_shares } => match key.code {
+                KeyCode::Tab => {
+                    let formats: Vec<_> = ShareFormat::iter().collect();
+                    let current_idx = formats.iter().position(|f| f == &format).unwrap();
+                    Self {
+                        step: AppExtendSharesStep::ShowShares {
+                            format: formats[(current_idx + 1) % formats.len()],
+                            new_shares,
+                        },
+                    }
+                    .into()
+                }
+                KeyCode::Enter | KeyCode::Esc => MainMenuState::new(MainMenuAction::ExtendShares).into(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, input) = match &self.step {
+            AppExtendSharesStep::EnterShare { shares, input } => (
+                match shares.first() {
+                    Some(first) => format!(
+                        "Enter an existing share (words, hex or base32) - {} collected, {} more needed:",
+                        shares.len(),
+                        first.threshold as usize - shares.len()
+                    ),
+                    None => "Enter an existing share (words, hex or base32):".to_string(),
+                },
+                input,
+            ),
+            AppExtendSharesStep::EnterNewShares { input, .. } => (
+                "Enter the new share indices with optional labels (e.g. '6: Alice, 7: Bob'):".to_string(),
+                input,
+            ),
+            AppExtendSharesStep::ShowShares { format, new_shares } => {
+                let mut text = vec![
+                    Line::from(format!(
+                        "{} new share(s) of the key with fingerprint {}, the existing shares stay valid.",
+                        new_shares.len(),
+                        hex::encode(new_shares[0].fingerprint)
+                    ))
+                    .style(Style::default().fg(Color::Green)),
+                    Line::from(format!("Format: {}", format.name())),
+                    Line::from(""),
+                ];
+                for share in new_shares {
+                    text.push(Line::from(Span::styled(
+                        format!("Share {}:", share.name()),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    text.push(Line::from(share.encode(*format)));
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Hand each new share to its custodian only.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
@@ -1,11 +1,13 @@
 use crate::app::data::AppData;
 use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::passphrase_store::PassphraseStoreState;
+use crate::app::state::slip39_shares::Slip39SharesState;
 use crate::app::state::AppStateEvents;
 use crate::app::AppState;
-use aead::OsRng;
+use crate::encrypt::{Cipher, Kdf, KeySlot, SlotKind};
+use crate::secret::{SecretBytes, SecretString};
 use bip39::Mnemonic;
 use crossterm::event::{KeyCode, KeyEvent};
-use rand::RngCore;
 use ratatui::layout::Rect;
 use ratatui::prelude::{Color, Line, Modifier, Span, Style};
 use ratatui::widgets::{Paragraph, Wrap};
@@ -13,22 +15,20 @@ use ratatui::Frame;
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct InitStoreState {
-    generated_key: Vec<u8>,
-    generated_mnemonic: String,
+    generated_key: SecretBytes,
+    generated_mnemonic: SecretString,
 }
 
 impl InitStoreState {
     pub fn try_init(data: &mut AppData) -> AppState {
-        let mut rng = OsRng::default();
-        let mut key = [0u8; 32];
-        rng.fill_bytes(&mut key);
+        let key = SecretBytes::random(32);
 
-        match Mnemonic::from_entropy(&key) {
+        match Mnemonic::from_entropy(key.expose()) {
             Ok(mnemonic) => {
                 data.message = Some("Store initialized successfully!".to_string());
                 Self {
-                    generated_key: key.to_vec(),
-                    generated_mnemonic: mnemonic.to_string(),
+                    generated_key: key,
+                    generated_mnemonic: SecretString::new(mnemonic.to_string()),
                 }
                 .into()
             }
@@ -38,6 +38,31 @@ impl InitStoreState {
             }
         }
     }
+
+    pub fn generated_key(&self) -> &[u8] {
+        self.generated_key.expose()
+    }
+
+    /// Replaces the current store with an empty one protected by the generated key
+    pub fn initialize(&self, data: &mut AppData) -> AppState {
+        let data_key = SecretBytes::random(32);
+        let slot = match KeySlot::wrap(SlotKind::Key, Kdf::Raw, self.generated_key.expose(), data_key.expose()) {
+            Ok(slot) => slot,
+            Err(e) => {
+                data.error = Some(e);
+                return MainMenuState::new(MainMenuAction::InitStore).into();
+            }
+        };
+        data.store_key = Some(self.generated_key.clone());
+        data.data_key = Some(data_key);
+        data.key_slots = vec![slot];
+        data.rotation_backup = None;
+        data.store_cipher = Cipher::default();
+        data.sections = Vec::new();
+        data.seal_values = false;
+        data.store_label = None;
+        MainMenuState::new(MainMenuAction::EditStore).into()
+    }
 }
 
 impl AppStateEvents for InitStoreState {
@@ -46,35 +71,31 @@ impl AppStateEvents for InitStoreState {
     }
 
     fn get_footer(&self, _data: &AppData) -> &'static str {
-        "[Esc: Cancel] [⏎ Enter: Initialize Store]"
+        "[Esc: Cancel] [⏎ Enter: Initialize Store] [s: SLIP-0039 Shares] [p: Use a Passphrase]"
     }
 
     fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
         match key.code {
-            KeyCode::Enter => {
-                data.store_key = Some(self.generated_key.clone());
-                data.sections = Vec::new();
-                MainMenuState::new(MainMenuAction::EditStore).into()
-            }
+            KeyCode::Enter => self.initialize(data),
+            KeyCode::Char('s') => Slip39SharesState::new(self.clone()).into(),
+            KeyCode::Char('p') => PassphraseStoreState::default().into(),
             KeyCode::Esc => MainMenuState::new(MainMenuAction::InitStore).into(),
             _ => self.clone().into(),
         }
     }
 
     fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
-        let mut text = Vec::new();
-
-        text.push(
+        let mut text = vec![
             Line::from("New 256-bit key generated!").style(Style::default().fg(Color::Green)),
-        );
-        text.push(Line::from(""));
+            Line::from(""),
+        ];
 
         // Hex key
         text.push(Line::from(Span::styled(
             "Key (hex):",
             Style::default().fg(Color::Yellow),
         )));
-        text.push(Line::from(hex::encode(self.generated_key.clone())));
+        text.push(Line::from(hex::encode(self.generated_key.expose())));
         text.push(Line::from(""));
 
         // Mnemonic
@@ -82,7 +103,7 @@ impl AppStateEvents for InitStoreState {
             "BIP39 Mnemonic:",
             Style::default().fg(Color::Yellow),
         )));
-        text.push(Line::from(self.generated_mnemonic.as_str()));
+        text.push(Line::from(self.generated_mnemonic.expose()));
         text.push(Line::from(""));
 
         text.push(Line::from(""));
@@ -0,0 +1,333 @@
+use crate::app::data::AppData;
+use crate::app::state::derive_key::DerivedKeyUse;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::passphrase_store::PassphraseStoreState;
+use crate::app::state::split_key::SplitKeyState;
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::encrypt::{Kdf, KeyProtection, KeySlot, SlotKind};
+use crate::secret::{SecretBytes, SecretString};
+use crate::store::Store;
+use bip39::Mnemonic;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::fs;
+use std::path::PathBuf;
+
+/// Random bytes written to a key file created from scratch
+const KEY_FILE_BYTES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum AppKeySlotsStep {
+    #[default]
+    SelectSlot,
+    /// Waiting for the revocation of the selected slot to be confirmed
+    ConfirmRevoke,
+    /// Key of a new key slot, shown once
+    ShowKey { key: SecretBytes, mnemonic: SecretString },
+    EnterKeyFile(TextInput),
+}
+
+/// Lists the key slots of the store, each wrapping the data key, and adds or revokes them.
+/// Slots live in the container header, so changing them leaves the encrypted contents untouched.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct KeySlotsState {
+    selected: usize,
+    step: AppKeySlotsStep,
+}
+
+impl KeySlotsState {
+    /// Selects the last slot, the one just added
+    pub fn at_last(data: &AppData) -> Self {
+        Self {
+            selected: data.key_slots.len().saturating_sub(1),
+            step: AppKeySlotsStep::SelectSlot,
+        }
+    }
+    fn with_step(&self, step: AppKeySlotsStep) -> Self {
+        Self { step, ..self.clone() }
+    }
+    fn menu() -> AppState {
+        MainMenuState::new(MainMenuAction::KeySlots).into()
+    }
+
+    /// Wraps the data key of the current store under `slot_key`
+    pub fn new_slot(data: &AppData, kind: SlotKind, kdf: Kdf, slot_key: &[u8]) -> Result<KeySlot, String> {
+        let data_key = data.data_key().ok_or_else(|| "No store loaded".to_string())?;
+        KeySlot::wrap(kind, kdf, slot_key, data_key)
+    }
+
+    /// Adds a slot to the current store, rewriting the header of the store file when it has key slots already
+    pub fn add_slot(data: &mut AppData, slot: KeySlot) {
+        let done = format!("{} slot added", slot.kind.name());
+        let mut slots = data.key_slots.clone();
+        slots.push(slot);
+        Self::update_slots(data, slots, done);
+    }
+
+    fn update_slots(data: &mut AppData, slots: Vec<KeySlot>, done: String) {
+        data.key_slots = slots.clone();
+        let (Some(path), Some(data_key)) = (data.store_path.clone(), data.data_key.clone()) else {
+            data.message = Some(format!("{}, it is written with the next Save Store", done));
+            return;
+        };
+        match Store::read_header(&path) {
+            Ok(Some(header)) if matches!(header.keys, KeyProtection::Slots(_)) => {
+                match Store::rewrite_slots(&path, data_key.expose(), slots) {
+                    Ok(()) => data.message = Some(format!("{}, the header of {} was updated", done, path.to_string_lossy())),
+                    Err(e) => {
+                        data.error = Some(format!("{}, but {} was not updated: {}", done, path.to_string_lossy(), e))
+                    }
+                }
+            }
+            _ => data.message = Some(format!("{}, it is written with the next Save Store", done)),
+        }
+    }
+
+    /// Whether the store was opened with this slot
+    fn opened_with(data: &AppData, slot: &KeySlot) -> bool {
+        data.store_key
+            .as_ref()
+            .is_some_and(|key| slot.unwrap(key.expose()).map(SecretBytes::new).is_ok())
+    }
+
+    pub fn describe_kdf(kdf: &Kdf) -> String {
+        match kdf {
+            Kdf::Argon2id(params) => format!(
+                "Argon2id, {} MiB of memory, {} pass(es)",
+                params.memory_kib / 1024,
+                params.iterations
+            ),
+            kdf => kdf.name().to_string(),
+        }
+    }
+
+    fn revoke_slot(&self, data: &mut AppData) -> AppState {
+        if data.key_slots.len() <= 1 {
+            data.error = Some("The last key slot cannot be revoked".to_string());
+            return self.with_step(AppKeySlotsStep::SelectSlot).into();
+        }
+        let mut slots = data.key_slots.clone();
+        let slot = slots.remove(self.selected);
+        if Self::opened_with(data, &slot) {
+            // The key no longer opens the store, splitting it would hand out useless shares
+            data.store_key = None;
+        }
+        Self::update_slots(data, slots, format!("{} slot {} revoked", slot.kind.name(), self.selected + 1));
+        Self {
+            selected: self.selected.min(data.key_slots.len() - 1),
+            step: AppKeySlotsStep::SelectSlot,
+        }
+        .into()
+    }
+
+    fn add_key_slot(&self, data: &mut AppData) -> AppState {
+        let key = SecretBytes::random(32);
+        let slot = Mnemonic::from_entropy(key.expose())
+            .map_err(|e| format!("Failed to initialize BIP39: {}", e))
+            .and_then(|mnemonic| Ok((mnemonic, Self::new_slot(data, SlotKind::Key, Kdf::Raw, key.expose())?)));
+        match slot {
+            Ok((mnemonic, slot)) => {
+                Self::add_slot(data, slot);
+                Self {
+                    step: AppKeySlotsStep::ShowKey { key, mnemonic: SecretString::new(mnemonic.to_string()) },
+                    ..Self::at_last(data)
+                }
+                .into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+
+    /// Uses an existing file as key, or fills a new one with random bytes
+    fn add_key_file_slot(&self, data: &mut AppData, path: &str) -> AppState {
+        let path = PathBuf::from(path.trim());
+        let created = !path.exists();
+        let contents = if created {
+            let contents = SecretBytes::random(KEY_FILE_BYTES);
+            fs::write(&path, contents.expose())
+                .map(|_| contents)
+                .map_err(|e| format!("Failed to write the key file: {}", e))
+        } else {
+            fs::read(&path)
+                .map(SecretBytes::new)
+                .map_err(|e| format!("Failed to read the key file: {}", e))
+        };
+        let slot = contents
+            .and_then(|contents| Kdf::KeyFile.derive_key(contents.expose()).map(SecretBytes::new))
+            .and_then(|slot_key| Self::new_slot(data, SlotKind::KeyFile, Kdf::KeyFile, slot_key.expose()));
+        match slot {
+            Ok(slot) => {
+                Self::add_slot(data, slot);
+                if created {
+                    data.message = data
+                        .message
+                        .take()
+                        .map(|message| format!("{}. Key file created at {}", message, path.to_string_lossy()));
+                }
+                Self::at_last(data).into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for KeySlotsState {
+    fn get_title(&self, data: &AppData) -> String {
+        match &self.step {
+            AppKeySlotsStep::SelectSlot => format!("Key Slots - {} Slot(s)", data.key_slots.len()),
+            AppKeySlotsStep::ConfirmRevoke => format!("Key Slots - Revoke Slot {}", self.selected + 1),
+            AppKeySlotsStep::ShowKey { .. } => "Key Slots - New Key".to_string(),
+            AppKeySlotsStep::EnterKeyFile(_) => "Key Slots - Enter Key File".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppKeySlotsStep::SelectSlot => {
+                "[Esc: Back] [↑/↓: Navigate] [k: Add Key] [p: Add Passphrase] [f: Add Key File] [s: Add Shamir Shares] [d: Revoke]"
+            }
+            AppKeySlotsStep::ConfirmRevoke => "[Esc: Cancel] [y: Revoke]",
+            AppKeySlotsStep::ShowKey { .. } => "[Esc / ⏎ Enter: Back to slots]",
+            AppKeySlotsStep::EnterKeyFile(_) => "[Esc: Cancel] [⏎ Enter: Add Slot]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppKeySlotsStep::SelectSlot => match key.code {
+                KeyCode::Up | KeyCode::Down if !data.key_slots.is_empty() => {
+                    let count = data.key_slots.len();
+                    let selected = if key.code == KeyCode::Up {
+                        (self.selected + count - 1) % count
+                    } else {
+                        (self.selected + 1) % count
+                    };
+                    Self { selected, ..self.clone() }.into()
+                }
+                KeyCode::Char('k') => self.add_key_slot(data),
+                KeyCode::Char('p') => PassphraseStoreState::new(DerivedKeyUse::AddSlot).into(),
+                KeyCode::Char('f') => {
+                    let path = data.store_path_or_default().with_extension("key");
+                    self.with_step(AppKeySlotsStep::EnterKeyFile(TextInput::new(path.to_string_lossy().to_string())))
+                        .into()
+                }
+                KeyCode::Char('s') => {
+                    SplitKeyState::new_slot(SecretBytes::random(32)).into()
+                }
+                KeyCode::Char('d') | KeyCode::Delete if self.selected < data.key_slots.len() => {
+                    self.with_step(AppKeySlotsStep::ConfirmRevoke).into()
+                }
+                KeyCode::Esc => Self::menu(),
+                _ => self.clone().into(),
+            },
+            AppKeySlotsStep::ConfirmRevoke => match key.code {
+                KeyCode::Char('y') => self.revoke_slot(data),
+                KeyCode::Esc | KeyCode::Char('n') => self.with_step(AppKeySlotsStep::SelectSlot).into(),
+                _ => self.clone().into(),
+            },
+            AppKeySlotsStep::ShowKey { .. } => match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.with_step(AppKeySlotsStep::SelectSlot).into(),
+                _ => self.clone().into(),
+            },
+            AppKeySlotsStep::EnterKeyFile(input) => match key.code {
+                KeyCode::Char(c) => self.with_step(AppKeySlotsStep::EnterKeyFile(input.with_insert_char(c))).into(),
+                KeyCode::Backspace => self.with_step(AppKeySlotsStep::EnterKeyFile(input.with_delete_char())).into(),
+                KeyCode::Left => self.with_step(AppKeySlotsStep::EnterKeyFile(input.with_move_left())).into(),
+                KeyCode::Right => self.with_step(AppKeySlotsStep::EnterKeyFile(input.with_move_right())).into(),
+                KeyCode::Enter if !input.get_text().trim().is_empty() => self.add_key_file_slot(data, input.get_text()),
+                KeyCode::Esc => self.with_step(AppKeySlotsStep::SelectSlot).into(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, data: &AppData, frame: &mut Frame, area: Rect) {
+        match &self.step {
+            AppKeySlotsStep::ShowKey { key, mnemonic } => {
+                let text = vec![
+                    Line::from("New key slot added, it opens the store with this key:").style(Style::default().fg(Color::Green)),
+                    Line::from(""),
+                    Line::from(Span::styled("Key (hex):", Style::default().fg(Color::Yellow))),
+                    Line::from(hex::encode(key.expose())),
+                    Line::from(""),
+                    Line::from(Span::styled("BIP39 Mnemonic:", Style::default().fg(Color::Yellow))),
+                    Line::from(mnemonic.expose()),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        "⚠ IMPORTANT: Save this key securely! It is not shown again, revoke the slot if it leaks.",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    )),
+                ];
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+            }
+            AppKeySlotsStep::EnterKeyFile(input) => {
+                let text = vec![
+                    Line::from("Enter the key file path, a file with 64 random bytes is created if it does not exist:"),
+                    Line::from(""),
+                    Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        "Any change to the file, even a single byte, locks this slot for good.",
+                        Style::default().fg(Color::Gray),
+                    )),
+                ];
+                frame.set_cursor_position(Position::new(
+                    area.x + input.cursor_char_pos() as u16,
+                    area.y + 2,
+                ));
+                frame.render_widget(Paragraph::new(text), area);
+            }
+            AppKeySlotsStep::SelectSlot | AppKeySlotsStep::ConfirmRevoke => {
+                let mut text = vec![
+                    Line::from("Each slot wraps the data key of the store, any of them opens it:"),
+                    Line::from(""),
+                ];
+                for (idx, slot) in data.key_slots.iter().enumerate() {
+                    let style = if idx == self.selected {
+                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                    } else {
+                        Style::default()
+                    };
+                    let mut details = Self::describe_kdf(&slot.kdf);
+                    if Self::opened_with(data, slot) {
+                        details.push_str(", opened this store");
+                    }
+                    text.push(Line::from(vec![
+                        Span::styled(format!(" {}. {} ", idx + 1, slot.kind.name()), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(format!(" {}", details), Style::default().fg(Color::Gray)),
+                    ]));
+                }
+                text.push(Line::from(""));
+                if self.step == AppKeySlotsStep::ConfirmRevoke
+                    && let Some(slot) = data.key_slots.get(self.selected)
+                {
+                    let warning = if Self::opened_with(data, slot) {
+                        format!("Revoke slot {}? The store was opened with it, reopen it with another slot afterwards.", self.selected + 1)
+                    } else {
+                        format!("Revoke slot {}? Whatever opened it no longer opens the store.", self.selected + 1)
+                    };
+                    text.push(Line::from(Span::styled(
+                        warning,
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    )));
+                } else {
+                    text.push(Line::from(Span::styled(
+                        "Slots are stored in the file header: adding or revoking one leaves the encrypted contents untouched.",
+                        Style::default().fg(Color::Gray),
+                    )));
+                }
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+            }
+        }
+    }
+}
@@ -3,20 +3,56 @@ use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
 use crate::app::state::AppStateEvents;
 use crate::app::text_input::TextInput;
 use crate::app::AppState;
-use crate::store::Store;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::state::derive_key::DeriveKeyState;
+use crate::encrypt::{Kdf, KeyProtection, UnlockInput};
+use crate::secret::SecretString;
+use crate::store::{LoadedStore, Store};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Position, Rect};
 use ratatui::prelude::{Color, Line, Span, Style};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use bip39::Mnemonic;
+use crate::age_shares::{mask_identity, resolve_share_input};
+use crate::envelope::{is_envelope, UnlockRequest};
+use crate::feldman::FeldmanCommitments;
+use crate::policy::{policy_status, PolicyShare, PublishedPolicy};
+use crate::share_encoding::{recover_encoded_secret, EncodedShare};
+use crate::slip39::{combine_shares, missing_shares, Slip39Share};
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum AppLoadStoreStep {
     EnterPath(TextInput),
+    /// The store header has passphrase slots
+    EnterPassphrase {
+        /// Derivations of the passphrase slots, tried in turn
+        kdfs: Vec<Kdf>,
+        input: TextInput,
+    },
     EnterKey(TextInput),
+    EnterShare {
+        /// Remote unlock in progress: the input may also be an envelope sealed to this request
+        request: Option<UnlockRequest>,
+        shares: Vec<EncodedShare>,
+        input: TextInput,
+    },
+    EnterPolicyShare {
+        policy: PublishedPolicy,
+        shares: Vec<PolicyShare>,
+        input: TextInput,
+    },
+    EnterSlip39Share {
+        shares: Vec<Slip39Share>,
+        input: TextInput,
+    },
+    EnterSlip39Passphrase {
+        shares: Vec<Slip39Share>,
+        input: TextInput,
+    },
 }
 impl Default for AppLoadStoreStep {
     fn default() -> Self {
@@ -48,24 +84,88 @@ impl LoadStoreState {
             step: AppLoadStoreStep::EnterPath(path),
         }
     }
+    fn new_passphrase(encrypted: bool, kdfs: Vec<Kdf>, input: TextInput) -> Self {
+        Self {
+            encrypted,
+            step: AppLoadStoreStep::EnterPassphrase { kdfs, input },
+        }
+    }
     fn new_key(encrypted: bool, raw_key: TextInput) -> Self {
         Self {
             encrypted,
             step: AppLoadStoreStep::EnterKey(raw_key),
         }
     }
+    fn new_share(encrypted: bool, request: Option<UnlockRequest>, shares: Vec<EncodedShare>, input: TextInput) -> Self {
+        Self {
+            encrypted,
+            step: AppLoadStoreStep::EnterShare { request, shares, input },
+        }
+    }
+    fn new_policy_share(encrypted: bool, policy: PublishedPolicy, shares: Vec<PolicyShare>, input: TextInput) -> Self {
+        Self {
+            encrypted,
+            step: AppLoadStoreStep::EnterPolicyShare { policy, shares, input },
+        }
+    }
+    fn new_slip39_share(encrypted: bool, shares: Vec<Slip39Share>, input: TextInput) -> Self {
+        Self {
+            encrypted,
+            step: AppLoadStoreStep::EnterSlip39Share { shares, input },
+        }
+    }
+    fn new_slip39_passphrase(encrypted: bool, shares: Vec<Slip39Share>, input: TextInput) -> Self {
+        Self {
+            encrypted,
+            step: AppLoadStoreStep::EnterSlip39Passphrase { shares, input },
+        }
+    }
 }
 
 impl AppStateEvents for LoadStoreState {
     fn get_title(&self, _data: &AppData) -> String {
         match &self.step {
             AppLoadStoreStep::EnterPath(_) => "Load Store - Enter Path".to_string(),
+            AppLoadStoreStep::EnterPassphrase { .. } => "Load Store - Enter Passphrase".to_string(),
             AppLoadStoreStep::EnterKey(_) => "Load Store - Enter Key".to_string(),
+            AppLoadStoreStep::EnterShare { shares, .. } => match shares.first() {
+                Some(first) if shares.len() >= first.threshold as usize => {
+                    format!("Load Store - Enter Extra Share {}", shares.len() + 1)
+                }
+                Some(first) => format!("Load Store - Enter Share {}/{}", shares.len() + 1, first.threshold),
+                None => "Load Store - Enter Share".to_string(),
+            },
+            AppLoadStoreStep::EnterPolicyShare { shares, .. } => {
+                format!("Load Store - Enter Policy Share {}", shares.len() + 1)
+            }
+            AppLoadStoreStep::EnterSlip39Share { shares, .. } => {
+                format!("Load Store - Enter SLIP-0039 Mnemonic {}", shares.len() + 1)
+            }
+            AppLoadStoreStep::EnterSlip39Passphrase { .. } => {
+                "Load Store - Enter SLIP-0039 Passphrase".to_string()
+            }
         }
     }
 
     fn get_footer(&self, _data: &AppData) -> &'static str {
-        "[Esc: Cancel] [⏎ Enter: Continue]"
+        match &self.step {
+            AppLoadStoreStep::EnterPassphrase { .. } => {
+                "[Esc: Cancel] [⏎ Enter: Unlock] [Tab: Use Key or Shares Instead]"
+            }
+            AppLoadStoreStep::EnterKey(_) => {
+                "[Esc: Cancel] [⏎ Enter: Continue] [Tab: Recover From Shamir Shares]"
+            }
+            AppLoadStoreStep::EnterShare { request: None, .. } => {
+                "[Esc: Cancel] [⏎ Enter: Add Share] [Tab: Recover With Policy] [Ctrl+R: Remote Unlock]"
+            }
+            AppLoadStoreStep::EnterShare { request: Some(_), .. } => {
+                "[Esc: Cancel] [⏎ Enter: Add Share / Envelope] [Tab: Recover With Policy]"
+            }
+            AppLoadStoreStep::EnterPolicyShare { .. } | AppLoadStoreStep::EnterSlip39Share { .. } => {
+                "[Esc: Cancel] [⏎ Enter: Add Share]"
+            }
+            _ => "[Esc: Cancel] [⏎ Enter: Continue]",
+        }
     }
 
     fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
@@ -85,10 +185,20 @@ impl AppStateEvents for LoadStoreState {
                 }
                 KeyCode::Enter => {
                     if !path.get_text().is_empty() {
-                        data.store_path = Some(PathBuf::from(path.get_text().clone()));
+                        data.store_path = Some(PathBuf::from(path.get_text()));
                         if !self.encrypted {
                             return self.try_load_store(data, None);
                         }
+                        if let Ok(Some(header)) = Store::read_header(&PathBuf::from(path.get_text())) {
+                            let kdfs: Vec<_> = match header.keys {
+                                KeyProtection::Direct(kdf) => vec![kdf],
+                                KeyProtection::Slots(slots) => slots.into_iter().map(|slot| slot.kdf).collect(),
+                            };
+                            let kdfs: Vec<_> = kdfs.into_iter().filter(|kdf| matches!(kdf, Kdf::Argon2id(_))).collect();
+                            if !kdfs.is_empty() {
+                                return LoadStoreState::new_passphrase(self.encrypted, kdfs, TextInput::default()).into();
+                            }
+                        }
                         return LoadStoreState::new_key(self.encrypted, TextInput::default()).into();
                     }
                     LoadStoreState::new_path(self.encrypted, path.clone()).into()
@@ -96,6 +206,30 @@ impl AppStateEvents for LoadStoreState {
                 KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
                 _ => self.clone().into(),
             },
+            AppLoadStoreStep::EnterPassphrase { kdfs, input } => match key.code {
+                KeyCode::Char(c) => {
+                    LoadStoreState::new_passphrase(self.encrypted, kdfs, input.with_insert_char(c)).into()
+                }
+                KeyCode::Backspace => {
+                    LoadStoreState::new_passphrase(self.encrypted, kdfs, input.with_delete_char()).into()
+                }
+                KeyCode::Left => {
+                    LoadStoreState::new_passphrase(self.encrypted, kdfs, input.with_move_left()).into()
+                }
+                KeyCode::Right => {
+                    LoadStoreState::new_passphrase(self.encrypted, kdfs, input.with_move_right()).into()
+                }
+                KeyCode::Enter if !input.get_text().is_empty() => match &data.store_path {
+                    Some(path) => DeriveKeyState::load(kdfs, SecretString::from(input.get_text()), path.clone()).into(),
+                    None => {
+                        data.error = Some("No path specified".to_string());
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Tab => LoadStoreState::new_key(self.encrypted, TextInput::default()).into(),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
+                _ => self.clone().into(),
+            },
             AppLoadStoreStep::EnterKey(raw_key) => match key.code {
                 KeyCode::Char(c) => {
                     LoadStoreState::new_key(self.encrypted, raw_key.with_insert_char(c)).into()
@@ -110,9 +244,20 @@ impl AppStateEvents for LoadStoreState {
                     LoadStoreState::new_key(self.encrypted, raw_key.with_move_right()).into()
                 }
                 KeyCode::Enter => {
+                    if let Ok(share) = Slip39Share::from_mnemonic(raw_key.get_text()) {
+                        return self.try_add_slip39_share(data, Vec::new(), share);
+                    }
                     if !raw_key.get_text().is_empty() {
-                        return match Self::parse_raw_key(raw_key.get_text().clone()) {
+                        return match Self::parse_raw_key(raw_key.get_text()) {
                             Ok(key) => self.try_load_store(data, Some(key)),
+                            // Anything else naming a file is a key file
+                            Err(_) if Path::new(raw_key.get_text()).is_file() => match fs::read(raw_key.get_text()) {
+                                Ok(contents) => self.try_unlock(data, Some(UnlockInput::KeyFile(contents))),
+                                Err(e) => {
+                                    data.error = Some(format!("Failed to read the key file: {}", e));
+                                    self.clone().into()
+                                }
+                            },
                             Err(e) => {
                                 data.error = Some(e);
                                 self.clone().into()
@@ -121,6 +266,117 @@ impl AppStateEvents for LoadStoreState {
                     }
                     LoadStoreState::new_key(self.encrypted, raw_key).into()
                 }
+                KeyCode::Tab => {
+                    LoadStoreState::new_share(self.encrypted, None, Vec::new(), TextInput::default()).into()
+                }
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
+                _ => self.clone().into(),
+            },
+            AppLoadStoreStep::EnterShare { request, shares, input } => match key.code {
+                // Before Char(c), which would take Ctrl+R as a typed 'r'
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) && request.is_none() => {
+                    let request = UnlockRequest::new();
+                    let path = data.unlock_request_path();
+                    match fs::write(&path, format!("{}\n", request.code())) {
+                        Ok(()) => {
+                            data.message = Some(format!(
+                                "Remote unlock started, send the request code or {} to the share holders",
+                                path.to_string_lossy()
+                            ))
+                        }
+                        Err(e) => data.error = Some(format!("Failed to write the request file: {}", e)),
+                    }
+                    LoadStoreState::new_share(self.encrypted, Some(request), shares, input).into()
+                }
+                KeyCode::Char(c) => {
+                    LoadStoreState::new_share(self.encrypted, request, shares, input.with_insert_char(c)).into()
+                }
+                KeyCode::Backspace => {
+                    LoadStoreState::new_share(self.encrypted, request, shares, input.with_delete_char()).into()
+                }
+                KeyCode::Left => {
+                    LoadStoreState::new_share(self.encrypted, request, shares, input.with_move_left()).into()
+                }
+                KeyCode::Right => {
+                    LoadStoreState::new_share(self.encrypted, request, shares, input.with_move_right()).into()
+                }
+                KeyCode::Enter => self.try_add_share(data, request, shares, input),
+                KeyCode::Tab => match PublishedPolicy::load(&data.policy_path()) {
+                    Ok(policy) => {
+                        LoadStoreState::new_policy_share(self.encrypted, policy, Vec::new(), TextInput::default()).into()
+                    }
+                    Err(e) => {
+                        data.error = Some(e);
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
+                _ => self.clone().into(),
+            },
+            AppLoadStoreStep::EnterPolicyShare { policy, shares, input } => match key.code {
+                KeyCode::Char(c) => {
+                    LoadStoreState::new_policy_share(self.encrypted, policy, shares, input.with_insert_char(c)).into()
+                }
+                KeyCode::Backspace => {
+                    LoadStoreState::new_policy_share(self.encrypted, policy, shares, input.with_delete_char()).into()
+                }
+                KeyCode::Left => {
+                    LoadStoreState::new_policy_share(self.encrypted, policy, shares, input.with_move_left()).into()
+                }
+                KeyCode::Right => {
+                    LoadStoreState::new_policy_share(self.encrypted, policy, shares, input.with_move_right()).into()
+                }
+                KeyCode::Enter => self.try_add_policy_share(data, policy, shares, input),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
+                _ => self.clone().into(),
+            },
+            AppLoadStoreStep::EnterSlip39Share { shares, input } => match key.code {
+                KeyCode::Char(c) => {
+                    LoadStoreState::new_slip39_share(self.encrypted, shares, input.with_insert_char(c)).into()
+                }
+                KeyCode::Backspace => {
+                    LoadStoreState::new_slip39_share(self.encrypted, shares, input.with_delete_char()).into()
+                }
+                KeyCode::Left => {
+                    LoadStoreState::new_slip39_share(self.encrypted, shares, input.with_move_left()).into()
+                }
+                KeyCode::Right => {
+                    LoadStoreState::new_slip39_share(self.encrypted, shares, input.with_move_right()).into()
+                }
+                KeyCode::Enter => match Slip39Share::from_mnemonic(input.get_text()) {
+                    Ok(share) => self.try_add_slip39_share(data, shares, share),
+                    Err(e) => {
+                        data.error = Some(e);
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
+                _ => self.clone().into(),
+            },
+            AppLoadStoreStep::EnterSlip39Passphrase { shares, input } => match key.code {
+                KeyCode::Char(c) => {
+                    LoadStoreState::new_slip39_passphrase(self.encrypted, shares, input.with_insert_char(c)).into()
+                }
+                KeyCode::Backspace => {
+                    LoadStoreState::new_slip39_passphrase(self.encrypted, shares, input.with_delete_char()).into()
+                }
+                KeyCode::Left => {
+                    LoadStoreState::new_slip39_passphrase(self.encrypted, shares, input.with_move_left()).into()
+                }
+                KeyCode::Right => {
+                    LoadStoreState::new_slip39_passphrase(self.encrypted, shares, input.with_move_right()).into()
+                }
+                KeyCode::Enter => match combine_shares(&shares, input.get_text()) {
+                    Ok(key) if key.len() == 32 => self.try_load_store(data, Some(key)),
+                    Ok(_) => {
+                        data.error = Some("The mnemonics do not hold a 256-bit key".to_string());
+                        MainMenuState::new(MainMenuAction::LoadStore).into()
+                    }
+                    Err(e) => {
+                        data.error = Some(e);
+                        MainMenuState::new(MainMenuAction::LoadStore).into()
+                    }
+                },
                 KeyCode::Esc => MainMenuState::new(MainMenuAction::LoadStore).into(),
                 _ => self.clone().into(),
             },
@@ -130,19 +386,82 @@ impl AppStateEvents for LoadStoreState {
     fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
         let (prompt, input) = match &self.step {
             AppLoadStoreStep::EnterPath(input) => {
-                ("Enter store file path:", input)
+                ("Enter store file path:".to_string(), input)
             }
+            AppLoadStoreStep::EnterPassphrase { input, .. } => (
+                "Enter the store passphrase:".to_string(),
+                input,
+            ),
             AppLoadStoreStep::EnterKey(input) => (
-                "Enter key (hex or BIP39 mnemonic):",
+                "Enter key (hex, BIP39 mnemonic, SLIP-0039 mnemonic or key file path):".to_string(),
+                input,
+            ),
+            AppLoadStoreStep::EnterShare { request, shares, input } => {
+                let kind = match request {
+                    Some(_) => "envelope (single line or file path) or share",
+                    None => "share (words, hex, base32, or '<file.age> <identity file>')",
+                };
+                (
+                    match shares.first() {
+                        Some(first) if shares.len() >= first.threshold as usize => format!(
+                            "Enter another {} - {} collected but they disagree:",
+                            kind,
+                            shares.len()
+                        ),
+                        Some(first) => format!(
+                            "Enter {} - {} collected, {} more needed:",
+                            kind,
+                            shares.len(),
+                            first.threshold as usize - shares.len()
+                        ),
+                        None => format!("Enter {}:", kind),
+                    },
+                    input,
+                )
+            }
+            AppLoadStoreStep::EnterPolicyShare { input, .. } => (
+                "Enter a member share with its path (e.g. '1.0 <share>'), or '<file.age> <identity file>':".to_string(),
                 input,
             ),
+            AppLoadStoreStep::EnterSlip39Share { shares, input } => (
+                match missing_shares(shares) {
+                    Ok(Some(missing)) => format!("Enter next SLIP-0039 mnemonic ({}):", missing),
+                    _ => "Enter next SLIP-0039 mnemonic:".to_string(),
+                },
+                input,
+            ),
+            AppLoadStoreStep::EnterSlip39Passphrase { input, .. } => (
+                "Enter the SLIP-0039 passphrase (leave empty if none):".to_string(),
+                input,
+            ),
+        };
+        let displayed = match &self.step {
+            AppLoadStoreStep::EnterSlip39Passphrase { .. } | AppLoadStoreStep::EnterPassphrase { .. } => {
+                "*".repeat(input.get_text().chars().count())
+            }
+            AppLoadStoreStep::EnterShare { .. } | AppLoadStoreStep::EnterPolicyShare { .. } => {
+                mask_identity(input.get_text())
+            }
+            _ => input.get_text().to_string(),
         };
 
-        let text = vec![
+        let mut text = vec![
             Line::from(prompt),
             Line::from(""),
-            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+            Line::from(Span::styled(displayed, Style::default().fg(Color::Yellow))),
         ];
+        if let AppLoadStoreStep::EnterShare { request: Some(request), .. } = &self.step {
+            text.push(Line::from(""));
+            text.push(Line::from("Request code, each share holder runs `bunkeys submit-share <request code>`:"));
+            text.push(Line::from(Span::styled(request.code(), Style::default().fg(Color::Green))));
+        }
+        // Tell which groups of the policy are satisfied and what is still missing
+        if let AppLoadStoreStep::EnterPolicyShare { policy, shares, .. } = &self.step
+            && let Ok(policy) = policy.parse()
+        {
+            text.push(Line::from(""));
+            text.extend(policy_status(&policy, shares).into_iter().map(Line::from));
+        }
         frame.set_cursor_position(Position::new(
             area.x + input.cursor_char_pos() as u16,
             area.y + 2,
@@ -155,10 +474,10 @@ impl AppStateEvents for LoadStoreState {
 }
 
 impl LoadStoreState {
-    fn parse_raw_key(raw_key: String) -> Result<Vec<u8>, String> {
+    fn parse_raw_key(raw_key: &str) -> Result<Vec<u8>, String> {
         Ok(if raw_key.contains(' ') {
             // It's a mnemonic
-            match Mnemonic::parse(&raw_key) {
+            match Mnemonic::parse(raw_key) {
                 Ok(mnemonic) => {
                     let bytes = mnemonic.to_entropy();
                     if bytes.len() <= 32 {
@@ -175,7 +494,7 @@ impl LoadStoreState {
             }
         } else {
             // Try to parse as hex
-            match hex::decode(&raw_key) {
+            match hex::decode(raw_key) {
                 Ok(bytes) if bytes.len() == 32 => bytes,
                 _ => {
                     return Err(
@@ -186,7 +505,157 @@ impl LoadStoreState {
         })
     }
 
+    fn try_add_share(
+        &self,
+        data: &mut AppData,
+        request: Option<UnlockRequest>,
+        mut shares: Vec<EncodedShare>,
+        input: TextInput,
+    ) -> AppState {
+        if input.get_text().is_empty() {
+            return self.clone().into();
+        }
+        let text = match &request {
+            Some(request) if is_envelope(input.get_text()) => request.open(input.get_text()),
+            _ => resolve_share_input(input.get_text()),
+        };
+        let share = match text.and_then(|text| EncodedShare::decode(&text)) {
+            Ok(share) => share,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        // Name a corrupted or forged share right away when the dealer published commitments for this key
+        let commitments = match FeldmanCommitments::load_if_exists(&data.commitments_path()) {
+            Ok(commitments) => commitments.filter(|commitments| commitments.covers(&share)),
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        if let Some(commitments) = &commitments
+            && let Err(e) = commitments.verify(&share)
+        {
+            data.error = Some(e);
+            return self.clone().into();
+        }
+        if let Some(first) = shares.first()
+            && let Err(e) = first.check_same_split(&share)
+        {
+            data.error = Some(e);
+            return self.clone().into();
+        }
+        if shares.iter().any(|other| other.share.x == share.share.x) {
+            data.error = Some(format!("Share {} was already entered", share.share.x));
+            return self.clone().into();
+        }
+        shares.push(share);
+
+        let threshold = shares[0].threshold as usize;
+        if shares.len() < threshold {
+            data.message = Some(format!(
+                "Share {}, {} more needed",
+                if commitments.is_some() { "verified" } else { "accepted" },
+                threshold - shares.len()
+            ));
+            return LoadStoreState::new_share(self.encrypted, request, shares, TextInput::default()).into();
+        }
+
+        match recover_encoded_secret(&shares) {
+            Ok(recovery) if recovery.secret.len() == 32 => {
+                let state = self.try_load_store(data, Some(recovery.secret));
+                if !recovery.inconsistent.is_empty() && data.error.is_none() {
+                    data.message = Some(format!(
+                        "Store loaded, inconsistent share(s) ignored: {}",
+                        recovery.inconsistent.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")
+                    ));
+                }
+                state
+            }
+            Ok(_) => {
+                data.error = Some("The shares do not hold a 256-bit key".to_string());
+                MainMenuState::new(MainMenuAction::LoadStore).into()
+            }
+            Err(e) => {
+                // More shares may outvote the wrong ones
+                data.error = Some(format!("{}. Enter another share", e));
+                LoadStoreState::new_share(self.encrypted, request, shares, TextInput::default()).into()
+            }
+        }
+    }
+
+    fn try_add_policy_share(
+        &self,
+        data: &mut AppData,
+        policy: PublishedPolicy,
+        mut shares: Vec<PolicyShare>,
+        input: TextInput,
+    ) -> AppState {
+        if input.get_text().is_empty() {
+            return self.clone().into();
+        }
+        let share = resolve_share_input(input.get_text()).and_then(|text| PolicyShare::decode(&text)).and_then(|share| {
+            share.check(&policy.parse()?, &shares)?;
+            Ok(share)
+        });
+        let share = match share {
+            Ok(share) => share,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        if shares.iter().any(|other| other.path == share.path && other.share.share.x == share.share.share.x) {
+            data.error = Some(format!("Share {} was already entered", share.share.name()));
+            return self.clone().into();
+        }
+        shares.push(share);
+
+        match policy.recover(&shares) {
+            Ok(Some(key)) if key.len() == 32 => self.try_load_store(data, Some(key)),
+            Ok(Some(_)) => {
+                data.error = Some("The shares do not hold a 256-bit key".to_string());
+                MainMenuState::new(MainMenuAction::LoadStore).into()
+            }
+            Ok(None) => {
+                data.message = Some("Share accepted, the policy is not satisfied yet".to_string());
+                LoadStoreState::new_policy_share(self.encrypted, policy, shares, TextInput::default()).into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                LoadStoreState::new_policy_share(self.encrypted, policy, shares, TextInput::default()).into()
+            }
+        }
+    }
+
+    fn try_add_slip39_share(&self, data: &mut AppData, mut shares: Vec<Slip39Share>, share: Slip39Share) -> AppState {
+        shares.push(share);
+        match missing_shares(&shares) {
+            Ok(Some(missing)) => {
+                data.message = Some(format!("Mnemonic accepted, {}", missing));
+                LoadStoreState::new_slip39_share(self.encrypted, shares, TextInput::default()).into()
+            }
+            Ok(None) => {
+                data.message = Some("Enough mnemonics collected".to_string());
+                LoadStoreState::new_slip39_passphrase(self.encrypted, shares, TextInput::default()).into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                shares.pop();
+                if shares.is_empty() {
+                    return self.clone().into();
+                }
+                LoadStoreState::new_slip39_share(self.encrypted, shares, TextInput::default()).into()
+            }
+        }
+    }
+
     fn try_load_store(&self, data: &mut AppData, key: Option<Vec<u8>>) -> AppState {
+        self.try_unlock(data, key.map(UnlockInput::Key))
+    }
+
+    fn try_unlock(&self, data: &mut AppData, unlock: Option<UnlockInput>) -> AppState {
         let path = match &data.store_path {
             Some(p) => p,
             None => {
@@ -195,19 +664,34 @@ impl LoadStoreState {
             }
         };
 
-        if self.encrypted && key.is_none() {
+        if self.encrypted && unlock.is_none() {
             data.error = Some("Encryption key is undefined".to_string());
             return self.clone().into();
         }
 
         // Try to load and decrypt the file
-        match Store::load(key.clone(), path.clone()) {
-            Ok(store) => {
-                data.sections = store.to_sections();
-                if key.is_some() {
-                    data.store_key = key;
+        Self::apply_loaded(data, Store::load(unlock, path.clone()))
+    }
+
+    /// Makes a store read from `data.store_path` the current one
+    pub fn apply_loaded(data: &mut AppData, loaded: Result<LoadedStore, String>) -> AppState {
+        match loaded {
+            Ok(loaded) => {
+                data.sections = loaded.store.into_sections();
+                data.seal_values = data.has_sealed_values();
+                data.store_label = loaded.label;
+                if loaded.data_key.is_some() {
+                    data.store_key = loaded.slot_key;
+                    data.data_key = loaded.data_key;
+                    data.key_slots = loaded.slots;
+                    data.store_cipher = loaded.cipher;
+                    data.rotation_backup = None;
                 }
-                data.message = Some("Store loaded successfully!".to_string());
+                data.message = Some(if loaded.legacy {
+                    "Store loaded successfully! It has no key slots yet and will be upgraded on the next save.".to_string()
+                } else {
+                    "Store loaded successfully!".to_string()
+                });
                 MainMenuState::new(MainMenuAction::EditStore).into()
             }
             Err(e) => {
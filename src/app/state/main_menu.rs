@@ -1,6 +1,6 @@
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::Frame;
-use ratatui::prelude::{Color, Rect, Style, Stylize, Constraint, Layout, Direction};
+use ratatui::prelude::{Color, Rect, Style, Constraint, Layout, Direction};
 use ratatui::widgets::{Table, Row, Cell};
 use ratatui::text::Text;
 use strum::IntoEnumIterator;
@@ -12,6 +12,14 @@ use crate::app::state::init_store::InitStoreState;
 use crate::app::state::load_store::LoadStoreState;
 use crate::app::state::edit_store::EditStoreState;
 use crate::app::state::save_store::SaveStoreState;
+use crate::app::state::key_slots::KeySlotsState;
+use crate::app::state::rotate_key::RotateKeyState;
+use crate::app::state::split_key::SplitKeyState;
+use crate::app::state::policy_split::PolicySplitState;
+use crate::app::state::verify_share::VerifyShareState;
+use crate::app::state::extend_shares::ExtendSharesState;
+use crate::app::state::renew_shares::{RenewSharesState, RenewalMode};
+use crate::app::state::recovery_drill::RecoveryDrillState;
 
 #[derive(Debug, Clone, PartialEq, EnumIter, Default)]
 pub enum MainMenuAction {
@@ -20,8 +28,18 @@ pub enum MainMenuAction {
     LoadStore,
     LoadUnencryptedStore,
     EditStore,
+    SealValues,
     SaveStore,
     SaveUnencryptedStore,
+    KeySlots,
+    RotateKey,
+    SplitKey,
+    SplitKeyByPolicy,
+    VerifyShare,
+    RecoveryDrill,
+    ExtendShares,
+    RefreshShares,
+    ReshareKey,
 }
 
 impl MainMenuAction {
@@ -31,15 +49,25 @@ impl MainMenuAction {
             MainMenuAction::LoadStore => ("Load Store", "Load existing store from file"),
             MainMenuAction::LoadUnencryptedStore => ("Load Store Data From Unencrypted File", "Load current store data from unencrypted file"),
             MainMenuAction::EditStore => ("Edit Store", "View and modify store contents"),
+            MainMenuAction::SealValues => ("Seal Values", "Keep each value encrypted until revealed, or switch back to plaintext values"),
             MainMenuAction::SaveStore => ("Save Store", "Save store to file"),
             MainMenuAction::SaveUnencryptedStore => ("Save Unencrypted Store", "Save store to file without encryption (NOT RECOMMENDED!)"),
+            MainMenuAction::KeySlots => ("Key Slots", "Add or revoke the keys, passphrases and shares opening the store"),
+            MainMenuAction::RotateKey => ("Rotate Key", "Re-encrypt the store under a new key, dropping every other slot"),
+            MainMenuAction::SplitKey => ("Split Key", "Split the store key into Shamir shares"),
+            MainMenuAction::SplitKeyByPolicy => ("Split Key by Policy", "Split the store key along nested groups and weighted members"),
+            MainMenuAction::VerifyShare => ("Verify Share", "Check a share against the published Feldman commitments"),
+            MainMenuAction::RecoveryDrill => ("Recovery Drill", "Check that shares still rebuild the key, without opening the store"),
+            MainMenuAction::ExtendShares => ("Extend Shares", "Issue shares for new custodians from existing ones"),
+            MainMenuAction::RefreshShares => ("Refresh Shares", "Renew the remaining custodians' shares, voiding the others"),
+            MainMenuAction::ReshareKey => ("Reshare Key", "Split the key held by shares with a new threshold and count"),
         }
     }
     pub fn requires_store(&self) -> bool {
-        match self {
-            MainMenuAction::LoadUnencryptedStore | MainMenuAction::EditStore | MainMenuAction::SaveStore | MainMenuAction::SaveUnencryptedStore => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            MainMenuAction::LoadUnencryptedStore | MainMenuAction::EditStore | MainMenuAction::SealValues | MainMenuAction::SaveStore | MainMenuAction::SaveUnencryptedStore | MainMenuAction::KeySlots | MainMenuAction::RotateKey | MainMenuAction::SplitKey | MainMenuAction::SplitKeyByPolicy
+        )
     }
 }
 
@@ -52,12 +80,21 @@ impl MainMenuState {
     pub fn new(action: MainMenuAction) -> Self {
         Self { selected_action: action }
     }
+
+    /// Where a flow started from `action` goes once done: the menu on that entry,
+    /// or Rotate Key again while the new key it shows is not confirmed, e.g. after splitting it
+    pub fn return_to(action: MainMenuAction, data: &AppData) -> AppState {
+        match action {
+            MainMenuAction::RotateKey if data.rotation_backup.is_some() => RotateKeyState::new(data).into(),
+            action => MainMenuState::new(action).into(),
+        }
+    }
 }
 
 impl AppStateEvents for MainMenuState {
     fn get_title(&self, data: &AppData) -> String {
         let mut title = "Main Menu".to_string();
-        if data.store_key.is_some() {
+        if data.data_key.is_some() {
             title.push_str(" - 🔓 Store Loaded");
         }
         title
@@ -82,7 +119,7 @@ impl AppStateEvents for MainMenuState {
                 MainMenuState::new(actions[next_idx].clone()).into()
             }
             KeyCode::Enter => {
-                if self.selected_action.requires_store() && data.store_key.is_none() {
+                if self.selected_action.requires_store() && data.data_key.is_none() {
                     data.error = Some("No store loaded. Please load or init a store first.".to_string());
                     return self.clone().into();
                 }
@@ -99,11 +136,47 @@ impl AppStateEvents for MainMenuState {
                     MainMenuAction::EditStore => {
                         EditStoreState::default().into()
                     }
+                    MainMenuAction::SealValues => {
+                        let seal = !data.seal_values;
+                        match data.set_seal_values(seal) {
+                            Ok(()) if seal => data.message = Some("Values sealed, each one is decrypted only when revealed or edited".to_string()),
+                            Ok(()) => data.message = Some("Values unsealed, they are kept in plaintext in memory".to_string()),
+                            Err(e) => data.error = Some(e),
+                        }
+                        self.clone().into()
+                    }
                     MainMenuAction::SaveStore => {
-                        SaveStoreState::new(true, data.get_store_path_string_as_enc()).into()
+                        SaveStoreState::new(true, data.get_store_path_string_as_enc(), data.store_label.clone(), data.store_cipher).into()
                     }
                     MainMenuAction::SaveUnencryptedStore => {
-                        SaveStoreState::new(false, data.get_store_path_string_as_yaml()).into()
+                        SaveStoreState::new(false, data.get_store_path_string_as_yaml(), None, data.store_cipher).into()
+                    }
+                    MainMenuAction::KeySlots => {
+                        KeySlotsState::default().into()
+                    }
+                    MainMenuAction::RotateKey => {
+                        RotateKeyState::new(data).into()
+                    }
+                    MainMenuAction::SplitKey => {
+                        SplitKeyState::default().into()
+                    }
+                    MainMenuAction::SplitKeyByPolicy => {
+                        PolicySplitState::default().into()
+                    }
+                    MainMenuAction::VerifyShare => {
+                        VerifyShareState::new(data).into()
+                    }
+                    MainMenuAction::RecoveryDrill => {
+                        RecoveryDrillState::default().into()
+                    }
+                    MainMenuAction::ExtendShares => {
+                        ExtendSharesState::default().into()
+                    }
+                    MainMenuAction::RefreshShares => {
+                        RenewSharesState::new(RenewalMode::Refresh).into()
+                    }
+                    MainMenuAction::ReshareKey => {
+                        RenewSharesState::new(RenewalMode::Reshare).into()
                     }
                 }
             }
@@ -126,8 +199,18 @@ impl AppStateEvents for MainMenuState {
                 MainMenuAction::LoadStore => "📂",
                 MainMenuAction::LoadUnencryptedStore => "📄",
                 MainMenuAction::EditStore => "✏️",
+                MainMenuAction::SealValues => "🔒",
                 MainMenuAction::SaveStore => "💾",
                 MainMenuAction::SaveUnencryptedStore => "⚠️",
+                MainMenuAction::KeySlots => "🗝️",
+                MainMenuAction::RotateKey => "♻️",
+                MainMenuAction::SplitKey => "🧩",
+                MainMenuAction::SplitKeyByPolicy => "🏛️",
+                MainMenuAction::VerifyShare => "🔍",
+                MainMenuAction::RecoveryDrill => "🩺",
+                MainMenuAction::ExtendShares => "➕",
+                MainMenuAction::RefreshShares => "🔄",
+                MainMenuAction::ReshareKey => "🔀",
             };
 
             let icon_cell = Cell::from(Text::from(format!(" {} ", icon)));
@@ -0,0 +1,234 @@
+use crate::app::data::AppData;
+use crate::app::state::derive_key::{DeriveKeyState, DerivedKeyUse};
+use crate::app::state::key_slots::KeySlotsState;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::encrypt::{Argon2Params, Kdf};
+use crate::secret::{SecretBytes, SecretString};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// Argon2id memory and time costs offered for a new passphrase, as in libsodium
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum PassphraseCost {
+    Interactive,
+    #[default]
+    Moderate,
+    Sensitive,
+}
+
+impl PassphraseCost {
+    pub fn to_string(self) -> (&'static str, &'static str) {
+        match self {
+            PassphraseCost::Interactive => ("Interactive", "64 MiB, 2 passes, well under a second"),
+            PassphraseCost::Moderate => ("Moderate", "256 MiB, 3 passes, about a second"),
+            PassphraseCost::Sensitive => ("Sensitive", "1 GiB, 4 passes, several seconds"),
+        }
+    }
+    fn params(self) -> Result<Argon2Params, String> {
+        match self {
+            PassphraseCost::Interactive => Argon2Params::new(64 * 1024, 2),
+            PassphraseCost::Moderate => Argon2Params::new(256 * 1024, 3),
+            PassphraseCost::Sensitive => Argon2Params::new(1024 * 1024, 4),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppPassphraseStoreStep {
+    EnterPassphrase(TextInput),
+    ConfirmPassphrase { passphrase: SecretString, input: TextInput },
+    SelectCost { passphrase: SecretString },
+}
+
+/// Creates an empty store, or adds a slot to the current one, opening with a passphrase stretched by Argon2id
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassphraseStoreState {
+    purpose: DerivedKeyUse,
+    cost: PassphraseCost,
+    step: AppPassphraseStoreStep,
+}
+
+impl Default for PassphraseStoreState {
+    fn default() -> Self {
+        Self::new(DerivedKeyUse::InitStore)
+    }
+}
+
+impl PassphraseStoreState {
+    pub fn new(purpose: DerivedKeyUse) -> Self {
+        Self {
+            purpose,
+            cost: PassphraseCost::default(),
+            step: AppPassphraseStoreStep::EnterPassphrase(TextInput::default()),
+        }
+    }
+    fn with_step(&self, step: AppPassphraseStoreStep) -> Self {
+        Self { step, ..self.clone() }
+    }
+
+    fn menu(&self) -> AppState {
+        match self.purpose {
+            DerivedKeyUse::AddSlot => KeySlotsState::default().into(),
+            _ => MainMenuState::new(MainMenuAction::InitStore).into(),
+        }
+    }
+
+    fn start_derivation(&self, data: &mut AppData, passphrase: SecretString) -> AppState {
+        let data_key = match self.purpose {
+            DerivedKeyUse::AddSlot => match data.data_key.clone() {
+                Some(data_key) => data_key,
+                None => {
+                    data.error = Some("No store loaded".to_string());
+                    return self.menu();
+                }
+            },
+            _ => SecretBytes::random(32),
+        };
+        match self.cost.params() {
+            Ok(params) => DeriveKeyState::new_slot(Kdf::Argon2id(params), passphrase, data_key, self.purpose.clone()).into(),
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for PassphraseStoreState {
+    fn get_title(&self, _data: &AppData) -> String {
+        let title = match self.purpose {
+            DerivedKeyUse::AddSlot => "Passphrase Slot",
+            _ => "Passphrase Store",
+        };
+        match &self.step {
+            AppPassphraseStoreStep::EnterPassphrase(_) => format!("{} - Enter Passphrase", title),
+            AppPassphraseStoreStep::ConfirmPassphrase { .. } => format!("{} - Confirm Passphrase", title),
+            AppPassphraseStoreStep::SelectCost { .. } => format!("{} - Select Cost", title),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppPassphraseStoreStep::SelectCost { .. } => "[Esc: Cancel] [↑/↓: Navigate] [⏎ Enter: Select]",
+            _ => "[Esc: Cancel] [⏎ Enter: Continue]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppPassphraseStoreStep::EnterPassphrase(input) => match key.code {
+                KeyCode::Char(c) => self.with_step(AppPassphraseStoreStep::EnterPassphrase(input.with_insert_char(c))).into(),
+                KeyCode::Backspace => self.with_step(AppPassphraseStoreStep::EnterPassphrase(input.with_delete_char())).into(),
+                KeyCode::Left => self.with_step(AppPassphraseStoreStep::EnterPassphrase(input.with_move_left())).into(),
+                KeyCode::Right => self.with_step(AppPassphraseStoreStep::EnterPassphrase(input.with_move_right())).into(),
+                KeyCode::Enter if input.get_text().chars().count() < MIN_PASSPHRASE_CHARS => {
+                    data.error = Some(format!("Passphrase must be at least {} characters long", MIN_PASSPHRASE_CHARS));
+                    self.clone().into()
+                }
+                KeyCode::Enter => self
+                    .with_step(AppPassphraseStoreStep::ConfirmPassphrase {
+                        passphrase: SecretString::from(input.get_text()),
+                        input: TextInput::default(),
+                    })
+                    .into(),
+                KeyCode::Esc => self.menu(),
+                _ => self.clone().into(),
+            },
+            AppPassphraseStoreStep::ConfirmPassphrase { passphrase, input } => {
+                let with_input = |input: TextInput| -> AppState {
+                    self.with_step(AppPassphraseStoreStep::ConfirmPassphrase { passphrase: passphrase.clone(), input }).into()
+                };
+                match key.code {
+                    KeyCode::Char(c) => with_input(input.with_insert_char(c)),
+                    KeyCode::Backspace => with_input(input.with_delete_char()),
+                    KeyCode::Left => with_input(input.with_move_left()),
+                    KeyCode::Right => with_input(input.with_move_right()),
+                    KeyCode::Enter if input.get_text() != passphrase.expose() => {
+                        data.error = Some("The passphrases do not match, enter it again".to_string());
+                        PassphraseStoreState::new(self.purpose.clone()).into()
+                    }
+                    KeyCode::Enter => self.with_step(AppPassphraseStoreStep::SelectCost { passphrase }).into(),
+                    KeyCode::Esc => self.menu(),
+                    _ => self.clone().into(),
+                }
+            }
+            AppPassphraseStoreStep::SelectCost { passphrase } => match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    let costs: Vec<_> = PassphraseCost::iter().collect();
+                    let current_idx = costs.iter().position(|c| c == &self.cost).unwrap();
+                    let next_idx = if key.code == KeyCode::Up {
+                        (current_idx + costs.len() - 1) % costs.len()
+                    } else {
+                        (current_idx + 1) % costs.len()
+                    };
+                    Self {
+                        cost: costs[next_idx],
+                        ..self.clone()
+                    }
+                    .into()
+                }
+                KeyCode::Enter => self.start_derivation(data, passphrase),
+                KeyCode::Esc => self.menu(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, input) = match &self.step {
+            AppPassphraseStoreStep::EnterPassphrase(input) => match self.purpose {
+                DerivedKeyUse::AddSlot => ("Enter the passphrase of the new slot:", input),
+                _ => ("Enter the passphrase protecting the new store:", input),
+            },
+            AppPassphraseStoreStep::ConfirmPassphrase { input, .. } => ("Enter the passphrase again:", input),
+            AppPassphraseStoreStep::SelectCost { .. } => {
+                let mut text = vec![
+                    Line::from("Select the Argon2id cost, higher costs slow down passphrase guessing as much as unlocking:"),
+                    Line::from(""),
+                ];
+                for cost in PassphraseCost::iter() {
+                    let (name, description) = cost.to_string();
+                    let style = if cost == self.cost {
+                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                    } else {
+                        Style::default()
+                    };
+                    text.push(Line::from(vec![
+                        Span::styled(format!(" {} ", name), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(format!(" {}", description), Style::default().fg(Color::Gray)),
+                    ]));
+                }
+                frame.render_widget(Paragraph::new(text), area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(
+                "*".repeat(input.get_text().chars().count()),
+                Style::default().fg(Color::Yellow),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "⚠ IMPORTANT: The passphrase cannot be recovered, add a key or Shamir slot from Key Slots if you fear forgetting it.",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
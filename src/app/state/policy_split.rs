@@ -0,0 +1,229 @@
+use crate::app::data::AppData;
+use crate::app::state::ceremony::{CeremonyState, DistributionMode};
+use crate::app::state::seal_shares::{SealSharesState, SealedSplit};
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::policy::{split_with_policy, PolicyNode, PolicyShare, PublishedPolicy};
+use crate::share_encoding::ShareFormat;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppPolicySplitStep {
+    EnterPolicy(TextInput),
+    SelectDistribution(PolicyNode),
+    ShowShares {
+        format: ShareFormat,
+        policy: PolicyNode,
+        shares: Vec<PolicyShare>,
+        /// Where the policy was published, recovery needs it next to the store
+        policy_path: PathBuf,
+    },
+}
+
+/// Splits the store key along a nested group and weight policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicySplitState {
+    distribution: DistributionMode,
+    step: AppPolicySplitStep,
+}
+
+impl Default for PolicySplitState {
+    fn default() -> Self {
+        Self::new_policy(TextInput::new("2 of (alice, bob, carol)".to_string()))
+    }
+}
+
+impl PolicySplitState {
+    fn new_policy(input: TextInput) -> Self {
+        Self {
+            distribution: DistributionMode::default(),
+            step: AppPolicySplitStep::EnterPolicy(input),
+        }
+    }
+    fn with_step(&self, step: AppPolicySplitStep) -> Self {
+        Self {
+            distribution: self.distribution,
+            step,
+        }
+    }
+
+    fn try_parse(&self, data: &mut AppData, input: &TextInput) -> AppState {
+        match PolicyNode::parse(input.get_text()) {
+            Ok(policy) => self.with_step(AppPolicySplitStep::SelectDistribution(policy)).into(),
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+
+    fn try_split(&self, data: &mut AppData, policy: PolicyNode) -> AppState {
+        let Some(key) = data.store_key.clone() else {
+            data.error = Some("No store key available".to_string());
+            return MainMenuState::new(MainMenuAction::SplitKeyByPolicy).into();
+        };
+        let shares = match split_with_policy(&policy, key.expose()) {
+            Ok(shares) => shares,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        let policy_path = data.policy_path();
+        if let Err(e) = PublishedPolicy::new(&policy, key.expose()).save(&policy_path) {
+            data.error = Some(e);
+            return self.clone().into();
+        }
+        data.message = Some(format!("Key split into {} member shares", shares.len()));
+        match self.distribution {
+            DistributionMode::ShowAll => {}
+            DistributionMode::Sealed => {
+                return SealSharesState::new(data, MainMenuAction::SplitKeyByPolicy, SealedSplit::Policy(shares)).into();
+            }
+            _ => {
+                let shares = shares.iter().map(Into::into).collect();
+                return CeremonyState::new(MainMenuAction::SplitKeyByPolicy, self.distribution, shares).into();
+            }
+        }
+        self.with_step(AppPolicySplitStep::ShowShares {
+            format: ShareFormat::default(),
+            policy,
+            shares,
+            policy_path,
+        })
+        .into()
+    }
+}
+
+impl AppStateEvents for PolicySplitState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppPolicySplitStep::EnterPolicy(_) => "Split Key by Policy - Enter Policy".to_string(),
+            AppPolicySplitStep::SelectDistribution(_) => "Split Key by Policy - Select Distribution".to_string(),
+            AppPolicySplitStep::ShowShares { .. } => "Split Key by Policy - Shares".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppPolicySplitStep::EnterPolicy(_) => "[Esc: Cancel] [⏎ Enter: Continue]",
+            AppPolicySplitStep::SelectDistribution(_) => "[Esc: Cancel] [↑/↓: Navigate] [⏎ Enter: Split Key]",
+            AppPolicySplitStep::ShowShares { .. } => "[Esc / ⏎ Enter: Back to menu] [Tab: Change Format]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppPolicySplitStep::EnterPolicy(input) => match key.code {
+                KeyCode::Char(c) => PolicySplitState::new_policy(input.with_insert_char(c)).into(),
+                KeyCode::Backspace => PolicySplitState::new_policy(input.with_delete_char()).into(),
+                KeyCode::Left => PolicySplitState::new_policy(input.with_move_left()).into(),
+                KeyCode::Right => PolicySplitState::new_policy(input.with_move_right()).into(),
+                KeyCode::Enter => self.try_parse(data, &input),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::SplitKeyByPolicy).into(),
+                _ => self.clone().into(),
+            },
+            AppPolicySplitStep::SelectDistribution(policy) => match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    let modes: Vec<_> = DistributionMode::iter().collect();
+                    let current_idx = modes.iter().position(|m| m == &self.distribution).unwrap();
+                    let next_idx = if key.code == KeyCode::Up {
+                        (current_idx + modes.len() - 1) % modes.len()
+                    } else {
+                        (current_idx + 1) % modes.len()
+                    };
+                    Self {
+                        distribution: modes[next_idx],
+                        step: AppPolicySplitStep::SelectDistribution(policy),
+                    }
+                    .into()
+                }
+                KeyCode::Enter => self.try_split(data, policy),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::SplitKeyByPolicy).into(),
+                _ => self.clone().into(),
+            },
+            AppPolicySplitStep::ShowShares { format, policy, shares, policy_path } => match key.code {
+                KeyCode::Tab => {
+                    let formats: Vec<_> = ShareFormat::iter().collect();
+                    let current_idx = formats.iter().position(|f| f == &format).unwrap();
+                    self.with_step(AppPolicySplitStep::ShowShares {
+                        format: formats[(current_idx + 1) % formats.len()],
+                        policy,
+                        shares,
+                        policy_path,
+                    })
+                    .into()
+                }
+                KeyCode::Enter | KeyCode::Esc => MainMenuState::new(MainMenuAction::SplitKeyByPolicy).into(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        match &self.step {
+            AppPolicySplitStep::EnterPolicy(input) => {
+                let text = vec![
+                    Line::from("Enter the recovery policy, e.g. '1 of (founders: 2 of (alice, bob, carol), 2 of (1 of (alice, bob, carol), engineers: 2 of (dan, erin, frank, gina)))' or '3 of (alice*2, bob, carol)':"),
+                    Line::from(""),
+                    Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+                ];
+                frame.set_cursor_position(Position::new(
+                    area.x + input.cursor_char_pos() as u16,
+                    area.y + 2,
+                ));
+                frame.render_widget(Paragraph::new(text), area);
+            }
+            AppPolicySplitStep::SelectDistribution(policy) => {
+                let mut text = vec![
+                    Line::from(format!("Policy: {}", policy)),
+                    Line::from(""),
+                    Line::from("Select how the shares are handed over:"),
+                    Line::from(""),
+                ];
+                for mode in DistributionMode::iter() {
+                    let (name, description) = mode.to_string();
+                    let style = if mode == self.distribution {
+                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                    } else {
+                        Style::default()
+                    };
+                    text.push(Line::from(vec![
+                        Span::styled(format!(" {} ", name), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(format!(" {}", description), Style::default().fg(Color::Gray)),
+                    ]));
+                }
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+            }
+            AppPolicySplitStep::ShowShares { format, policy, shares, policy_path } => {
+                let mut text = vec![
+                    Line::from(format!("Key split along the policy {}", policy))
+                        .style(Style::default().fg(Color::Green)),
+                    Line::from(format!("Policy published to {}.", policy_path.to_string_lossy())),
+                    Line::from(format!("Format: {}", format.name())),
+                    Line::from(""),
+                ];
+                for share in shares {
+                    text.push(Line::from(Span::styled(
+                        format!("Share {}:", share.name()),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    text.push(Line::from(share.encode(*format)));
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Hand each member all the shares bearing their name, together with their path.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+            }
+        }
+    }
+}
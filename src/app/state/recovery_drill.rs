@@ -0,0 +1,204 @@
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::feldman::FeldmanCommitments;
+use crate::share_encoding::{recover_encoded_secret, EncodedShare};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppRecoveryDrillStep {
+    EnterShare {
+        shares: Vec<EncodedShare>,
+        input: TextInput,
+    },
+    Report {
+        success: bool,
+        lines: Vec<String>,
+    },
+}
+
+/// Checks that `threshold` shares still rebuild the key they were split from,
+/// without decrypting the store: the key only lives for the duration of the check
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryDrillState {
+    step: AppRecoveryDrillStep,
+}
+
+impl Default for RecoveryDrillState {
+    fn default() -> Self {
+        Self::new_share(Vec::new(), TextInput::default())
+    }
+}
+
+impl RecoveryDrillState {
+    fn new_share(shares: Vec<EncodedShare>, input: TextInput) -> Self {
+        Self {
+            step: AppRecoveryDrillStep::EnterShare { shares, input },
+        }
+    }
+
+    fn try_add_share(&self, data: &mut AppData, mut shares: Vec<EncodedShare>, input: TextInput) -> AppState {
+        if input.get_text().is_empty() {
+            return self.clone().into();
+        }
+        let share = match EncodedShare::decode(input.get_text()) {
+            Ok(share) => share,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        if let Some(first) = shares.first()
+            && let Err(e) = first.check_same_split(&share)
+        {
+            data.error = Some(e);
+            return self.clone().into();
+        }
+        if shares.iter().any(|other| other.share.x == share.share.x) {
+            data.error = Some(format!("Share {} was already entered", share.share.x));
+            return self.clone().into();
+        }
+        shares.push(share);
+
+        let threshold = shares[0].threshold as usize;
+        if shares.len() < threshold {
+            data.message = Some(format!("Share accepted, {} more needed", threshold - shares.len()));
+            return RecoveryDrillState::new_share(shares, TextInput::default()).into();
+        }
+        let (success, lines) = Self::drill(data, &shares);
+        Self {
+            step: AppRecoveryDrillStep::Report { success, lines },
+        }
+        .into()
+    }
+
+    /// Rebuilds the key and compares it against the share metadata and the published commitments
+    fn drill(data: &AppData, shares: &[EncodedShare]) -> (bool, Vec<String>) {
+        let fingerprint = hex::encode(shares[0].fingerprint);
+        let names = shares.iter().map(|share| share.name()).collect::<Vec<_>>().join(", ");
+        let mut lines = vec![format!("Shares {} of the key with fingerprint {}", names, fingerprint)];
+
+        let commitments_path = data.commitments_path();
+        let commitments = match FeldmanCommitments::load_if_exists(&commitments_path) {
+            Ok(commitments) => commitments.filter(|c| c.covers(&shares[0])),
+            Err(e) => {
+                lines.push(format!("✗ {}", e));
+                return (false, lines);
+            }
+        };
+        if let Some(commitments) = &commitments {
+            for share in shares {
+                if let Err(e) = commitments.verify(share) {
+                    lines.push(format!("✗ {}", e));
+                    return (false, lines);
+                }
+            }
+            lines.push(format!("✓ Every share verifies against {}", commitments_path.to_string_lossy()));
+        }
+
+        let recovery = match recover_encoded_secret(shares) {
+            Ok(recovery) => recovery,
+            Err(e) => {
+                lines.push(format!("✗ {}", e));
+                return (false, lines);
+            }
+        };
+        lines.push(format!("✓ The rebuilt key matches the share fingerprint {}", fingerprint));
+        if let Some(commitments) = &commitments {
+            match commitments.check_key(&recovery.secret) {
+                Ok(()) => lines.push("✓ The rebuilt key matches the published commitments".to_string()),
+                Err(e) => {
+                    lines.push(format!("✗ {}", e));
+                    return (false, lines);
+                }
+            }
+        }
+        (true, lines)
+    }
+}
+
+impl AppStateEvents for RecoveryDrillState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppRecoveryDrillStep::EnterShare { shares, .. } => match shares.first() {
+                Some(first) => format!("Recovery Drill - Enter Share {}/{}", shares.len() + 1, first.threshold),
+                None => "Recovery Drill - Enter Share".to_string(),
+            },
+            AppRecoveryDrillStep::Report { .. } => "Recovery Drill - Report".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppRecoveryDrillStep::EnterShare { .. } => "[Esc: Cancel] [⏎ Enter: Add Share]",
+            AppRecoveryDrillStep::Report { .. } => "[Esc / ⏎ Enter: Back to menu]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppRecoveryDrillStep::EnterShare { shares, input } => match key.code {
+                KeyCode::Char(c) => RecoveryDrillState::new_share(shares, input.with_insert_char(c)).into(),
+                KeyCode::Backspace => RecoveryDrillState::new_share(shares, input.with_delete_char()).into(),
+                KeyCode::Left => RecoveryDrillState::new_share(shares, input.with_move_left()).into(),
+                KeyCode::Right => RecoveryDrillState::new_share(shares, input.with_move_right()).into(),
+                KeyCode::Enter => self.try_add_share(data, shares, input),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::RecoveryDrill).into(),
+                _ => self.clone().into(),
+            },
+            AppRecoveryDrillStep::Report { .. } => match key.code {
+                KeyCode::Enter | KeyCode::Esc => MainMenuState::new(MainMenuAction::RecoveryDrill).into(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (shares, input) = match &self.step {
+            AppRecoveryDrillStep::EnterShare { shares, input } => (shares, input),
+            AppRecoveryDrillStep::Report { success, lines } => {
+                let (verdict, color) = if *success {
+                    ("Drill passed: these shares recover the key.", Color::Green)
+                } else {
+                    ("Drill failed: these shares do not recover the key.", Color::Red)
+                };
+                let mut text = vec![
+                    Line::from(Span::styled(verdict, Style::default().fg(color).add_modifier(Modifier::BOLD))),
+                    Line::from(""),
+                ];
+                text.extend(lines.iter().map(|line| Line::from(line.as_str())));
+                text.push(Line::from(""));
+                text.push(Line::from(Span::styled(
+                    "The store was not opened and the key was not kept.",
+                    Style::default().fg(Color::Gray),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+        };
+        let prompt = match shares.first() {
+            Some(first) => format!(
+                "Enter a share (words, hex or base32) - {} collected, {} more needed:",
+                shares.len(),
+                first.threshold as usize - shares.len()
+            ),
+            None => "Enter a share (words, hex or base32):".to_string(),
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::split_key::SplitKeyState;
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::feldman::FeldmanCommitments;
+use crate::secret::SecretBytes;
+use crate::share_encoding::{recover_encoded_secret, EncodedShare, ShareFormat};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenewalMode {
+    /// Same key and split, new share values: the shares left out become useless
+    Refresh,
+    /// Same key, new split with another threshold and share count
+    Reshare,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppRenewSharesStep {
+    EnterShare {
+        shares: Vec<EncodedShare>,
+        input: TextInput,
+    },
+    ShowShares {
+        format: ShareFormat,
+        shares: Vec<EncodedShare>,
+        /// Where the refreshed Feldman commitments were published, if the split had some
+        commitments_path: Option<PathBuf>,
+    },
+}
+
+/// Collects existing shares to refresh them, or to reshare the key they hold through `SplitKeyState`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenewSharesState {
+    mode: RenewalMode,
+    step: AppRenewSharesStep,
+}
+
+impl RenewSharesState {
+    pub fn new(mode: RenewalMode) -> Self {
+        Self::new_share(mode, Vec::new(), TextInput::default())
+    }
+    fn new_share(mode: RenewalMode, shares: Vec<EncodedShare>, input: TextInput) -> Self {
+        Self {
+            mode,
+            step: AppRenewSharesStep::EnterShare { shares, input },
+        }
+    }
+
+    fn menu(&self) -> AppState {
+        match self.mode {
+            RenewalMode::Refresh => MainMenuState::new(MainMenuAction::RefreshShares).into(),
+            RenewalMode::Reshare => MainMenuState::new(MainMenuAction::ReshareKey).into(),
+        }
+    }
+
+    fn try_add_share(&self, data: &mut AppData, mut shares: Vec<EncodedShare>, input: TextInput) -> AppState {
+        if input.get_text().is_empty() {
+            return match (self.mode, shares.first()) {
+                (RenewalMode::Refresh, Some(first)) if shares.len() >= first.threshold as usize => {
+                    self.try_refresh(data, &shares)
+                }
+                _ => self.clone().into(),
+            };
+        }
+        let share = match EncodedShare::decode(input.get_text()) {
+            Ok(share) => share,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        if let Some(first) = shares.first()
+            && let Err(e) = first.check_same_split(&share)
+        {
+            data.error = Some(e);
+            return self.clone().into();
+        }
+        shares.push(share);
+
+        let threshold = shares[0].threshold as usize;
+        if shares.len() < threshold {
+            data.message = Some(format!("Share accepted, {} more needed", threshold - shares.len()));
+            return RenewSharesState::new_share(self.mode, shares, TextInput::default()).into();
+        }
+        match self.mode {
+            RenewalMode::Refresh => {
+                data.message = Some(format!(
+                    "Share accepted, enter the share of every remaining custodian then ⏎ Enter on an empty line ({} collected)",
+                    shares.len()
+                ));
+                RenewSharesState::new_share(self.mode, shares, TextInput::default()).into()
+            }
+            RenewalMode::Reshare => match recover_encoded_secret(&shares) {
+                Ok(recovery) => {
+                    data.message = Some("Key recovered, choose the new split".to_string());
+                    SplitKeyState::reshare(SecretBytes::new(recovery.secret)).into()
+                }
+                Err(e) => {
+                    data.error = Some(format!("{}. Enter another share", e));
+                    RenewSharesState::new_share(self.mode, shares, TextInput::default()).into()
+                }
+            },
+        }
+    }
+
+    fn try_refresh(&self, data: &mut AppData, shares: &[EncodedShare]) -> AppState {
+        let path = data.commitments_path();
+        let commitments = match FeldmanCommitments::load_if_exists(&path) {
+            Ok(commitments) => commitments,
+            Err(e) => {
+                data.error = Some(e);
+                return self.clone().into();
+            }
+        };
+        match EncodedShare::refresh(shares, commitments.as_ref()) {
+            Ok((shares, commitments)) => {
+                data.message = Some(format!("{} shares refreshed", shares.len()));
+                let commitments_path = commitments.and_then(|commitments| match commitments.save(&path) {
+                    Ok(()) => Some(path),
+                    Err(e) => {
+                        data.error = Some(e);
+                        None
+                    }
+                });
+                Self {
+                    mode: self.mode,
+                    step: AppRenewSharesStep::ShowShares {
+                        format: ShareFormat::default(),
+                        shares,
+                        commitments_path,
+                    },
+                }
+                .into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for RenewSharesState {
+    fn get_title(&self, _data: &AppData) -> String {
+        let name = match self.mode {
+            RenewalMode::Refresh => "Refresh Shares",
+            RenewalMode::Reshare => "Reshare Key",
+        };
+        match &self.step {
+            AppRenewSharesStep::EnterShare { shares, .. } => format!("{} - Enter Share {}", name, shares.len() + 1),
+            AppRenewSharesStep::ShowShares { .. } => format!("{} - Refreshed Shares", name),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppRenewSharesStep::EnterShare { .. } => match self.mode {
+                RenewalMode::Refresh => "[Esc: Cancel] [⏎ Enter: Add Share / Refresh when empty]",
+                RenewalMode::Reshare => "[Esc: Cancel] [⏎ Enter: Add Share]",
+            },
+            AppRenewSharesStep::ShowShares { .. } => "[Esc / ⏎ Enter: Back to menu] [Tab: Change Format]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppRenewSharesStep::EnterShare { shares, input } => match key.code {
+                KeyCode::Char(c) => RenewSharesState::new_share(self.mode, shares, input.with_insert_char(c)).into(),
+                KeyCode::Backspace => RenewSharesState::new_share(self.mode, shares, input.with_delete_char()).into(),
+                KeyCode::Left => RenewSharesState::new_share(self.mode, shares, input.with_move_left()).into(),
+                KeyCode::Right => RenewSharesState::new_share(self.mode, shares, input.with_move_right()).into(),
+                KeyCode::Enter => self.try_add_share(data, shares, input),
+                KeyCode::Esc => self.menu(),
+                _ => self.clone().into(),
+            },
+            AppRenewSharesStep::ShowShares { format, shares, commitments_path } => match key.code {
+                KeyCode::Tab => {
+                    let formats: Vec<_> = ShareFormat::iter().collect();
+                    let current_idx = formats.iter().position(|f| f == &format).unwrap();
+                    Self {
+                        mode: self.mode,
+                        step: AppRenewSharesStep::ShowShares {
+                            format: formats[(current_idx + 1) % formats.len()],
+                            shares,
+                            commitments_path,
+                        },
+                    }
+                    .into()
+                }
+                KeyCode::Enter | KeyCode::Esc => self.menu(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, input) = match &self.step {
+            AppRenewSharesStep::EnterShare { shares, input } => (
+                match (self.mode, shares.first()) {
+                    (_, None) => "Enter an existing share (words, hex or base32):".to_string(),
+                    (_, Some(first)) if shares.len() < first.threshold as usize => format!(
+                        "Enter an existing share (words, hex or base32) - {} collected, {} more needed:",
+                        shares.len(),
+                        first.threshold as usize - shares.len()
+                    ),
+                    (RenewalMode::Refresh, Some(_)) => format!(
+                        "Enter the share of another remaining custodian, or leave empty to refresh the {} collected:",
+                        shares.len()
+                    ),
+                    (RenewalMode::Reshare, Some(_)) => format!(
+                        "Enter another share (words, hex or base32) - {} collected but they disagree:",
+                        shares.len()
+                    ),
+                },
+                input,
+            ),
+            AppRenewSharesStep::ShowShares { format, shares, commitments_path } => {
+                let mut text = vec![
+                    Line::from(format!(
+                        "{} shares refreshed, the key (fingerprint {}) is unchanged but shares left out no longer combine with these.",
+                        shares.len(),
+                        hex::encode(shares[0].fingerprint)
+                    ))
+                    .style(Style::default().fg(Color::Green)),
+                    Line::from(format!("Format: {}", format.name())),
+                ];
+                if let Some(path) = commitments_path {
+                    text.push(Line::from(format!(
+                        "Refreshed Feldman commitments published to {}.",
+                        path.to_string_lossy()
+                    )));
+                }
+                text.push(Line::from(""));
+                for share in shares {
+                    text.push(Line::from(Span::styled(
+                        format!("Share {}:", share.name()),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    text.push(Line::from(share.encode(*format)));
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Custodians must destroy their old share once they hold the refreshed one.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
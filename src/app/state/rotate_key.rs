@@ -0,0 +1,188 @@
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::split_key::SplitKeyState;
+use crate::app::state::{AppState, AppStateEvents};
+use crate::encrypt::{Kdf, KeySlot, SlotKind};
+use crate::secret::{SecretBytes, SecretString};
+use crate::store::{Encryption, Store};
+use bip39::Mnemonic;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum AppRotateKeyStep {
+    #[default]
+    Confirm,
+    /// The store was rewritten under this key, the backup waits for it to be recorded
+    ShowKey { key: SecretBytes, mnemonic: SecretString },
+}
+
+/// Re-encrypts the store under a new key and data key, leaving a single key slot
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RotateKeyState {
+    step: AppRotateKeyStep,
+}
+
+impl RotateKeyState {
+    /// Shows the new key again while the backup of a rotation is pending, asks to rotate otherwise
+    pub fn new(data: &AppData) -> Self {
+        if data.rotation_backup.is_some()
+            && let Some(key) = &data.store_key
+            && let Ok(mnemonic) = Mnemonic::from_entropy(key.expose())
+        {
+            return Self {
+                step: AppRotateKeyStep::ShowKey { key: key.clone(), mnemonic: SecretString::new(mnemonic.to_string()) },
+            };
+        }
+        Self::default()
+    }
+
+    fn menu() -> AppState {
+        MainMenuState::new(MainMenuAction::RotateKey).into()
+    }
+
+    fn rotate(&self, data: &mut AppData) -> AppState {
+        let path = data
+            .get_store_path_string_as_enc()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| data.store_path_or_default());
+
+        let key = SecretBytes::random(32);
+        let data_key = SecretBytes::random(32);
+        let rotated = Mnemonic::from_entropy(key.expose())
+            .map_err(|e| format!("Failed to initialize BIP39: {}", e))
+            .and_then(|mnemonic| Ok((mnemonic, KeySlot::wrap(SlotKind::Key, Kdf::Raw, key.expose(), data_key.expose())?)))
+            .and_then(|(mnemonic, slot)| {
+                let encryption = Encryption {
+                    cipher: data.store_cipher,
+                    data_key: data_key.expose(),
+                    slots: vec![slot.clone()],
+                };
+                let sections = data.resealed_sections(data_key.expose())?;
+                let backup = Store::from_sections(&sections).replace(encryption, data.store_label.clone(), &path)?;
+                Ok((mnemonic, slot, sections, backup))
+            });
+
+        match rotated {
+            Ok((mnemonic, slot, sections, backup)) => {
+                data.sections = sections;
+                data.store_key = Some(key.clone());
+                data.data_key = Some(data_key);
+                data.key_slots = vec![slot];
+                data.store_path = Some(path);
+                data.rotation_backup = backup;
+                data.message = Some(
+                    "Store re-encrypted under the new key, the other key slots and the shares of the old key no longer open it"
+                        .to_string(),
+                );
+                Self {
+                    step: AppRotateKeyStep::ShowKey { key, mnemonic: SecretString::new(mnemonic.to_string()) },
+                }
+                .into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                Self::menu()
+            }
+        }
+    }
+
+    fn confirm_recorded(data: &mut AppData) -> AppState {
+        if let Some(backup) = data.rotation_backup.take() {
+            match fs::remove_file(&backup) {
+                Ok(()) => data.message = Some(format!("Backup {} deleted", backup.to_string_lossy())),
+                Err(e) => {
+                    data.error = Some(format!("Failed to delete the backup: {}", e));
+                    data.rotation_backup = Some(backup);
+                }
+            }
+        }
+        Self::menu()
+    }
+}
+
+impl AppStateEvents for RotateKeyState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppRotateKeyStep::Confirm => "Rotate Key".to_string(),
+            AppRotateKeyStep::ShowKey { .. } => "Rotate Key - New Key".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppRotateKeyStep::Confirm => "[Esc: Cancel] [⏎ Enter: Rotate Key]",
+            AppRotateKeyStep::ShowKey { .. } => "[Esc: Back, Keep Backup] [s: Split New Key] [y: Key Recorded, Delete Backup]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match &self.step {
+            AppRotateKeyStep::Confirm => match key.code {
+                KeyCode::Enter => self.rotate(data),
+                KeyCode::Esc => Self::menu(),
+                _ => self.clone().into(),
+            },
+            AppRotateKeyStep::ShowKey { .. } => match key.code {
+                KeyCode::Char('y') => Self::confirm_recorded(data),
+                KeyCode::Char('s') => SplitKeyState::after_rotation().into(),
+                KeyCode::Esc => {
+                    if let Some(backup) = &data.rotation_backup {
+                        data.message = Some(format!(
+                            "Backup {} kept until the new key is confirmed from Rotate Key",
+                            backup.to_string_lossy()
+                        ));
+                    }
+                    Self::menu()
+                }
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, data: &AppData, frame: &mut Frame, area: Rect) {
+        let text = match &self.step {
+            AppRotateKeyStep::Confirm => vec![
+                Line::from("Generate a new key and re-encrypt the store under a new data key?"),
+                Line::from(""),
+                Line::from("The store file is replaced at once, the previous one is copied next to it as a backup."),
+                Line::from("Only the new key opens the store: every other key slot is dropped, and shares of the old key become useless."),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "Add passphrase, key file or Shamir slots again from Key Slots afterwards.",
+                    Style::default().fg(Color::Gray),
+                )),
+            ],
+            AppRotateKeyStep::ShowKey { key, mnemonic } => {
+                let mut text = vec![
+                    Line::from("Store re-encrypted, it now opens with this key:").style(Style::default().fg(Color::Green)),
+                    Line::from(""),
+                    Line::from(Span::styled("Key (hex):", Style::default().fg(Color::Yellow))),
+                    Line::from(hex::encode(key.expose())),
+                    Line::from(""),
+                    Line::from(Span::styled("BIP39 Mnemonic:", Style::default().fg(Color::Yellow))),
+                    Line::from(mnemonic.expose()),
+                    Line::from(""),
+                ];
+                if let Some(backup) = &data.rotation_backup {
+                    text.push(Line::from(format!(
+                        "The previous store file is kept at {} until you confirm the new key is recorded.",
+                        backup.to_string_lossy()
+                    )));
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Save this key securely! The old key no longer opens the store.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                text
+            }
+        };
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+    }
+}
@@ -9,16 +9,23 @@ use ratatui::Frame;
 use std::env;
 use std::path::PathBuf;
 use crate::app::text_input::TextInput;
-use crate::store::Store;
+use crate::encrypt::Cipher;
+use crate::store::{Encryption, Store};
+use strum::IntoEnumIterator;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SaveStoreState {
     encrypted: bool,
     path: TextInput,
+    /// Plaintext label of the container, encrypted stores only
+    label: TextInput,
+    label_focused: bool,
+    /// Cipher the store is encrypted with, another one than the store's migrates it
+    cipher: Cipher,
 }
 
 impl SaveStoreState {
-    pub fn new(encrypted: bool, path: Option<String>) -> Self {
+    pub fn new(encrypted: bool, path: Option<String>, label: Option<String>, cipher: Cipher) -> Self {
         let path = path.unwrap_or_else(|| {
             let current_path = env::current_dir().unwrap().to_string_lossy().to_string();
             if encrypted {
@@ -30,10 +37,20 @@ impl SaveStoreState {
         Self {
             encrypted,
             path: TextInput::new(path),
+            label: TextInput::new(label.unwrap_or_default()),
+            label_focused: false,
+            cipher,
         }
     }
-    fn from_text_input(encrypted: bool, path: TextInput) -> Self {
-        Self { encrypted, path }
+    /// Applies an edit to the focused input
+    fn with_edit(&self, edit: impl Fn(&TextInput) -> TextInput) -> Self {
+        let mut state = self.clone();
+        if self.label_focused {
+            state.label = edit(&self.label);
+        } else {
+            state.path = edit(&self.path);
+        }
+        state
     }
 }
 
@@ -43,23 +60,37 @@ impl AppStateEvents for SaveStoreState {
     }
 
     fn get_footer(&self, _data: &AppData) -> &'static str {
-        "[Esc: Cancel] [⏎ Enter: Continue]"
+        if self.encrypted {
+            "[Esc: Cancel] [Tab: Path / Label] [↑/↓: Cipher] [⏎ Enter: Continue]"
+        } else {
+            "[Esc: Cancel] [⏎ Enter: Continue]"
+        }
     }
 
     fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
         match key.code {
-            KeyCode::Char(c) => {
-                SaveStoreState::from_text_input(self.encrypted, self.path.with_insert_char(c))
-                    .into()
-            }
-            KeyCode::Left => {
-                SaveStoreState::from_text_input(self.encrypted, self.path.with_move_left()).into()
+            KeyCode::Char(c) => self.with_edit(|input| input.with_insert_char(c)).into(),
+            KeyCode::Left => self.with_edit(TextInput::with_move_left).into(),
+            KeyCode::Right => self.with_edit(TextInput::with_move_right).into(),
+            KeyCode::Backspace => self.with_edit(TextInput::with_delete_char).into(),
+            KeyCode::Tab if self.encrypted => Self {
+                label_focused: !self.label_focused,
+                ..self.clone()
             }
-            KeyCode::Right => {
-                SaveStoreState::from_text_input(self.encrypted, self.path.with_move_right()).into()
-            }
-            KeyCode::Backspace => {
-                SaveStoreState::from_text_input(self.encrypted, self.path.with_delete_char()).into()
+            .into(),
+            KeyCode::Up | KeyCode::Down if self.encrypted => {
+                let ciphers: Vec<_> = Cipher::iter().collect();
+                let current_idx = ciphers.iter().position(|c| c == &self.cipher).unwrap();
+                let next_idx = if key.code == KeyCode::Up {
+                    (current_idx + ciphers.len() - 1) % ciphers.len()
+                } else {
+                    (current_idx + 1) % ciphers.len()
+                };
+                Self {
+                    cipher: ciphers[next_idx],
+                    ..self.clone()
+                }
+                .into()
             }
             KeyCode::Enter => {
                 if !self.path.get_text().is_empty() {
@@ -74,7 +105,7 @@ impl AppStateEvents for SaveStoreState {
         }
     }
 
-    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+    fn render(&self, data: &AppData, frame: &mut Frame, area: Rect) {
         let mut text = vec![
             Line::from("Enter store file path:"),
             Line::from(""),
@@ -83,9 +114,32 @@ impl AppStateEvents for SaveStoreState {
                 Style::default().fg(Color::Yellow),
             )),
         ];
+        if self.encrypted {
+            text.push(Line::from(""));
+            text.push(Line::from("Store label (optional, stored unencrypted in the file header):"));
+            text.push(Line::from(""));
+            text.push(Line::from(Span::styled(self.label.get_text(), Style::default().fg(Color::Yellow))));
+            text.push(Line::from(""));
+            text.push(Line::from(vec![
+                Span::raw("Cipher: "),
+                Span::styled(
+                    format!(" {} ", self.cipher.name()),
+                    Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!(" {}", self.cipher.description()), Style::default().fg(Color::Gray)),
+            ]));
+            if self.cipher != data.store_cipher {
+                text.push(Line::from(format!(
+                    "The store is migrated from {} to {}, its key slots are kept.",
+                    data.store_cipher.name(),
+                    self.cipher.name()
+                )));
+            }
+        }
+        let (input, line) = if self.label_focused { (&self.label, 6) } else { (&self.path, 2) };
         frame.set_cursor_position(Position::new(
-            area.x + self.path.cursor_char_pos() as u16,
-            area.y + 2,
+            area.x + input.cursor_char_pos() as u16,
+            area.y + line,
         ));
 
         if !self.encrypted {
@@ -106,16 +160,41 @@ impl SaveStoreState {
     fn try_save_store(self, data: &mut AppData) -> AppState {
         let path = PathBuf::from(&self.path.get_text());
 
-        let key = if self.encrypted {
-            Some(data.store_key.clone().unwrap())
-        } else {
-            None
+        let encryption = match (data.data_key(), self.encrypted) {
+            (Some(data_key), true) => Some(Encryption {
+                cipher: self.cipher,
+                data_key,
+                slots: data.key_slots.clone(),
+            }),
+            (None, true) => {
+                data.error = Some("No store key, init or load an encrypted store first".to_string());
+                return self.into();
+            }
+            (_, false) => None,
         };
 
-        match Store::from_sections(&data.sections).save(key, path.clone()) {
+        let label = Some(self.label.get_text().trim().to_string()).filter(|label| !label.is_empty());
+
+        // Sealed values are only readable with the store key, an unencrypted file gets them in plaintext
+        let store = if self.encrypted {
+            Ok(Store::from_sections(&data.sections))
+        } else {
+            data.unsealed_sections().map(|sections| Store::from_sections(&sections))
+        };
+        match store.and_then(|store| store.save(encryption, label.clone(), path.clone())) {
             Ok(msg) => {
                 data.store_path = Some(path);
-                data.message = Some(msg);
+                if self.encrypted {
+                    data.store_label = label;
+                    if self.cipher != data.store_cipher {
+                        data.message = Some(format!("{} Migrated to {}.", msg, self.cipher.name()));
+                        data.store_cipher = self.cipher;
+                    } else {
+                        data.message = Some(msg);
+                    }
+                } else {
+                    data.message = Some(msg);
+                }
                 MainMenuState::new(MainMenuAction::SaveStore).into()
             }
             Err(e) => {
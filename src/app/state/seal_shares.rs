@@ -0,0 +1,156 @@
+use crate::age_shares::{load_recipients, seal_policy_shares, seal_shares};
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::policy::PolicyShare;
+use crate::share_encoding::EncodedShare;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::path::PathBuf;
+
+/// Shares of a new split, waiting for their recipients
+#[derive(Debug, Clone, PartialEq)]
+pub enum SealedSplit {
+    Threshold(Vec<EncodedShare>),
+    Policy(Vec<PolicyShare>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppSealSharesStep {
+    EnterRecipients(TextInput),
+    ShowFiles {
+        /// Share name and the file it was sealed to
+        files: Vec<(String, PathBuf)>,
+    },
+}
+
+/// Encrypts each share of a new split to its custodian's age X25519 public key, one file per share
+#[derive(Debug, Clone, PartialEq)]
+pub struct SealSharesState {
+    /// Menu entry to go back to
+    action: MainMenuAction,
+    split: SealedSplit,
+    step: AppSealSharesStep,
+}
+
+impl SealSharesState {
+    pub fn new(data: &AppData, action: MainMenuAction, split: SealedSplit) -> Self {
+        let path = data.recipients_path().to_string_lossy().to_string();
+        Self {
+            action,
+            split,
+            step: AppSealSharesStep::EnterRecipients(TextInput::new(path)),
+        }
+    }
+    fn with_step(&self, step: AppSealSharesStep) -> Self {
+        Self {
+            step,
+            ..self.clone()
+        }
+    }
+
+    fn menu(&self, data: &AppData) -> AppState {
+        MainMenuState::return_to(self.action.clone(), data)
+    }
+
+    fn try_seal(&self, data: &mut AppData, input: &TextInput) -> AppState {
+        let store_path = data.store_path_or_default();
+        let files = load_recipients(&PathBuf::from(input.get_text())).and_then(|recipients| match &self.split {
+            SealedSplit::Threshold(shares) => seal_shares(&store_path, shares, &recipients),
+            SealedSplit::Policy(shares) => seal_policy_shares(&store_path, shares, &recipients),
+        });
+        match files {
+            Ok(files) => {
+                data.message = Some(format!("{} share file(s) written", files.len()));
+                self.with_step(AppSealSharesStep::ShowFiles { files }).into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for SealSharesState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppSealSharesStep::EnterRecipients(_) => "Encrypt Shares - Enter Recipients File".to_string(),
+            AppSealSharesStep::ShowFiles { .. } => "Encrypt Shares - Share Files".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppSealSharesStep::EnterRecipients(_) => "[Esc: Abort] [⏎ Enter: Encrypt Shares]",
+            AppSealSharesStep::ShowFiles { .. } => "[Esc / ⏎ Enter: Back to menu]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppSealSharesStep::EnterRecipients(input) => match key.code {
+                KeyCode::Char(c) => self.with_step(AppSealSharesStep::EnterRecipients(input.with_insert_char(c))).into(),
+                KeyCode::Backspace => self.with_step(AppSealSharesStep::EnterRecipients(input.with_delete_char())).into(),
+                KeyCode::Left => self.with_step(AppSealSharesStep::EnterRecipients(input.with_move_left())).into(),
+                KeyCode::Right => self.with_step(AppSealSharesStep::EnterRecipients(input.with_move_right())).into(),
+                KeyCode::Enter => self.try_seal(data, &input),
+                KeyCode::Esc => {
+                    data.error = Some("No share file was written, split the key again".to_string());
+                    self.menu(data)
+                }
+                _ => self.clone().into(),
+            },
+            AppSealSharesStep::ShowFiles { .. } => match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        match &self.step {
+            AppSealSharesStep::EnterRecipients(input) => {
+                let prompt = match &self.split {
+                    SealedSplit::Threshold(shares) => format!(
+                        "Enter the recipients file: {} age1 public keys, one per line, each optionally followed by '# name':",
+                        shares.len()
+                    ),
+                    SealedSplit::Policy(_) => {
+                        "Enter the recipients file: one age1 public key per line, followed by '# <member name>':".to_string()
+                    }
+                };
+                let text = vec![
+                    Line::from(prompt),
+                    Line::from(""),
+                    Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+                ];
+                frame.set_cursor_position(Position::new(
+                    area.x + input.cursor_char_pos() as u16,
+                    area.y + 2,
+                ));
+                frame.render_widget(Paragraph::new(text), area);
+            }
+            AppSealSharesStep::ShowFiles { files } => {
+                let mut text = vec![
+                    Line::from(format!("{} share(s) encrypted, each file only opens with its custodian's key:", files.len()))
+                        .style(Style::default().fg(Color::Green)),
+                    Line::from(""),
+                ];
+                for (name, path) in files {
+                    text.push(Line::from(format!("Share {} → {}", name, path.to_string_lossy())));
+                }
+                text.push(Line::from(""));
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Send each file to its custodian, they recover with '<file.age> <identity file>' as their share.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::app::data::AppData;
+use crate::app::state::init_store::InitStoreState;
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::slip39::{generate_mnemonics, Slip39Group, DEFAULT_ITERATION_EXPONENT};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppSlip39Step {
+    EnterGroups(TextInput),
+    EnterPassphrase {
+        group_threshold: u8,
+        groups: Vec<Slip39Group>,
+        input: TextInput,
+    },
+    ShowMnemonics(Vec<Vec<String>>),
+}
+
+/// Emits SLIP-0039 mnemonics for the key generated by `InitStoreState`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slip39SharesState {
+    init: InitStoreState,
+    step: AppSlip39Step,
+}
+
+impl Slip39SharesState {
+    pub fn new(init: InitStoreState) -> Self {
+        Self {
+            init,
+            step: AppSlip39Step::EnterGroups(TextInput::new("1: 2/3".to_string())),
+        }
+    }
+    fn with_step(&self, step: AppSlip39Step) -> Self {
+        Self {
+            init: self.init.clone(),
+            step,
+        }
+    }
+
+    /// Parses `<group threshold>: <member threshold>/<member count>, ...`.
+    /// A single group can be written without its group threshold: `2/3`.
+    fn parse_groups(text: &str) -> Result<(u8, Vec<Slip39Group>), String> {
+        let error = || "Invalid groups. Expected e.g. '1: 2/3' or '2: 2/3, 1/1, 3/5'".to_string();
+        let (group_threshold, groups) = match text.split_once(':') {
+            Some((threshold, groups)) => (threshold.trim().parse::<u8>().map_err(|_| error())?, groups),
+            None => (1, text),
+        };
+        let groups = groups
+            .split(',')
+            .map(|group| {
+                let (t, n) = group.split_once('/').ok_or_else(error)?;
+                Ok(Slip39Group {
+                    member_threshold: t.trim().parse().map_err(|_| error())?,
+                    member_count: n.trim().parse().map_err(|_| error())?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok((group_threshold, groups))
+    }
+}
+
+impl AppStateEvents for Slip39SharesState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppSlip39Step::EnterGroups(_) => "SLIP-0039 Shares - Enter Groups".to_string(),
+            AppSlip39Step::EnterPassphrase { .. } => "SLIP-0039 Shares - Enter Passphrase".to_string(),
+            AppSlip39Step::ShowMnemonics(_) => "SLIP-0039 Shares - Mnemonics".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppSlip39Step::ShowMnemonics(_) => "[Esc: Back] [⏎ Enter: Initialize Store]",
+            _ => "[Esc: Back] [⏎ Enter: Continue]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppSlip39Step::EnterGroups(input) => match key.code {
+                KeyCode::Char(c) => self.with_step(AppSlip39Step::EnterGroups(input.with_insert_char(c))).into(),
+                KeyCode::Backspace => self.with_step(AppSlip39Step::EnterGroups(input.with_delete_char())).into(),
+                KeyCode::Left => self.with_step(AppSlip39Step::EnterGroups(input.with_move_left())).into(),
+                KeyCode::Right => self.with_step(AppSlip39Step::EnterGroups(input.with_move_right())).into(),
+                KeyCode::Enter => match Self::parse_groups(input.get_text()) {
+                    Ok((group_threshold, groups)) => self
+                        .with_step(AppSlip39Step::EnterPassphrase {
+                            group_threshold,
+                            groups,
+                            input: TextInput::default(),
+                        })
+                        .into(),
+                    Err(e) => {
+                        data.error = Some(e);
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Esc => self.init.clone().into(),
+                _ => self.clone().into(),
+            },
+            AppSlip39Step::EnterPassphrase { group_threshold, groups, input } => {
+                let with_input = |input: TextInput| {
+                    self.with_step(AppSlip39Step::EnterPassphrase {
+                        group_threshold,
+                        groups: groups.clone(),
+                        input,
+                    })
+                };
+                match key.code {
+                    KeyCode::Char(c) => with_input(input.with_insert_char(c)).into(),
+                    KeyCode::Backspace => with_input(input.with_delete_char()).into(),
+                    KeyCode::Left => with_input(input.with_move_left()).into(),
+                    KeyCode::Right => with_input(input.with_move_right()).into(),
+                    KeyCode::Enter => match generate_mnemonics(
+                        group_threshold,
+                        &groups,
+                        self.init.generated_key(),
+                        input.get_text(),
+                        DEFAULT_ITERATION_EXPONENT,
+                    ) {
+                        Ok(mnemonics) => {
+                            data.message = Some("SLIP-0039 mnemonics generated".to_string());
+                            self.with_step(AppSlip39Step::ShowMnemonics(mnemonics)).into()
+                        }
+                        Err(e) => {
+                            data.error = Some(e);
+                            self.clone().into()
+                        }
+                    },
+                    KeyCode::Esc => self.init.clone().into(),
+                    _ => self.clone().into(),
+                }
+            }
+            AppSlip39Step::ShowMnemonics(_) => match key.code {
+                KeyCode::Enter => self.init.initialize(data),
+                KeyCode::Esc => self.init.clone().into(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, displayed, input) = match &self.step {
+            AppSlip39Step::EnterGroups(input) => (
+                "Enter groups as '<group threshold>: <threshold>/<count>, ...' (e.g. '2: 2/3, 1/1, 3/5'):",
+                input.get_text().to_string(),
+                input,
+            ),
+            AppSlip39Step::EnterPassphrase { input, .. } => (
+                "Enter an optional passphrase (printable ASCII, leave empty for none):",
+                "*".repeat(input.get_text().chars().count()),
+                input,
+            ),
+            AppSlip39Step::ShowMnemonics(groups) => {
+                let mut text = Vec::new();
+                for (gi, mnemonics) in groups.iter().enumerate() {
+                    text.push(Line::from(Span::styled(
+                        format!("Group {}:", gi + 1),
+                        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                    )));
+                    for (mi, mnemonic) in mnemonics.iter().enumerate() {
+                        text.push(Line::from(Span::styled(
+                            format!("Share {}:", mi + 1),
+                            Style::default().fg(Color::Yellow),
+                        )));
+                        text.push(Line::from(mnemonic.as_str()));
+                    }
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Without the passphrase, the mnemonics recover a different key.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(displayed, Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
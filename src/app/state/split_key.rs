@@ -0,0 +1,395 @@
+use crate::app::data::AppData;
+use crate::app::state::ceremony::{CeremonyState, DistributionMode};
+use crate::app::state::seal_shares::{SealSharesState, SealedSplit};
+use crate::app::state::key_slots::KeySlotsState;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::encrypt::{Kdf, SlotKind};
+use crate::feldman::split_secret_verifiable;
+use crate::secret::SecretBytes;
+use crate::shamir_secret_sharing::{split_secret, ShamirBackend};
+use crate::share_encoding::{EncodedShare, ShareFormat};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum AppSplitKeyStep {
+    #[default]
+    SelectBackend,
+    EnterThreshold(TextInput),
+    EnterShareCount { threshold: usize, input: TextInput },
+    SelectDistribution { threshold: usize, share_count: usize },
+    ShowShares {
+        format: ShareFormat,
+        shares: Vec<EncodedShare>,
+        /// Where the Feldman commitments were published, prime field backend only
+        commitments_path: Option<PathBuf>,
+    },
+}
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SplitKeyState {
+    /// Key recovered from shares when resharing, the store key is split otherwise
+    key: Option<SecretBytes>,
+    /// `key` is new and wraps the data key in a Shamir slot once split
+    new_slot: bool,
+    /// The store key was just rotated, Rotate Key asks to confirm it once split
+    after_rotation: bool,
+    backend: ShamirBackend,
+    distribution: DistributionMode,
+    step: AppSplitKeyStep,
+}
+
+impl SplitKeyState {
+    /// Splits a key recovered from shares with a new threshold and share count
+    pub fn reshare(key: SecretBytes) -> Self {
+        Self {
+            key: Some(key),
+            ..Self::default()
+        }
+    }
+    /// Splits a fresh random key, which becomes a Shamir slot of the store
+    pub fn new_slot(key: SecretBytes) -> Self {
+        Self {
+            key: Some(key),
+            new_slot: true,
+            ..Self::default()
+        }
+    }
+    /// Splits the store key that Rotate Key just generated, going back to it once done
+    pub fn after_rotation() -> Self {
+        Self {
+            after_rotation: true,
+            ..Self::default()
+        }
+    }
+    fn new_backend(&self, backend: ShamirBackend) -> Self {
+        Self {
+            backend,
+            ..self.with_step(AppSplitKeyStep::SelectBackend)
+        }
+    }
+    fn new_threshold(&self, input: TextInput) -> Self {
+        self.with_step(AppSplitKeyStep::EnterThreshold(input))
+    }
+    fn new_share_count(&self, threshold: usize, input: TextInput) -> Self {
+        self.with_step(AppSplitKeyStep::EnterShareCount { threshold, input })
+    }
+    fn new_distribution(&self, distribution: DistributionMode, threshold: usize, share_count: usize) -> Self {
+        Self {
+            distribution,
+            ..self.with_step(AppSplitKeyStep::SelectDistribution { threshold, share_count })
+        }
+    }
+    fn with_step(&self, step: AppSplitKeyStep) -> Self {
+        Self {
+            step,
+            ..self.clone()
+        }
+    }
+    fn menu_action(&self) -> MainMenuAction {
+        match self.key {
+            Some(_) if self.new_slot => MainMenuAction::KeySlots,
+            Some(_) => MainMenuAction::ReshareKey,
+            None if self.after_rotation => MainMenuAction::RotateKey,
+            None => MainMenuAction::SplitKey,
+        }
+    }
+    fn menu(&self, data: &AppData) -> AppState {
+        MainMenuState::return_to(self.menu_action(), data)
+    }
+
+    pub(crate) fn parse_count(input: &TextInput, name: &str) -> Result<usize, String> {
+        match input.get_text().trim().parse::<usize>() {
+            Ok(n) if (1..=255).contains(&n) => Ok(n),
+            _ => Err(format!("{} must be a number between 1 and 255", name)),
+        }
+    }
+
+    fn try_split_key(&self, data: &mut AppData, threshold: usize, share_count: usize) -> AppState {
+        let key = match self.key.as_ref().or(data.store_key.as_ref()) {
+            Some(key) => key.clone(),
+            None => {
+                data.error = Some("No store key available".to_string());
+                return self.menu(data);
+            }
+        };
+
+        let split = match self.backend {
+            ShamirBackend::PrimeField => split_secret_verifiable(key.expose(), threshold, share_count)
+                .map(|(shares, commitments)| (shares, Some(commitments))),
+            ShamirBackend::Gf256 => split_secret(self.backend, key.expose(), threshold, share_count).map(|shares| (shares, None)),
+        };
+        match split {
+            Ok((shares, commitments)) => {
+                if !self.new_slot {
+                    data.message = Some(format!("Key split into {} shares", share_count));
+                } else {
+                    // The slot is only added once its key exists as shares
+                    match KeySlotsState::new_slot(data, SlotKind::Shamir, Kdf::Raw, key.expose()) {
+                        Ok(slot) => KeySlotsState::add_slot(data, slot),
+                        Err(e) => {
+                            data.error = Some(e);
+                            return self.menu(data);
+                        }
+                    }
+                }
+                let commitments_path = commitments.and_then(|commitments| {
+                    let path = data.commitments_path();
+                    match commitments.save(&path) {
+                        Ok(()) => Some(path),
+                        Err(e) => {
+                            data.error = Some(e);
+                            None
+                        }
+                    }
+                });
+                let shares = EncodedShare::wrap_all(shares, threshold, key.expose());
+                match self.distribution {
+                    DistributionMode::ShowAll => {}
+                    DistributionMode::Sealed => {
+                        return SealSharesState::new(data, self.menu_action(), SealedSplit::Threshold(shares)).into();
+                    }
+                    _ => {
+                        let shares = shares.iter().map(Into::into).collect();
+                        return CeremonyState::new(self.menu_action(), self.distribution, shares).into();
+                    }
+                }
+                self.with_step(AppSplitKeyStep::ShowShares {
+                    format: ShareFormat::default(),
+                    shares,
+                    commitments_path,
+                })
+                .into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for SplitKeyState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppSplitKeyStep::SelectBackend => "Split Key - Select Backend".to_string(),
+            AppSplitKeyStep::EnterThreshold(_) => "Split Key - Enter Threshold".to_string(),
+            AppSplitKeyStep::EnterShareCount { .. } => "Split Key - Enter Share Count".to_string(),
+            AppSplitKeyStep::SelectDistribution { .. } => "Split Key - Select Distribution".to_string(),
+            AppSplitKeyStep::ShowShares { .. } => "Split Key - Shares".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppSplitKeyStep::SelectBackend | AppSplitKeyStep::SelectDistribution { .. } => {
+                "[Esc: Cancel] [↑/↓: Navigate] [⏎ Enter: Select]"
+            }
+            AppSplitKeyStep::ShowShares { .. } if self.after_rotation => {
+                "[Esc / ⏎ Enter: Back to Rotate Key] [Tab: Change Format]"
+            }
+            AppSplitKeyStep::ShowShares { .. } => "[Esc / ⏎ Enter: Back to menu] [Tab: Change Format]",
+            _ => "[Esc: Cancel] [⏎ Enter: Continue]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppSplitKeyStep::SelectBackend => match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    let backends: Vec<_> = ShamirBackend::iter().collect();
+                    let current_idx = backends.iter().position(|b| b == &self.backend).unwrap();
+                    let next_idx = if key.code == KeyCode::Up {
+                        (current_idx + backends.len() - 1) % backends.len()
+                    } else {
+                        (current_idx + 1) % backends.len()
+                    };
+                    self.new_backend(backends[next_idx]).into()
+                }
+                KeyCode::Enter => self.new_threshold(TextInput::default()).into(),
+                KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+            AppSplitKeyStep::EnterThreshold(input) => match key.code {
+                KeyCode::Char(c) => self.new_threshold(input.with_insert_char(c)).into(),
+                KeyCode::Backspace => self.new_threshold(input.with_delete_char()).into(),
+                KeyCode::Left => self.new_threshold(input.with_move_left()).into(),
+                KeyCode::Right => self.new_threshold(input.with_move_right()).into(),
+                KeyCode::Enter => match Self::parse_count(&input, "Threshold") {
+                    Ok(threshold) => {
+                        self.new_share_count(threshold, TextInput::default()).into()
+                    }
+                    Err(e) => {
+                        data.error = Some(e);
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+            AppSplitKeyStep::EnterShareCount { threshold, input } => match key.code {
+                KeyCode::Char(c) => {
+                    self.new_share_count(threshold, input.with_insert_char(c)).into()
+                }
+                KeyCode::Backspace => {
+                    self.new_share_count(threshold, input.with_delete_char()).into()
+                }
+                KeyCode::Left => {
+                    self.new_share_count(threshold, input.with_move_left()).into()
+                }
+                KeyCode::Right => {
+                    self.new_share_count(threshold, input.with_move_right()).into()
+                }
+                KeyCode::Enter => match Self::parse_count(&input, "Share count") {
+                    Ok(share_count) if share_count < threshold => {
+                        data.error = Some(format!(
+                            "Share count must be at least the threshold ({})",
+                            threshold
+                        ));
+                        self.clone().into()
+                    }
+                    Ok(share_count) => self.new_distribution(self.distribution, threshold, share_count).into(),
+                    Err(e) => {
+                        data.error = Some(e);
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+            AppSplitKeyStep::SelectDistribution { threshold, share_count } => match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    let modes: Vec<_> = DistributionMode::iter().collect();
+                    let current_idx = modes.iter().position(|m| m == &self.distribution).unwrap();
+                    let next_idx = if key.code == KeyCode::Up {
+                        (current_idx + modes.len() - 1) % modes.len()
+                    } else {
+                        (current_idx + 1) % modes.len()
+                    };
+                    self.new_distribution(modes[next_idx], threshold, share_count).into()
+                }
+                KeyCode::Enter => self.try_split_key(data, threshold, share_count),
+                KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+            AppSplitKeyStep::ShowShares { format, shares, commitments_path } => match key.code {
+                KeyCode::Tab => {
+                    let formats: Vec<_> = ShareFormat::iter().collect();
+                    let current_idx = formats.iter().position(|f| f == &format).unwrap();
+                    self.with_step(AppSplitKeyStep::ShowShares {
+                        format: formats[(current_idx + 1) % formats.len()],
+                        shares,
+                        commitments_path,
+                    })
+                    .into()
+                }
+                KeyCode::Enter | KeyCode::Esc => self.menu(data),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, input) = match &self.step {
+            AppSplitKeyStep::SelectBackend => {
+                let mut text = vec![
+                    Line::from("Select the secret sharing backend:"),
+                    Line::from(""),
+                ];
+                for backend in ShamirBackend::iter() {
+                    let (name, description) = backend.to_string();
+                    let style = if backend == self.backend {
+                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                    } else {
+                        Style::default()
+                    };
+                    text.push(Line::from(vec![
+                        Span::styled(format!(" {} ", name), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(format!(" {}", description), Style::default().fg(Color::Gray)),
+                    ]));
+                }
+                frame.render_widget(Paragraph::new(text), area);
+                return;
+            }
+            AppSplitKeyStep::SelectDistribution { .. } => {
+                let mut text = vec![
+                    Line::from("Select how the shares are handed over:"),
+                    Line::from(""),
+                ];
+                for mode in DistributionMode::iter() {
+                    let (name, description) = mode.to_string();
+                    let style = if mode == self.distribution {
+                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                    } else {
+                        Style::default()
+                    };
+                    text.push(Line::from(vec![
+                        Span::styled(format!(" {} ", name), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(format!(" {}", description), Style::default().fg(Color::Gray)),
+                    ]));
+                }
+                frame.render_widget(Paragraph::new(text), area);
+                return;
+            }
+            AppSplitKeyStep::EnterThreshold(input) => {
+                ("Enter threshold (number of shares needed to recover the key):", input)
+            }
+            AppSplitKeyStep::EnterShareCount { input, .. } => {
+                ("Enter share count (number of shares to generate):", input)
+            }
+            AppSplitKeyStep::ShowShares { format, shares, commitments_path } => {
+                let mut text = vec![
+                    Line::from(format!(
+                        "Store key split into {} shares, any {} of them recover the key (fingerprint {}).",
+                        shares.len(),
+                        shares[0].threshold,
+                        hex::encode(shares[0].fingerprint)
+                    ))
+                    .style(Style::default().fg(Color::Green)),
+                    Line::from(format!("Format: {}", format.name())),
+                ];
+                if let Some(path) = commitments_path {
+                    text.push(Line::from(format!(
+                        "Feldman commitments published to {}, custodians can check their share with Verify Share.",
+                        path.to_string_lossy()
+                    )));
+                }
+                text.push(Line::from(""));
+                for share in shares {
+                    text.push(Line::from(Span::styled(
+                        format!("Share {}/{}:", share.share.x, share.share_count),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    text.push(Line::from(share.encode(*format)));
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Give each share to a different custodian and keep them apart.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+
+                let paragraph = Paragraph::new(text).wrap(Wrap { trim: false });
+                frame.render_widget(paragraph, area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::app::data::AppData;
+use crate::app::state::edit_store::EditStoreState;
+use crate::app::state::split_key::SplitKeyState;
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::secret::SecretString;
+use crate::share_encoding::{EncodedShare, ShareFormat};
+use crate::split_value::split_value;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppSplitValueStep {
+    EnterThreshold(TextInput),
+    EnterShareCount { threshold: usize, input: TextInput },
+    ShowShares { format: ShareFormat, shares: Vec<EncodedShare> },
+}
+
+/// Splits a single entry value into shares for dual control, the value is replaced by a split marker
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitValueState {
+    /// Section and entry indices of the value
+    section: usize,
+    entry: usize,
+    /// Editor position to go back to
+    flattened_index: usize,
+    step: AppSplitValueStep,
+}
+
+impl SplitValueState {
+    pub fn new(section: usize, entry: usize, flattened_index: usize) -> Self {
+        Self {
+            section,
+            entry,
+            flattened_index,
+            step: AppSplitValueStep::EnterThreshold(TextInput::default()),
+        }
+    }
+    fn with_step(&self, step: AppSplitValueStep) -> Self {
+        Self {
+            step,
+            ..self.clone()
+        }
+    }
+
+    fn editor(&self) -> AppState {
+        EditStoreState::at(self.flattened_index).into()
+    }
+
+    fn try_split_value(&self, data: &mut AppData, threshold: usize, share_count: usize) -> AppState {
+        let split = data
+            .reveal_value(self.section, self.entry)
+            .and_then(|value| split_value(value.expose(), threshold, share_count));
+        let entry = &mut data.sections[self.section].entries[self.entry];
+        match split {
+            Ok((marker, shares)) => {
+                entry.value = SecretString::new(marker.to_value());
+                data.message = Some(format!(
+                    "Value of '{}' split into {} shares, save the store to keep only the marker",
+                    entry.key, share_count
+                ));
+                self.with_step(AppSplitValueStep::ShowShares {
+                    format: ShareFormat::default(),
+                    shares,
+                })
+                .into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for SplitValueState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppSplitValueStep::EnterThreshold(_) => "Split Value - Enter Threshold".to_string(),
+            AppSplitValueStep::EnterShareCount { .. } => "Split Value - Enter Share Count".to_string(),
+            AppSplitValueStep::ShowShares { .. } => "Split Value - Shares".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppSplitValueStep::ShowShares { .. } => "[Esc / ⏎ Enter: Back to store] [Tab: Change Format]",
+            _ => "[Esc: Cancel] [⏎ Enter: Continue]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppSplitValueStep::EnterThreshold(input) => {
+                let with_input = |input: TextInput| -> AppState { self.with_step(AppSplitValueStep::EnterThreshold(input)).into() };
+                match key.code {
+                    KeyCode::Char(c) => with_input(input.with_insert_char(c)),
+                    KeyCode::Backspace => with_input(input.with_delete_char()),
+                    KeyCode::Left => with_input(input.with_move_left()),
+                    KeyCode::Right => with_input(input.with_move_right()),
+                    KeyCode::Enter => match SplitKeyState::parse_count(&input, "Threshold") {
+                        Ok(threshold) => self
+                            .with_step(AppSplitValueStep::EnterShareCount { threshold, input: TextInput::default() })
+                            .into(),
+                        Err(e) => {
+                            data.error = Some(e);
+                            self.clone().into()
+                        }
+                    },
+                    KeyCode::Esc => self.editor(),
+                    _ => self.clone().into(),
+                }
+            }
+            AppSplitValueStep::EnterShareCount { threshold, input } => {
+                let with_input = |input: TextInput| -> AppState { self.with_step(AppSplitValueStep::EnterShareCount { threshold, input }).into() };
+                match key.code {
+                    KeyCode::Char(c) => with_input(input.with_insert_char(c)),
+                    KeyCode::Backspace => with_input(input.with_delete_char()),
+                    KeyCode::Left => with_input(input.with_move_left()),
+                    KeyCode::Right => with_input(input.with_move_right()),
+                    KeyCode::Enter => match SplitKeyState::parse_count(&input, "Share count") {
+                        Ok(share_count) => self.try_split_value(data, threshold, share_count),
+                        Err(e) => {
+                            data.error = Some(e);
+                            self.clone().into()
+                        }
+                    },
+                    KeyCode::Esc => self.editor(),
+                    _ => self.clone().into(),
+                }
+            }
+            AppSplitValueStep::ShowShares { format, shares } => match key.code {
+                KeyCode::Tab => {
+                    let formats: Vec<_> = ShareFormat::iter().collect();
+                    let current_idx = formats.iter().position(|f| f == &format).unwrap();
+                    self.with_step(AppSplitValueStep::ShowShares {
+                        format: formats[(current_idx + 1) % formats.len()],
+                        shares,
+                    })
+                    .into()
+                }
+                KeyCode::Enter | KeyCode::Esc => self.editor(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, data: &AppData, frame: &mut Frame, area: Rect) {
+        let key = &data.sections[self.section].entries[self.entry].key;
+        let (prompt, input) = match &self.step {
+            AppSplitValueStep::EnterThreshold(input) => (
+                format!("Enter threshold (number of shares needed to recombine the value of '{}'):", key),
+                input,
+            ),
+            AppSplitValueStep::EnterShareCount { input, .. } => {
+                ("Enter share count (number of shares to generate):".to_string(), input)
+            }
+            AppSplitValueStep::ShowShares { format, shares } => {
+                let mut text = vec![
+                    Line::from(format!(
+                        "Value of '{}' split into {} shares, any {} of them recombine it from the store editor.",
+                        key,
+                        shares.len(),
+                        shares[0].threshold
+                    ))
+                    .style(Style::default().fg(Color::Green)),
+                    Line::from(format!("Format: {}", format.name())),
+                    Line::from(""),
+                ];
+                for share in shares {
+                    text.push(Line::from(Span::styled(
+                        format!("Share {}/{}:", share.share.x, share.share_count),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    text.push(Line::from(share.encode(*format)));
+                    text.push(Line::from(""));
+                }
+                text.push(Line::from(Span::styled(
+                    "⚠ IMPORTANT: Give each share to a different custodian, the value is no longer in the store.",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )));
+                frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), area);
+                return;
+            }
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
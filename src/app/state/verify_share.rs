@@ -0,0 +1,136 @@
+use crate::app::data::AppData;
+use crate::app::state::main_menu::{MainMenuAction, MainMenuState};
+use crate::app::state::{AppState, AppStateEvents};
+use crate::app::text_input::TextInput;
+use crate::feldman::FeldmanCommitments;
+use crate::share_encoding::EncodedShare;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppVerifyShareStep {
+    EnterPath(TextInput),
+    EnterShare {
+        commitments: FeldmanCommitments,
+        input: TextInput,
+    },
+}
+
+/// Lets a custodian check their share against the published Feldman commitments, without any other share
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyShareState {
+    step: AppVerifyShareStep,
+}
+
+impl VerifyShareState {
+    pub fn new(data: &AppData) -> Self {
+        Self::new_path(TextInput::new(data.commitments_path().to_string_lossy().to_string()))
+    }
+    fn new_path(path: TextInput) -> Self {
+        Self {
+            step: AppVerifyShareStep::EnterPath(path),
+        }
+    }
+    fn new_share(commitments: FeldmanCommitments, input: TextInput) -> Self {
+        Self {
+            step: AppVerifyShareStep::EnterShare { commitments, input },
+        }
+    }
+
+    fn try_verify_share(&self, data: &mut AppData, commitments: &FeldmanCommitments, input: &TextInput) -> AppState {
+        if input.get_text().is_empty() {
+            return self.clone().into();
+        }
+        let result = EncodedShare::decode(input.get_text()).and_then(|share| {
+            commitments.verify(&share)?;
+            Ok(share)
+        });
+        match result {
+            Ok(share) => {
+                data.message = Some(format!(
+                    "Share {} is valid for the key with fingerprint {}",
+                    share.name(),
+                    commitments.fingerprint
+                ));
+                VerifyShareState::new_share(commitments.clone(), TextInput::default()).into()
+            }
+            Err(e) => {
+                data.error = Some(e);
+                self.clone().into()
+            }
+        }
+    }
+}
+
+impl AppStateEvents for VerifyShareState {
+    fn get_title(&self, _data: &AppData) -> String {
+        match &self.step {
+            AppVerifyShareStep::EnterPath(_) => "Verify Share - Enter Commitments Path".to_string(),
+            AppVerifyShareStep::EnterShare { .. } => "Verify Share - Enter Share".to_string(),
+        }
+    }
+
+    fn get_footer(&self, _data: &AppData) -> &'static str {
+        match &self.step {
+            AppVerifyShareStep::EnterPath(_) => "[Esc: Cancel] [⏎ Enter: Continue]",
+            AppVerifyShareStep::EnterShare { .. } => "[Esc: Back to menu] [⏎ Enter: Verify Share]",
+        }
+    }
+
+    fn handle_key(&self, data: &mut AppData, key: KeyEvent) -> AppState {
+        match self.step.clone() {
+            AppVerifyShareStep::EnterPath(path) => match key.code {
+                KeyCode::Char(c) => VerifyShareState::new_path(path.with_insert_char(c)).into(),
+                KeyCode::Backspace => VerifyShareState::new_path(path.with_delete_char()).into(),
+                KeyCode::Left => VerifyShareState::new_path(path.with_move_left()).into(),
+                KeyCode::Right => VerifyShareState::new_path(path.with_move_right()).into(),
+                KeyCode::Enter => match FeldmanCommitments::load(&PathBuf::from(path.get_text())) {
+                    Ok(commitments) => VerifyShareState::new_share(commitments, TextInput::default()).into(),
+                    Err(e) => {
+                        data.error = Some(e);
+                        self.clone().into()
+                    }
+                },
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::VerifyShare).into(),
+                _ => self.clone().into(),
+            },
+            AppVerifyShareStep::EnterShare { commitments, input } => match key.code {
+                KeyCode::Char(c) => VerifyShareState::new_share(commitments, input.with_insert_char(c)).into(),
+                KeyCode::Backspace => VerifyShareState::new_share(commitments, input.with_delete_char()).into(),
+                KeyCode::Left => VerifyShareState::new_share(commitments, input.with_move_left()).into(),
+                KeyCode::Right => VerifyShareState::new_share(commitments, input.with_move_right()).into(),
+                KeyCode::Enter => self.try_verify_share(data, &commitments, &input),
+                KeyCode::Esc => MainMenuState::new(MainMenuAction::VerifyShare).into(),
+                _ => self.clone().into(),
+            },
+        }
+    }
+
+    fn render(&self, _data: &AppData, frame: &mut Frame, area: Rect) {
+        let (prompt, input) = match &self.step {
+            AppVerifyShareStep::EnterPath(input) => ("Enter the Feldman commitments file path:".to_string(), input),
+            AppVerifyShareStep::EnterShare { commitments, input } => (
+                format!(
+                    "Enter a share (words, hex or base32) of the key with fingerprint {}, threshold {}:",
+                    commitments.fingerprint, commitments.threshold
+                ),
+                input,
+            ),
+        };
+
+        let text = vec![
+            Line::from(prompt),
+            Line::from(""),
+            Line::from(Span::styled(input.get_text(), Style::default().fg(Color::Yellow))),
+        ];
+        frame.set_cursor_position(Position::new(
+            area.x + input.cursor_char_pos() as u16,
+            area.y + 2,
+        ));
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
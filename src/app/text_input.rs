@@ -1,51 +1,60 @@
+use crate::secret::SecretString;
+
+/// Typed text, kept as a secret since it may be a key, a passphrase or a share
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct TextInput {
-    text: String,
+    text: SecretString,
     cursor_pos: usize,
 }
 
 impl TextInput {
     pub fn new(text: String) -> Self {
+        Self::from_secret(SecretString::new(text))
+    }
+    /// Input holding a secret already, such as a revealed entry value, without copying it out
+    pub fn from_secret(text: SecretString) -> Self {
         Self {
-            cursor_pos: text.len(),
+            cursor_pos: text.expose().len(),
             text,
         }
     }
-    pub fn get_text(&self) -> &String {
-        &self.text
+    pub fn get_text(&self) -> &str {
+        self.text.expose()
     }
 
     pub fn cursor_char_pos(&self) -> usize {
-        self.text[..self.cursor_pos].chars().count()
+        self.get_text()[..self.cursor_pos].chars().count()
     }
 
+    /// Edits allocate the new text at its final size, so that no stale copy is left by a reallocation
     pub fn with_insert_char(&self, c: char) -> Self {
-        let mut ti = self.clone();
-        ti.text.insert(self.cursor_pos, c);
-        ti.cursor_pos += c.len_utf8();
-        ti
+        Self {
+            text: self.text.with_insert(self.cursor_pos, c),
+            cursor_pos: self.cursor_pos + c.len_utf8(),
+        }
     }
 
     pub fn with_delete_char(&self) -> Self {
-        let mut ti = self.clone();
-        if ti.cursor_pos > 0 {
-            // Find the previous char boundary
-            let prev_pos = ti.text[..ti.cursor_pos]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-            ti.text.remove(prev_pos);
-            ti.cursor_pos = prev_pos;
+        if self.cursor_pos == 0 {
+            return self.clone();
+        }
+        // Find the previous char boundary
+        let prev_pos = self.get_text()[..self.cursor_pos]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        Self {
+            text: self.text.with_remove(prev_pos),
+            cursor_pos: prev_pos,
         }
-        ti
     }
 
     pub fn with_move_left(&self) -> Self {
         let mut ti = self.clone();
         if ti.cursor_pos > 0 {
             // Move to previous char boundary
-            ti.cursor_pos = ti.text[..ti.cursor_pos]
+            ti.cursor_pos = self.get_text()[..ti.cursor_pos]
                 .char_indices()
                 .next_back()
                 .map(|(i, _)| i)
@@ -56,9 +65,9 @@ impl TextInput {
 
     pub fn with_move_right(&self) -> Self {
         let mut ti = self.clone();
-        if ti.cursor_pos < ti.text.len() {
+        if ti.cursor_pos < self.get_text().len() {
             // Move to next char boundary
-            if let Some((_, c)) = ti.text[ti.cursor_pos..].char_indices().next() {
+            if let Some((_, c)) = self.get_text()[ti.cursor_pos..].char_indices().next() {
                 ti.cursor_pos += c.len_utf8();
             }
         }
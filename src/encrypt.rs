@@ -1,63 +1,881 @@
-use std::io::{stdout, Write};
 use aead::Key;
 use aes_gcm::{
-    aead::{Aead, AeadCore, KeyInit, Nonce, OsRng},
+    aead::{Aead, AeadCore, KeyInit, Nonce, OsRng, Payload},
     Aes256Gcm,
 };
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::XChaCha20Poly1305;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use strum_macros::EnumIter;
+use crate::secret::SecretBytes;
+use zeroize::Zeroize;
 
-fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> (Vec<u8>, Nonce<Aes256Gcm>) {
-    let key = Key::<Aes256Gcm>::from_slice(key);
-    let cipher = Aes256Gcm::new(key);
+/// Magic bytes opening every store container
+pub const CONTAINER_MAGIC: &[u8; 4] = b"BNKY";
+/// Current version of the container format
+pub const CONTAINER_VERSION: u8 = 2;
+/// First container version, whose payload is encrypted with the store key itself
+const DIRECT_KEY_VERSION: u8 = 1;
+/// The label is stored in plaintext and only names the store, so it is kept short
+pub const MAX_CONTAINER_LABEL_BYTES: usize = 255;
+/// The slot count is written on a single byte
+pub const MAX_KEY_SLOTS: usize = 255;
+/// Nonce length of the legacy headerless files
+const LEGACY_NONCE_BYTES: usize = 12;
+pub(crate) const KEY_BYTES: usize = 32;
+const ARGON2_SALT_BYTES: usize = 16;
+/// memory (KiB), iterations and parallelism as u32 BE, then the salt
+const ARGON2_PARAMS_BYTES: usize = 12 + ARGON2_SALT_BYTES;
+/// Argon2id costs beyond these are refused, so that a tampered header cannot make unlocking
+/// allocate or run without bound. The memory cap is the largest preset, Sensitive (1 GiB, 4 passes).
+const MAX_ARGON2_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ARGON2_ITERATIONS: u32 = 8;
+const MAX_ARGON2_PARALLELISM: u32 = 4;
+/// Hashed before the contents of a key file, so that the slot key is not a plain SHA-256 of the file
+const KEY_FILE_CONTEXT: &[u8] = b"bunkeys key file";
 
-    let nonce = Aes256Gcm::generate_nonce(&mut OsRng); // 96-bit nonce
-    let ciphertext = cipher.encrypt(&nonce, plaintext).unwrap();
+/// AEAD cipher encrypting the store or wrapping a slot, recorded in the header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum Cipher {
+    /// Random 192-bit nonces never collide in practice, however many times a key encrypts
+    #[default]
+    XChaCha20Poly1305,
+    /// Random 96-bit nonces limit how many times one key should encrypt
+    Aes256Gcm,
+}
+
+impl Cipher {
+    pub fn id(self) -> u8 {
+        match self {
+            Cipher::Aes256Gcm => 1,
+            Cipher::XChaCha20Poly1305 => 2,
+        }
+    }
+    pub fn from_id(id: u8) -> Result<Self, String> {
+        match id {
+            1 => Ok(Cipher::Aes256Gcm),
+            2 => Ok(Cipher::XChaCha20Poly1305),
+            _ => Err(format!("Unknown cipher {}, the store was written by a newer version", id)),
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Cipher::Aes256Gcm => "AES-256-GCM",
+            Cipher::XChaCha20Poly1305 => "XChaCha20-Poly1305",
+        }
+    }
+    pub fn description(self) -> &'static str {
+        match self {
+            Cipher::Aes256Gcm => "96-bit random nonces, hardware accelerated on most CPUs",
+            Cipher::XChaCha20Poly1305 => "192-bit random nonces, no limit on the number of saves",
+        }
+    }
+    pub fn nonce_len(self) -> usize {
+        match self {
+            Cipher::Aes256Gcm => 12,
+            Cipher::XChaCha20Poly1305 => 24,
+        }
+    }
+
+    pub(crate) fn generate_nonce(self) -> Vec<u8> {
+        match self {
+            Cipher::Aes256Gcm => Aes256Gcm::generate_nonce(&mut OsRng).to_vec(),
+            Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::generate_nonce(&mut OsRng).to_vec(),
+        }
+    }
+    pub(crate) fn encrypt(self, key: &[u8], nonce: &[u8], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+        if key.len() != KEY_BYTES {
+            return Err(format!("Encryption key must be {} bytes", KEY_BYTES));
+        }
+        let payload = Payload { msg, aad };
+        match self {
+            Cipher::Aes256Gcm => Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+                .encrypt(Nonce::<Aes256Gcm>::from_slice(nonce), payload),
+            Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::new(Key::<XChaCha20Poly1305>::from_slice(key))
+                .encrypt(Nonce::<XChaCha20Poly1305>::from_slice(nonce), payload),
+        }
+        .map_err(|e| format!("Encryption failed: {}", e))
+    }
+    pub(crate) fn decrypt(self, key: &[u8], nonce: &[u8], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+        if key.len() != KEY_BYTES {
+            return Err(format!("Decryption key must be {} bytes", KEY_BYTES));
+        }
+        let payload = Payload { msg, aad };
+        match self {
+            Cipher::Aes256Gcm => Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+                .decrypt(Nonce::<Aes256Gcm>::from_slice(nonce), payload),
+            Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::new(Key::<XChaCha20Poly1305>::from_slice(key))
+                .decrypt(Nonce::<XChaCha20Poly1305>::from_slice(nonce), payload),
+        }
+        .map_err(|e| format!("Decryption failed: {}", e))
+    }
+}
 
-    (ciphertext, nonce)
+/// Argon2id costs and salt of a passphrase slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argon2Params {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: [u8; ARGON2_SALT_BYTES],
 }
 
-fn decrypt(key: &[u8; 32], nonce: &Nonce<Aes256Gcm>, ciphertext: &[u8]) -> Vec<u8> {
-    let key = Key::<Aes256Gcm>::from_slice(key);
-    let cipher = Aes256Gcm::new(key);
+impl Argon2Params {
+    /// Costs for a new passphrase, with a fresh random salt
+    pub fn new(memory_kib: u32, iterations: u32) -> Result<Self, String> {
+        let mut salt = [0u8; ARGON2_SALT_BYTES];
+        OsRng.fill_bytes(&mut salt);
+        Argon2Params { memory_kib, iterations, parallelism: 1, salt }.checked()
+    }
 
-    cipher.decrypt(nonce, ciphertext).unwrap()
+    /// Refuses costs above the caps, whether typed in or read from a header
+    fn checked(self) -> Result<Self, String> {
+        if self.memory_kib > MAX_ARGON2_MEMORY_KIB {
+            return Err(format!("Argon2id memory of {} KiB is above the {} KiB limit", self.memory_kib, MAX_ARGON2_MEMORY_KIB));
+        }
+        if self.iterations > MAX_ARGON2_ITERATIONS {
+            return Err(format!("Argon2id pass count of {} is above the limit of {}", self.iterations, MAX_ARGON2_ITERATIONS));
+        }
+        if self.parallelism > MAX_ARGON2_PARALLELISM {
+            return Err(format!("Argon2id lane count of {} is above the limit of {}", self.parallelism, MAX_ARGON2_PARALLELISM));
+        }
+        Ok(self)
+    }
 }
 
-/// Encrypt a string to a byte vector with nonce prepended
-/// The output format is: [nonce (12 bytes) | ciphertext]
-pub fn encrypt_string(key: Vec<u8>, plaintext: Vec<u8>) -> Result<Vec<u8>, String> {
-    let key_obj = Key::<Aes256Gcm>::from_slice(&key);
-    let cipher = Aes256Gcm::new(key_obj);
+/// How a slot key is derived from what the user types.
+/// A raw key, or one recovered from shares, always opens its slot whatever the KDF.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Kdf {
+    /// The slot key is a random 256-bit key and is used as is
+    #[default]
+    Raw,
+    /// The slot key is stretched from a passphrase
+    Argon2id(Argon2Params),
+    /// The slot key is hashed from the contents of a file
+    KeyFile,
+}
 
-    let nonce = Aes256Gcm::generate_nonce(&mut OsRng); // 96-bit (12 bytes) nonce
-    let ciphertext = cipher
-        .encrypt(&nonce, plaintext.as_slice())
-        .map_err(|e| format!("Encryption failed: {}", e))?;
+impl Kdf {
+    pub fn id(&self) -> u8 {
+        match self {
+            Kdf::Raw => 0,
+            Kdf::Argon2id(_) => 1,
+            Kdf::KeyFile => 2,
+        }
+    }
+    pub fn from_id(id: u8, params: &[u8]) -> Result<Self, String> {
+        match (id, params) {
+            (0, []) => Ok(Kdf::Raw),
+            (0, _) => Err("The raw key derivation takes no parameters".to_string()),
+            (1, params) if params.len() == ARGON2_PARAMS_BYTES => {
+                let u32_at = |i: usize| u32::from_be_bytes(params[i..i + 4].try_into().unwrap());
+                Argon2Params {
+                    memory_kib: u32_at(0),
+                    iterations: u32_at(4),
+                    parallelism: u32_at(8),
+                    salt: params[12..].try_into().unwrap(),
+                }
+                .checked()
+                .map(Kdf::Argon2id)
+            }
+            (1, _) => Err(format!("Argon2id takes {} bytes of parameters", ARGON2_PARAMS_BYTES)),
+            (2, []) => Ok(Kdf::KeyFile),
+            (2, _) => Err("The key file derivation takes no parameters".to_string()),
+            _ => Err(format!("Unknown key derivation {}, the store was written by a newer version", id)),
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kdf::Raw => "Raw key",
+            Kdf::Argon2id(_) => "Argon2id passphrase",
+            Kdf::KeyFile => "Key file",
+        }
+    }
+    /// Parameters written in the header
+    pub fn params(&self) -> Vec<u8> {
+        match self {
+            Kdf::Raw | Kdf::KeyFile => Vec::new(),
+            Kdf::Argon2id(params) => {
+                let mut bytes = Vec::with_capacity(ARGON2_PARAMS_BYTES);
+                bytes.extend_from_slice(&params.memory_kib.to_be_bytes());
+                bytes.extend_from_slice(&params.iterations.to_be_bytes());
+                bytes.extend_from_slice(&params.parallelism.to_be_bytes());
+                bytes.extend_from_slice(&params.salt);
+                bytes
+            }
+        }
+    }
 
-    // Prepend nonce to ciphertext
-    let mut result = Vec::with_capacity(12 + ciphertext.len());
-    result.extend_from_slice(&nonce);
-    result.extend_from_slice(&ciphertext);
+    /// Turns a passphrase or the contents of a key file into a 256-bit key,
+    /// which takes a while by design for passphrases
+    pub fn derive_key(&self, secret: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Kdf::Raw => Err("This key is not derived, enter it directly".to_string()),
+            Kdf::Argon2id(params) => {
+                let costs = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(KEY_BYTES))
+                    .map_err(|e| format!("Invalid Argon2id parameters: {}", e))?;
+                let mut key = vec![0u8; KEY_BYTES];
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, costs)
+                    .hash_password_into(secret, &params.salt, &mut key)
+                    .map_err(|e| format!("Key derivation failed: {}", e))?;
+                Ok(key)
+            }
+            Kdf::KeyFile if secret.is_empty() => Err("The key file is empty".to_string()),
+            Kdf::KeyFile => Ok(Sha256::new().chain_update(KEY_FILE_CONTEXT).chain_update(secret).finalize().to_vec()),
+        }
+    }
+
+    /// Key the input stands for under this derivation, None when the input does not fit it
+    fn input_key(&self, input: &UnlockInput) -> Option<Result<Vec<u8>, String>> {
+        match (input, self) {
+            (UnlockInput::Key(key), _) => Some(Ok(key.clone())),
+            (UnlockInput::Passphrase(passphrase), Kdf::Argon2id(_)) => Some(self.derive_key(passphrase.as_bytes())),
+            (UnlockInput::KeyFile(contents), Kdf::KeyFile) => Some(self.derive_key(contents)),
+            _ => None,
+        }
+    }
+}
+
+/// What the user gives to open a store
+#[derive(Clone, PartialEq)]
+pub enum UnlockInput {
+    /// 256-bit key, typed as hex or a mnemonic, or recovered from shares
+    Key(Vec<u8>),
+    Passphrase(String),
+    /// Contents of a key file
+    KeyFile(Vec<u8>),
+}
+
+impl UnlockInput {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnlockInput::Key(_) => "key",
+            UnlockInput::Passphrase(_) => "passphrase",
+            UnlockInput::KeyFile(_) => "key file",
+        }
+    }
+}
+
+impl Drop for UnlockInput {
+    fn drop(&mut self) {
+        match self {
+            UnlockInput::Key(bytes) | UnlockInput::KeyFile(bytes) => bytes.zeroize(),
+            UnlockInput::Passphrase(passphrase) => passphrase.zeroize(),
+        }
+    }
+}
+
+/// How the user holds the key of a slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotKind {
+    /// Random key shown as hex and BIP39 mnemonic
+    Key,
+    Passphrase,
+    KeyFile,
+    /// Random key only ever handed out as Shamir shares
+    Shamir,
+}
+
+impl SlotKind {
+    pub fn id(self) -> u8 {
+        match self {
+            SlotKind::Key => 1,
+            SlotKind::Passphrase => 2,
+            SlotKind::KeyFile => 3,
+            SlotKind::Shamir => 4,
+        }
+    }
+    pub fn from_id(id: u8) -> Result<Self, String> {
+        match id {
+            1 => Ok(SlotKind::Key),
+            2 => Ok(SlotKind::Passphrase),
+            3 => Ok(SlotKind::KeyFile),
+            4 => Ok(SlotKind::Shamir),
+            _ => Err(format!("Unknown key slot kind {}, the store was written by a newer version", id)),
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            SlotKind::Key => "Key / mnemonic",
+            SlotKind::Passphrase => "Passphrase",
+            SlotKind::KeyFile => "Key file",
+            SlotKind::Shamir => "Shamir shares",
+        }
+    }
+}
+
+/// A copy of the store data key, encrypted under the key of the slot.
+/// Any slot opens the store, and slots come and go without re-encrypting the payload.
+/// Binary layout:
+/// [kind | cipher id | KDF id | KDF params length (u16 BE) | KDF params | nonce length | nonce | wrapped key length | wrapped key]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySlot {
+    pub kind: SlotKind,
+    pub cipher: Cipher,
+    pub kdf: Kdf,
+    nonce: Vec<u8>,
+    wrapped_key: Vec<u8>,
+}
+
+impl KeySlot {
+    /// Encrypts the data key under `slot_key`, which derives from the user input as `kdf` says
+    pub fn wrap(kind: SlotKind, kdf: Kdf, slot_key: &[u8], data_key: &[u8]) -> Result<Self, String> {
+        let cipher = Cipher::default();
+        let mut slot = KeySlot { kind, cipher, kdf, nonce: cipher.generate_nonce(), wrapped_key: Vec::new() };
+        slot.wrapped_key = cipher.encrypt(slot_key, &slot.nonce, data_key, &slot.associated_data())?;
+        Ok(slot)
+    }
+
+    /// Decrypts the data key with the slot key, failing if the key does not open this slot
+    pub fn unwrap(&self, slot_key: &[u8]) -> Result<Vec<u8>, String> {
+        self.cipher
+            .decrypt(slot_key, &self.nonce, &self.wrapped_key, &self.associated_data())
+            .map_err(|_| format!("The key does not open this {} slot", self.kind.name().to_lowercase()))
+    }
+
+    /// Key opening this slot from the user input, None when the input does not match the slot
+    pub fn slot_key(&self, input: &UnlockInput) -> Option<Result<Vec<u8>, String>> {
+        self.kdf.input_key(input)
+    }
+
+    /// The kind and derivation are authenticated, so that they cannot be swapped between slots
+    fn associated_data(&self) -> Vec<u8> {
+        let mut aad = vec![self.kind.id(), self.cipher.id(), self.kdf.id()];
+        aad.extend_from_slice(&self.kdf.params());
+        aad
+    }
+
+    fn write_to(&self, bytes: &mut Vec<u8>) {
+        let params = self.kdf.params();
+        bytes.push(self.kind.id());
+        bytes.push(self.cipher.id());
+        bytes.push(self.kdf.id());
+        bytes.extend_from_slice(&(params.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&params);
+        bytes.push(self.nonce.len() as u8);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.push(self.wrapped_key.len() as u8);
+        bytes.extend_from_slice(&self.wrapped_key);
+    }
+
+    fn read_from(reader: &mut HeaderReader) -> Result<Self, String> {
+        let kind = SlotKind::from_id(reader.byte()?)?;
+        let cipher = Cipher::from_id(reader.byte()?)?;
+        let kdf = reader.kdf()?;
+        let nonce = reader.nonce(cipher)?;
+        let wrapped_len = reader.byte()? as usize;
+        let wrapped_key = reader.take(wrapped_len)?.to_vec();
+        Ok(KeySlot { kind, cipher, kdf, nonce, wrapped_key })
+    }
+}
+
+/// Tries every slot the input fits, returning the data key and the key of the slot that opened
+pub fn unlock_data_key(slots: &[KeySlot], input: &UnlockInput) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let mut tried = false;
+    let mut derivation_error = None;
+    for slot in slots {
+        let Some(slot_key) = slot.slot_key(input) else {
+            continue;
+        };
+        tried = true;
+        // A slot whose derivation fails must not hide the slots after it
+        let slot_key = match slot_key {
+            Ok(slot_key) => slot_key,
+            Err(e) => {
+                derivation_error = Some(e);
+                continue;
+            }
+        };
+        if let Ok(data_key) = slot.unwrap(&slot_key) {
+            return Ok((data_key, slot_key));
+        }
+    }
+    match (tried, derivation_error) {
+        (_, Some(e)) => Err(e),
+        (true, None) => Err(format!("No key slot opens with this {}", input.name())),
+        (false, None) => Err(format!("The store has no {} slot", input.name())),
+    }
+}
+
+/// How the key encrypting the payload is protected
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyProtection {
+    /// Version 1: the store key encrypts the payload itself, derived as described
+    Direct(Kdf),
+    /// Version 2: a random data key encrypts the payload, wrapped in each slot
+    Slots(Vec<KeySlot>),
+}
+
+/// Header of a store container.
+/// Binary layout (v2):
+/// [magic "BNKY" | version | cipher id | nonce length | nonce | label length | label | slot count | slots]
+/// followed by the ciphertext. Everything up to the label is the AEAD associated data,
+/// the slots authenticate themselves so that they can be rewritten alone.
+/// Binary layout (v1), entirely authenticated:
+/// [magic "BNKY" | version | cipher id | nonce length | nonce | KDF id | KDF params length (u16 BE) | KDF params | label length | label]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerHeader {
+    pub cipher: Cipher,
+    pub nonce: Vec<u8>,
+    /// Plaintext name of the store, readable without the key
+    pub label: Option<String>,
+    pub keys: KeyProtection,
+}
+
+impl ContainerHeader {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.associated_data();
+        if let KeyProtection::Slots(slots) = &self.keys {
+            bytes.push(slots.len() as u8);
+            for slot in slots {
+                slot.write_to(&mut bytes);
+            }
+        }
+        bytes
+    }
+
+    /// Part of the header authenticated along with the payload
+    fn associated_data(&self) -> Vec<u8> {
+        let label = self.label.as_deref().unwrap_or_default();
+        let mut bytes = Vec::with_capacity(10 + self.nonce.len() + label.len());
+        bytes.extend_from_slice(CONTAINER_MAGIC);
+        bytes.push(match self.keys {
+            KeyProtection::Direct(_) => DIRECT_KEY_VERSION,
+            KeyProtection::Slots(_) => CONTAINER_VERSION,
+        });
+        bytes.push(self.cipher.id());
+        bytes.push(self.nonce.len() as u8);
+        bytes.extend_from_slice(&self.nonce);
+        if let KeyProtection::Direct(kdf) = &self.keys {
+            let params = kdf.params();
+            bytes.push(kdf.id());
+            bytes.extend_from_slice(&(params.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&params);
+        }
+        bytes.push(label.len() as u8);
+        bytes.extend_from_slice(label.as_bytes());
+        bytes
+    }
+
+    /// Reads the header at the start of a container, returning it along with its length in bytes
+    pub fn parse(data: &[u8]) -> Result<(Self, usize), String> {
+        let mut reader = HeaderReader { data, pos: 0 };
+        if reader.take(CONTAINER_MAGIC.len())? != CONTAINER_MAGIC {
+            return Err("Not a Bunkeys store container".to_string());
+        }
+        let version = reader.byte()?;
+        if version != CONTAINER_VERSION && version != DIRECT_KEY_VERSION {
+            return Err(format!("Unsupported store container version {}", version));
+        }
+        let cipher = Cipher::from_id(reader.byte()?)?;
+        let nonce = reader.nonce(cipher)?;
+        let direct_kdf = match version {
+            DIRECT_KEY_VERSION => Some(reader.kdf()?),
+            _ => None,
+        };
+        let label_len = reader.byte()? as usize;
+        let label = match reader.take(label_len)? {
+            [] => None,
+            label => Some(String::from_utf8(label.to_vec()).map_err(|_| "Store label is not valid UTF-8".to_string())?),
+        };
+        let keys = match direct_kdf {
+            Some(kdf) => KeyProtection::Direct(kdf),
+            None => {
+                let slot_count = reader.byte()? as usize;
+                let slots = (0..slot_count)
+                    .map(|_| KeySlot::read_from(&mut reader))
+                    .collect::<Result<Vec<_>, _>>()?;
+                KeyProtection::Slots(slots)
+            }
+        };
+        Ok((ContainerHeader { cipher, nonce, label, keys }, reader.pos))
+    }
 
+    /// Key slots of the container, empty for version 1
+    pub fn slots(&self) -> &[KeySlot] {
+        match &self.keys {
+            KeyProtection::Direct(_) => &[],
+            KeyProtection::Slots(slots) => slots,
+        }
+    }
+}
+
+struct HeaderReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| "Store container header is truncated".to_string())?;
+        self.pos += len;
+        Ok(bytes)
+    }
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    fn nonce(&mut self, cipher: Cipher) -> Result<Vec<u8>, String> {
+        let nonce_len = self.byte()? as usize;
+        if nonce_len != cipher.nonce_len() {
+            return Err(format!("{} takes a {}-byte nonce", cipher.name(), cipher.nonce_len()));
+        }
+        Ok(self.take(nonce_len)?.to_vec())
+    }
+    fn kdf(&mut self) -> Result<Kdf, String> {
+        let kdf_id = self.byte()?;
+        let params_len = u16::from_be_bytes([self.byte()?, self.byte()?]) as usize;
+        Kdf::from_id(kdf_id, self.take(params_len)?)
+    }
+}
+
+/// Whether the data starts like a store container rather than a legacy headerless file
+pub fn is_container(data: &[u8]) -> bool {
+    data.starts_with(CONTAINER_MAGIC)
+}
+
+/// Encrypt a byte vector with the store data key into a container: [header | ciphertext].
+/// Each slot holds the data key wrapped under a key of its own, possibly with another cipher.
+/// Encrypting again under another cipher migrates the store, the slots still open it.
+pub fn encrypt_string(
+    cipher: Cipher,
+    data_key: &[u8],
+    plaintext: Vec<u8>,
+    slots: Vec<KeySlot>,
+    label: Option<String>,
+) -> Result<Vec<u8>, String> {
+    if slots.is_empty() {
+        return Err("The store needs at least one key slot".to_string());
+    }
+    if slots.len() > MAX_KEY_SLOTS {
+        return Err(format!("A store holds at most {} key slots", MAX_KEY_SLOTS));
+    }
+    if label.as_ref().is_some_and(|label| label.len() > MAX_CONTAINER_LABEL_BYTES) {
+        return Err(format!("Store label is longer than {} bytes", MAX_CONTAINER_LABEL_BYTES));
+    }
+    let header = ContainerHeader {
+        cipher,
+        nonce: cipher.generate_nonce(),
+        label: label.filter(|label| !label.is_empty()),
+        keys: KeyProtection::Slots(slots),
+    };
+    seal_container(data_key, SecretBytes::new(plaintext).expose(), &header)
+}
+
+fn seal_container(key: &[u8], plaintext: &[u8], header: &ContainerHeader) -> Result<Vec<u8>, String> {
+    let ciphertext = header.cipher.encrypt(key, &header.nonce, plaintext, &header.associated_data())?;
+    let mut result = header.to_bytes();
+    result.extend_from_slice(&ciphertext);
     Ok(result)
 }
 
-/// Decrypt a byte vector (with prepended nonce) to a string
-/// The input format is: [nonce (12 bytes) | ciphertext]
-pub fn decrypt_string(key: Vec<u8>, data: Vec<u8>) -> Result<Vec<u8>, String> {
-    if data.len() < 12 {
+/// A decrypted store
+pub struct Decrypted {
+    pub plaintext: SecretBytes,
+    /// None for legacy headerless files
+    pub header: Option<ContainerHeader>,
+    /// Key the payload is encrypted with
+    pub data_key: SecretBytes,
+    /// Key of the slot that opened, which opens it again when entered as a raw key
+    pub slot_key: SecretBytes,
+}
+
+/// Decrypt a store container, or a legacy headerless file, trying every key slot the input fits.
+/// Legacy files are [nonce (12 bytes) | ciphertext] without associated data, and only open with a key.
+pub fn decrypt_string(input: &UnlockInput, data: &[u8]) -> Result<Decrypted, String> {
+    if is_container(data) {
+        match decrypt_container(input, data) {
+            Ok(decrypted) => return Ok(decrypted),
+            // A legacy nonce may start with the magic bytes, by a 2⁻³² chance
+            Err(e) => return decrypt_legacy(input, data).map_err(|_| e),
+        }
+    }
+    decrypt_legacy(input, data)
+}
+
+fn decrypt_container(input: &UnlockInput, data: &[u8]) -> Result<Decrypted, String> {
+    let (header, header_len) = ContainerHeader::parse(data)?;
+    let (data_key, slot_key) = match &header.keys {
+        KeyProtection::Direct(kdf) => {
+            let key = kdf
+                .input_key(input)
+                .ok_or_else(|| format!("This store does not open with a {}", input.name()))??;
+            (key.clone(), key)
+        }
+        KeyProtection::Slots(slots) => unlock_data_key(slots, input)?,
+    };
+    let (data_key, slot_key) = (SecretBytes::new(data_key), SecretBytes::new(slot_key));
+    let plaintext = header
+        .cipher
+        .decrypt(data_key.expose(), &header.nonce, &data[header_len..], &header.associated_data())?;
+    Ok(Decrypted { plaintext: SecretBytes::new(plaintext), header: Some(header), data_key, slot_key })
+}
+
+fn decrypt_legacy(input: &UnlockInput, data: &[u8]) -> Result<Decrypted, String> {
+    let UnlockInput::Key(key) = input else {
+        return Err(format!("This store does not open with a {}", input.name()));
+    };
+    if data.len() < LEGACY_NONCE_BYTES {
         return Err("Data too short: must contain at least 12-byte nonce".to_string());
     }
+    let (nonce, ciphertext) = data.split_at(LEGACY_NONCE_BYTES);
+    let plaintext = Cipher::Aes256Gcm.decrypt(key, nonce, ciphertext, &[])?;
+    Ok(Decrypted {
+        plaintext: SecretBytes::new(plaintext),
+        header: None,
+        data_key: SecretBytes::new(key.clone()),
+        slot_key: SecretBytes::new(key.clone()),
+    })
+}
 
-    // Extract nonce (first 12 bytes)
-    let nonce = Nonce::<Aes256Gcm>::from_slice(&data[0..12]);
+/// Replaces the key slots of a container, leaving the encrypted payload untouched.
+/// The data key must decrypt the payload, so that the new slots do open the store.
+pub fn rewrite_slots(data: &[u8], data_key: &[u8], slots: Vec<KeySlot>) -> Result<Vec<u8>, String> {
+    if slots.is_empty() {
+        return Err("The store needs at least one key slot".to_string());
+    }
+    if slots.len() > MAX_KEY_SLOTS {
+        return Err(format!("A store holds at most {} key slots", MAX_KEY_SLOTS));
+    }
+    let (mut header, header_len) = ContainerHeader::parse(data)?;
+    if !matches!(header.keys, KeyProtection::Slots(_)) {
+        return Err("The store file has no key slots yet, save the store first".to_string());
+    }
+    let ciphertext = &data[header_len..];
+    header
+        .cipher
+        .decrypt(data_key, &header.nonce, ciphertext, &header.associated_data())
+        .map(SecretBytes::new)
+        .map_err(|_| "The store file was written with another data key, save the store first".to_string())?;
+    header.keys = KeyProtection::Slots(slots);
+    let mut result = header.to_bytes();
+    result.extend_from_slice(ciphertext);
+    Ok(result)
+}
 
-    // Extract ciphertext (remaining bytes)
-    let ciphertext = &data[12..];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
 
-    let key_obj = Key::<Aes256Gcm>::from_slice(&key);
-    let cipher = Aes256Gcm::new(key_obj);
+    const KEY: [u8; 32] = [7; 32];
+    const DATA_KEY: [u8; 32] = [9; 32];
 
-    cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|e| format!("Decryption failed: {}", e))
+    fn legacy_encrypt(plaintext: &[u8]) -> Vec<u8> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&KEY));
+        let mut data = nonce.to_vec();
+        data.extend(cipher.encrypt(&nonce, plaintext).unwrap());
+        data
+    }
+
+    fn key_slot(key: &[u8]) -> KeySlot {
+        KeySlot::wrap(SlotKind::Key, Kdf::Raw, key, &DATA_KEY).unwrap()
+    }
+
+    fn seal(plaintext: &[u8], slots: Vec<KeySlot>, label: Option<&str>) -> Vec<u8> {
+        encrypt_string(Cipher::Aes256Gcm, &DATA_KEY, plaintext.to_vec(), slots, label.map(str::to_string)).unwrap()
+    }
+
+    #[test]
+    fn container_round_trips_with_label() {
+        let data = seal(b"secrets", vec![key_slot(&KEY)], Some("Team vault"));
+        assert!(data.starts_with(CONTAINER_MAGIC));
+        let decrypted = decrypt_string(&UnlockInput::Key(KEY.to_vec()), &data).unwrap();
+        assert_eq!(decrypted.plaintext.expose(), b"secrets");
+        assert_eq!(decrypted.data_key.expose(), DATA_KEY);
+        assert_eq!(decrypted.slot_key.expose(), KEY);
+        let header = decrypted.header.unwrap();
+        assert_eq!(header.cipher, Cipher::Aes256Gcm);
+        assert_eq!(header.slots().len(), 1);
+        assert_eq!(header.label.as_deref(), Some("Team vault"));
+        assert_eq!(ContainerHeader::parse(&data).unwrap().0, header);
+    }
+
+    #[test]
+    fn legacy_files_are_still_readable() {
+        let decrypted = decrypt_string(&UnlockInput::Key(KEY.to_vec()), &legacy_encrypt(b"old store")).unwrap();
+        assert_eq!(decrypted.plaintext.expose(), b"old store");
+        assert_eq!(decrypted.header, None);
+        assert!(decrypt_string(&UnlockInput::Passphrase("old".to_string()), &legacy_encrypt(b"old store")).is_err());
+    }
+
+    #[test]
+    fn version_1_containers_are_still_readable() {
+        let header = ContainerHeader {
+            cipher: Cipher::Aes256Gcm,
+            nonce: Cipher::Aes256Gcm.generate_nonce(),
+            label: Some("v1".to_string()),
+            keys: KeyProtection::Direct(Kdf::Raw),
+        };
+        let data = seal_container(&KEY, b"secrets", &header).unwrap();
+        assert_eq!(data[4], DIRECT_KEY_VERSION);
+        let decrypted = decrypt_string(&UnlockInput::Key(KEY.to_vec()), &data).unwrap();
+        assert_eq!(decrypted.plaintext.expose(), b"secrets");
+        assert_eq!(decrypted.data_key.expose(), KEY);
+        assert_eq!(decrypted.header, Some(header));
+        assert!(rewrite_slots(&data, &KEY, vec![key_slot(&KEY)]).is_err());
+    }
+
+    #[test]
+    fn tampered_header_is_rejected() {
+        let data = seal(b"secrets", vec![key_slot(&KEY)], Some("Team vault"));
+        let label_pos = data.windows(4).position(|w| w == b"Team").unwrap();
+        let mut tampered = data.clone();
+        tampered[label_pos] = b'S';
+        assert!(decrypt_string(&UnlockInput::Key(KEY.to_vec()), &tampered).is_err());
+        assert!(decrypt_string(&UnlockInput::Key(vec![8; 32]), &data).is_err());
+
+        // Relabelling a slot breaks its own authentication
+        let mut slot = key_slot(&KEY);
+        slot.kind = SlotKind::Shamir;
+        assert!(slot.unwrap(&KEY).is_err());
+    }
+
+    #[test]
+    fn unknown_versions_and_algorithms_are_rejected() {
+        let data = seal(b"secrets", vec![key_slot(&KEY)], None);
+        let mut newer = data.clone();
+        newer[4] = CONTAINER_VERSION + 1;
+        assert!(ContainerHeader::parse(&newer).unwrap_err().contains("version"));
+        let mut unknown_cipher = data.clone();
+        unknown_cipher[5] = 0xff;
+        assert!(ContainerHeader::parse(&unknown_cipher).unwrap_err().contains("cipher"));
+        assert!(ContainerHeader::parse(&data[..8]).is_err());
+        assert!(encrypt_string(Cipher::Aes256Gcm, &DATA_KEY, Vec::new(), vec![key_slot(&KEY)], Some("x".repeat(256))).is_err());
+        assert!(encrypt_string(Cipher::Aes256Gcm, &DATA_KEY, Vec::new(), Vec::new(), None).is_err());
+    }
+
+    #[test]
+    fn passphrase_key_round_trips_through_the_header() {
+        let kdf = Kdf::Argon2id(Argon2Params::new(64, 1).unwrap());
+        let key = kdf.derive_key(b"correct horse battery staple").unwrap();
+        assert_eq!(key.len(), KEY_BYTES);
+        assert_ne!(kdf.derive_key(b"wrong horse battery staple").unwrap(), key);
+
+        let slot = KeySlot::wrap(SlotKind::Passphrase, kdf.clone(), &key, &DATA_KEY).unwrap();
+        let data = seal(b"secrets", vec![slot], None);
+        let (header, _) = ContainerHeader::parse(&data).unwrap();
+        assert_eq!(header.slots()[0].kdf, kdf);
+        let passphrase = UnlockInput::Passphrase("correct horse battery staple".to_string());
+        assert_eq!(decrypt_string(&passphrase, &data).unwrap().plaintext.expose(), b"secrets");
+        // The derived key opens the slot as a raw key, as when it was split into shares
+        assert_eq!(decrypt_string(&UnlockInput::Key(key.clone()), &data).unwrap().plaintext.expose(), b"secrets");
+
+        // A fresh salt gives another key for the same passphrase
+        let resalted = Kdf::Argon2id(Argon2Params::new(64, 1).unwrap());
+        assert_ne!(resalted.derive_key(b"correct horse battery staple").unwrap(), key);
+        assert!(Kdf::Raw.derive_key(b"passphrase").is_err());
+        assert!(Kdf::from_id(1, &[0; 4]).is_err());
+    }
+
+    #[test]
+    fn argon2_costs_above_the_limits_are_rejected() {
+        let sensitive = Kdf::Argon2id(Argon2Params::new(1024 * 1024, 4).unwrap());
+        assert_eq!(Kdf::from_id(1, &sensitive.params()), Ok(sensitive.clone()));
+
+        for (offset, name) in [(0, "memory"), (4, "pass count"), (8, "lane count")] {
+            let mut params = sensitive.params();
+            params[offset..offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+            assert!(Kdf::from_id(1, &params).unwrap_err().contains(name));
+        }
+        assert!(Argon2Params::new(MAX_ARGON2_MEMORY_KIB + 1, 1).unwrap_err().contains("memory"));
+        assert!(Argon2Params::new(64, MAX_ARGON2_ITERATIONS + 1).unwrap_err().contains("pass count"));
+    }
+
+    #[test]
+    fn every_slot_matching_the_input_is_tried() {
+        let first = Kdf::Argon2id(Argon2Params::new(64, 1).unwrap());
+        let second = Kdf::Argon2id(Argon2Params::new(64, 1).unwrap());
+        let slots = vec![
+            KeySlot::wrap(SlotKind::Passphrase, first.clone(), &first.derive_key(b"first passphrase").unwrap(), &DATA_KEY).unwrap(),
+            KeySlot::wrap(SlotKind::Passphrase, second.clone(), &second.derive_key(b"second passphrase").unwrap(), &DATA_KEY).unwrap(),
+            KeySlot::wrap(SlotKind::KeyFile, Kdf::KeyFile, &Kdf::KeyFile.derive_key(b"key file").unwrap(), &DATA_KEY).unwrap(),
+            key_slot(&KEY),
+        ];
+        let data = seal(b"secrets", slots.clone(), None);
+        for input in [
+            UnlockInput::Passphrase("first passphrase".to_string()),
+            UnlockInput::Passphrase("second passphrase".to_string()),
+            UnlockInput::KeyFile(b"key file".to_vec()),
+            UnlockInput::Key(KEY.to_vec()),
+        ] {
+            assert_eq!(decrypt_string(&input, &data).unwrap().data_key.expose(), DATA_KEY);
+        }
+        let wrong = UnlockInput::Passphrase("third passphrase".to_string());
+        assert!(unlock_data_key(&slots, &wrong).unwrap_err().contains("No key slot"));
+        let key_file = UnlockInput::KeyFile(b"key file".to_vec());
+        assert!(unlock_data_key(&slots[..2], &key_file).unwrap_err().contains("no key file slot"));
+        assert!(Kdf::KeyFile.derive_key(b"").is_err());
+    }
+
+    #[test]
+    fn a_failing_derivation_does_not_hide_later_slots() {
+        let broken = Kdf::Argon2id(Argon2Params { memory_kib: 1, ..Argon2Params::new(64, 1).unwrap() });
+        let working = Kdf::Argon2id(Argon2Params::new(64, 1).unwrap());
+        let passphrase = UnlockInput::Passphrase("passphrase".to_string());
+        let broken_slot = KeySlot::wrap(SlotKind::Passphrase, broken, &KEY, &DATA_KEY).unwrap();
+        let working_slot =
+            KeySlot::wrap(SlotKind::Passphrase, working.clone(), &working.derive_key(b"passphrase").unwrap(), &DATA_KEY).unwrap();
+
+        assert_eq!(unlock_data_key(&[broken_slot.clone(), working_slot], &passphrase).unwrap().0, DATA_KEY);
+        assert!(unlock_data_key(&[broken_slot], &passphrase).unwrap_err().contains("Invalid Argon2id parameters"));
+    }
+
+    #[test]
+    fn slots_are_rewritten_without_touching_the_payload() {
+        let other_key = [3u8; 32];
+        let data = seal(b"secrets", vec![key_slot(&KEY)], Some("Team vault"));
+        let (_, header_len) = ContainerHeader::parse(&data).unwrap();
+
+        let added = rewrite_slots(&data, &DATA_KEY, vec![key_slot(&KEY), key_slot(&other_key)]).unwrap();
+        let (header, added_len) = ContainerHeader::parse(&added).unwrap();
+        assert_eq!(header.slots().len(), 2);
+        assert_eq!(header.label.as_deref(), Some("Team vault"));
+        assert_eq!(added[added_len..], data[header_len..]);
+        assert_eq!(decrypt_string(&UnlockInput::Key(other_key.to_vec()), &added).unwrap().plaintext.expose(), b"secrets");
+
+        let revoked = rewrite_slots(&added, &DATA_KEY, vec![key_slot(&other_key)]).unwrap();
+        assert!(decrypt_string(&UnlockInput::Key(KEY.to_vec()), &revoked).is_err());
+        assert_eq!(decrypt_string(&UnlockInput::Key(other_key.to_vec()), &revoked).unwrap().plaintext.expose(), b"secrets");
+
+        assert!(rewrite_slots(&data, &other_key, vec![key_slot(&other_key)]).is_err());
+        assert!(rewrite_slots(&data, &DATA_KEY, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn xchacha_containers_round_trip_and_migrate() {
+        let slots = vec![key_slot(&KEY)];
+        assert_eq!(slots[0].cipher, Cipher::XChaCha20Poly1305);
+        let aes = seal(b"secrets", slots.clone(), Some("Team vault"));
+        let decrypted = decrypt_string(&UnlockInput::Key(KEY.to_vec()), &aes).unwrap();
+
+        // Migrating re-encrypts the payload, the slots still open the store
+        let xchacha = encrypt_string(
+            Cipher::XChaCha20Poly1305,
+            decrypted.data_key.expose(),
+            decrypted.plaintext.expose().to_vec(),
+            decrypted.header.unwrap().slots().to_vec(),
+            Some("Team vault".to_string()),
+        )
+        .unwrap();
+        let (header, _) = ContainerHeader::parse(&xchacha).unwrap();
+        assert_eq!(header.cipher, Cipher::XChaCha20Poly1305);
+        assert_eq!(header.nonce.len(), 24);
+        assert_eq!(header.slots(), slots.as_slice());
+        assert_eq!(decrypt_string(&UnlockInput::Key(KEY.to_vec()), &xchacha).unwrap().plaintext.expose(), b"secrets");
+
+        // The cipher id is authenticated along with the payload
+        let mut swapped = xchacha.clone();
+        swapped[5] = Cipher::Aes256Gcm.id();
+        assert!(decrypt_string(&UnlockInput::Key(KEY.to_vec()), &swapped).is_err());
+        for cipher in Cipher::iter() {
+            assert_eq!(Cipher::from_id(cipher.id()), Ok(cipher));
+            assert_eq!(cipher.generate_nonce().len(), cipher.nonce_len());
+        }
+    }
 }
@@ -0,0 +1,122 @@
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::path::Path;
+use age::x25519::{Identity, Recipient};
+use data_encoding::BASE64;
+
+/// Prefix of the single line envelopes, so that they can be told apart from file paths
+pub const ENVELOPE_PREFIX: &str = "bunkeys-envelope:";
+
+/// Ephemeral keypair of a remote unlock: holders seal their share to the request code,
+/// which is the age X25519 public key (`age1...`), and only this coordinator can open it.
+/// The secret key is never written anywhere and is gone once the store is loaded.
+#[derive(Clone)]
+pub struct UnlockRequest {
+    identity: Identity,
+}
+
+impl UnlockRequest {
+    pub fn new() -> Self {
+        UnlockRequest { identity: Identity::generate() }
+    }
+
+    /// Code to hand to the share holders
+    pub fn code(&self) -> String {
+        self.identity.to_public().to_string()
+    }
+
+    /// Opens an envelope given either as a single line or as the path of an envelope file, returning the share text
+    pub fn open(&self, envelope: &str) -> Result<String, String> {
+        let envelope = envelope.trim();
+        let sealed = match envelope.strip_prefix(ENVELOPE_PREFIX) {
+            Some(encoded) => BASE64.decode(encoded.as_bytes()).map_err(|_| "Invalid envelope encoding".to_string())?,
+            None => {
+                let file = fs::read(Path::new(envelope)).map_err(|e| format!("Failed to read envelope file: {}", e))?;
+                match std::str::from_utf8(&file).ok().and_then(|text| text.trim().strip_prefix(ENVELOPE_PREFIX)) {
+                    Some(encoded) => BASE64.decode(encoded.as_bytes()).map_err(|_| "Invalid envelope encoding".to_string())?,
+                    None => file,
+                }
+            }
+        };
+        let share = age::decrypt(&self.identity, &sealed)
+            .map_err(|_| "The envelope was not sealed to this unlock request".to_string())?;
+        String::from_utf8(share).map_err(|_| "The envelope does not hold a share".to_string())
+    }
+}
+
+impl Default for UnlockRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for UnlockRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.code() == other.code()
+    }
+}
+
+impl Debug for UnlockRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnlockRequest").field("code", &self.code()).finish_non_exhaustive()
+    }
+}
+
+/// Whether the input is an envelope rather than a share: a single line envelope or an existing file.
+/// Age share files are followed by the custodian identity, so they never come alone.
+pub fn is_envelope(input: &str) -> bool {
+    let input = input.trim();
+    input.starts_with(ENVELOPE_PREFIX) || !input.contains(char::is_whitespace) && Path::new(input).is_file()
+}
+
+/// Seals the text of a share to the coordinator's request code, as a single line envelope
+pub fn seal_envelope(request_code: &str, share_text: &str) -> Result<String, String> {
+    let recipient = request_code
+        .trim()
+        .parse::<Recipient>()
+        .map_err(|e| format!("Invalid request code: {}", e))?;
+    let sealed = age::encrypt(&recipient, share_text.as_bytes()).map_err(|e| format!("Failed to seal the share: {}", e))?;
+    Ok(format!("{}{}", ENVELOPE_PREFIX, BASE64.encode(&sealed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARE: &str = "0201020202002000000000abcdef";
+
+    #[test]
+    fn seals_and_opens_envelopes() {
+        let request = UnlockRequest::new();
+        let envelope = seal_envelope(&request.code(), SHARE).unwrap();
+        assert!(is_envelope(&envelope));
+        assert!(!envelope.contains(SHARE));
+        assert_eq!(request.open(&format!("  {}\n", envelope)), Ok(SHARE.to_string()));
+    }
+
+    #[test]
+    fn opens_envelope_files() {
+        let request = UnlockRequest::new();
+        let path = std::env::temp_dir().join(format!("bunkeys-envelope-{}.txt", std::process::id()));
+        fs::write(&path, format!("{}\n", seal_envelope(&request.code(), SHARE).unwrap())).unwrap();
+        let input = path.to_string_lossy().to_string();
+        assert!(is_envelope(&input));
+        assert_eq!(request.open(&input), Ok(SHARE.to_string()));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_envelopes_of_another_request() {
+        let envelope = seal_envelope(&UnlockRequest::new().code(), SHARE).unwrap();
+        assert_eq!(
+            UnlockRequest::new().open(&envelope),
+            Err("The envelope was not sealed to this unlock request".to_string())
+        );
+        assert_eq!(
+            UnlockRequest::new().open(&format!("{}not base64!", ENVELOPE_PREFIX)),
+            Err("Invalid envelope encoding".to_string())
+        );
+        assert!(seal_envelope("age1notacode", SHARE).unwrap_err().starts_with("Invalid request code"));
+        assert!(!is_envelope(SHARE));
+    }
+}
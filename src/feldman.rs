@@ -0,0 +1,332 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use crate::field::{Field, Fp521, MODULUS_521};
+use crate::polynom::Polynom;
+use crate::shamir_secret_sharing::{
+    add_to_prime_field_shares, check_consistent_shares, check_split_parameters, prime_field_polynom, prime_field_shares,
+    ShamirBackend, Share,
+};
+use crate::share_encoding::{key_fingerprint, EncodedShare};
+
+lazy_static! {
+    // Cofactor k of the Schnorr group: the first even k from 2²⁵⁵¹ such that k·q + 1 is prime
+    static ref GROUP_COFACTOR: BigUint = (BigUint::one() << 2551usize) + BigUint::from(2924u32);
+    // 3072-bit prime P = k·q + 1 where q = 2⁵²¹ - 1 is the modulus of the Shamir prime field
+    static ref GROUP_MODULUS: BigUint = {
+        let modulus = &*GROUP_COFACTOR * &*MODULUS_521 + BigUint::one();
+        assert_eq!(modulus.bits(), 3072);
+        modulus
+    };
+    // g = 2ᵏ mod P generates the subgroup of order q, so exponents live in GF(2⁵²¹ - 1)
+    static ref GROUP_GENERATOR: BigUint = {
+        let generator = BigUint::from(2u32).modpow(&GROUP_COFACTOR, &GROUP_MODULUS);
+        assert!(!generator.is_one());
+        generator
+    };
+}
+
+/// Extension of the commitments file published next to the store
+pub const COMMITMENTS_EXTENSION: &str = "vss";
+
+/// Feldman commitments Cⱼ = g^aⱼ mod P to the coefficients aⱼ of a prime field Shamir polynom.
+/// They let each shareholder check their share offline, without learning anything usable about the key:
+/// C₀ = g^key only gives the key away to a discrete logarithm of ~2¹²⁸ operations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeldmanCommitments {
+    /// Hex fingerprint of the key, as carried by the shares
+    pub fingerprint: String,
+    pub threshold: u8,
+    /// Hex commitments, constant term first
+    pub commitments: Vec<String>,
+}
+
+/// Splits a secret over the prime field backend and commits to the polynom used.
+pub fn split_secret_verifiable(
+    secret: &[u8],
+    threshold: usize,
+    share_count: usize,
+) -> Result<(Vec<Share>, FeldmanCommitments), String> {
+    split_secret_verifiable_with_rng(secret, threshold, share_count, &mut OsRng)
+}
+
+/// `split_secret_verifiable` drawing the polynom coefficients from `rng`, which must be a CSPRNG outside of tests
+pub fn split_secret_verifiable_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: usize,
+    share_count: usize,
+    rng: &mut R,
+) -> Result<(Vec<Share>, FeldmanCommitments), String> {
+    let share_count = check_split_parameters(threshold, share_count)?;
+    let polynom = prime_field_polynom(secret, threshold, rng)?;
+    let commitments = FeldmanCommitments::commit(&polynom, &key_fingerprint(secret));
+    Ok((prime_field_shares(&polynom, share_count), commitments))
+}
+
+/// Refreshes prime field shares like `refresh_shares`, and updates the commitments to match:
+/// the zero-constant polynom δ added to the shares turns Cⱼ into Cⱼ·g^δⱼ, C₀ is unchanged.
+pub fn refresh_shares_verifiable(
+    shares: &[Share],
+    commitments: &FeldmanCommitments,
+) -> Result<(Vec<Share>, FeldmanCommitments), String> {
+    refresh_shares_verifiable_with_rng(shares, commitments, &mut OsRng)
+}
+
+/// `refresh_shares_verifiable` drawing the zero-constant polynom from `rng`, which must be a CSPRNG outside of tests
+pub fn refresh_shares_verifiable_with_rng<R: RngCore + ?Sized>(
+    shares: &[Share],
+    commitments: &FeldmanCommitments,
+    rng: &mut R,
+) -> Result<(Vec<Share>, FeldmanCommitments), String> {
+    let threshold = commitments.threshold as usize;
+    let first = check_consistent_shares(shares, threshold, 0)?;
+    if first.backend != ShamirBackend::PrimeField {
+        return Err("Feldman commitments only cover prime field shares".to_string());
+    }
+    let delta = Polynom::new_random_of_degree_with_constant_term(threshold - 1, Fp521::zero(), rng);
+    let refreshed = FeldmanCommitments {
+        fingerprint: commitments.fingerprint.clone(),
+        threshold: commitments.threshold,
+        commitments: commitments
+            .parse()?
+            .iter()
+            .zip(&delta.coefficients)
+            .map(|(commitment, coef)| {
+                let shift = GROUP_GENERATOR.modpow(&coef.to_biguint(), &GROUP_MODULUS);
+                hex::encode((commitment * shift % &*GROUP_MODULUS).to_bytes_be())
+            })
+            .collect(),
+    };
+    Ok((add_to_prime_field_shares(shares, &delta), refreshed))
+}
+
+impl FeldmanCommitments {
+    pub fn commit(polynom: &Polynom<Fp521>, fingerprint: &[u8]) -> Self {
+        FeldmanCommitments {
+            fingerprint: hex::encode(fingerprint),
+            threshold: polynom.coefficients.len() as u8,
+            commitments: polynom
+                .coefficients
+                .iter()
+                .map(|coef| hex::encode(GROUP_GENERATOR.modpow(&coef.to_biguint(), &GROUP_MODULUS).to_bytes_be()))
+                .collect(),
+        }
+    }
+
+    /// Commitments file matching a store file: `store.enc` -> `store.vss`
+    pub fn path_for_store(store_path: &Path) -> PathBuf {
+        store_path.with_extension(COMMITMENTS_EXTENSION)
+    }
+
+    /// Commitments at `path`, None when none were published there.
+    /// A file that cannot be read is an error, so that verification is never silently skipped.
+    pub fn load_if_exists(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::load(path).map(Some)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let yaml = serde_yaml::to_string(self).map_err(|e| format!("Failed to serialize commitments: {}", e))?;
+        fs::write(path, yaml).map_err(|e| format!("Failed to write commitments file: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let yaml = fs::read(path).map_err(|e| format!("Failed to read commitments file: {}", e))?;
+        serde_yaml::from_slice(&yaml).map_err(|e| format!("Failed to parse commitments file: {}", e))
+    }
+
+    /// Whether these commitments were published for the split `share` belongs to
+    pub fn covers(&self, share: &EncodedShare) -> bool {
+        self.fingerprint == hex::encode(share.fingerprint) && share.share.backend == ShamirBackend::PrimeField
+    }
+
+    /// Parses the commitments, rejecting anything outside the subgroup of order q
+    fn parse(&self) -> Result<Vec<BigUint>, String> {
+        if self.threshold == 0 || self.commitments.len() != self.threshold as usize {
+            return Err("Commitments count does not match the threshold".to_string());
+        }
+        self.commitments
+            .iter()
+            .map(|commitment| {
+                let value = hex::decode(commitment)
+                    .map(|bytes| BigUint::from_bytes_be(&bytes))
+                    .map_err(|_| "Invalid commitment encoding".to_string())?;
+                if value >= *GROUP_MODULUS || !value.modpow(&MODULUS_521, &GROUP_MODULUS).is_one() {
+                    return Err("Commitment is not an element of the group".to_string());
+                }
+                Ok(value)
+            })
+            .collect()
+    }
+
+    /// Checks a recovered key against the published fingerprint and C₀ = g^key
+    pub fn check_key(&self, secret: &[u8]) -> Result<(), String> {
+        if self.fingerprint != hex::encode(key_fingerprint(secret)) {
+            return Err("The key does not match the fingerprint of the commitments".to_string());
+        }
+        let key = BigUint::from_bytes_be(secret);
+        if key >= *MODULUS_521 || GROUP_GENERATOR.modpow(&key, &GROUP_MODULUS) != self.parse()?[0] {
+            return Err("The key does not match the constant term commitment".to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks g^y = ∏ Cⱼ^(xʲ) mod P, i.e. that the share lies on the committed polynom
+    pub fn verify(&self, share: &EncodedShare) -> Result<(), String> {
+        if share.share.backend != ShamirBackend::PrimeField {
+            return Err("Feldman commitments only cover prime field shares".to_string());
+        }
+        if self.fingerprint != hex::encode(share.fingerprint) {
+            return Err(format!(
+                "Share {} belongs to another key (fingerprint {} instead of {})",
+                share.share.x,
+                hex::encode(share.fingerprint),
+                self.fingerprint
+            ));
+        }
+        if self.threshold != share.threshold {
+            return Err(format!("Share {} threshold does not match the commitments", share.share.x));
+        }
+        let commitments = self.parse()?;
+        let y = Fp521::from_bytes(&share.share.y)
+            .ok_or_else(|| format!("Share {} value is out of the field range", share.share.x))?;
+
+        let x = Fp521::from_index(share.share.x);
+        let mut x_pow = Fp521::one();
+        let mut expected = BigUint::one();
+        for commitment in &commitments {
+            expected = expected * commitment.modpow(&x_pow.to_biguint(), &GROUP_MODULUS) % &*GROUP_MODULUS;
+            x_pow = x_pow.mul(&x);
+        }
+        if GROUP_GENERATOR.modpow(&y.to_biguint(), &GROUP_MODULUS) != expected {
+            return Err(format!(
+                "Share {} fails verification against the Feldman commitments, it is corrupted or forged",
+                share.share.x
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const SECRET: [u8; 32] = [0x42; 32];
+
+    fn verifiable_split() -> (Vec<EncodedShare>, FeldmanCommitments) {
+        let (shares, commitments) = split_secret_verifiable(&SECRET, 2, 3).unwrap();
+        (EncodedShare::wrap_all(shares, 2, &SECRET), commitments)
+    }
+
+    #[test]
+    fn group_constants_are_consistent() {
+        assert_eq!(*GROUP_MODULUS, &*GROUP_COFACTOR * &*MODULUS_521 + BigUint::one());
+        assert_eq!(GROUP_MODULUS.bits(), 3072);
+        let order = &*GROUP_MODULUS - BigUint::one();
+        for base in [2u32, 3, 5] {
+            assert!(BigUint::from(base).modpow(&order, &GROUP_MODULUS).is_one(), "P fails the Fermat test to base {}", base);
+        }
+        // q is prime, so g ≠ 1 with g^q = 1 has order exactly q
+        assert!(!GROUP_GENERATOR.is_one());
+        assert!(GROUP_GENERATOR.modpow(&MODULUS_521, &GROUP_MODULUS).is_one());
+    }
+
+    #[test]
+    fn valid_shares_and_key_verify() {
+        let (shares, commitments) = verifiable_split();
+        assert_eq!(commitments.threshold, 2);
+        assert_eq!(commitments.commitments.len(), 2);
+        for share in &shares {
+            assert!(commitments.covers(share));
+            assert_eq!(commitments.verify(share), Ok(()));
+        }
+        assert_eq!(commitments.check_key(&SECRET), Ok(()));
+        assert!(commitments.check_key(&[0x43; 32]).is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_shares() {
+        let (shares, commitments) = verifiable_split();
+        let mut tampered = shares[0].clone();
+        let y = Fp521::from_bytes(&tampered.share.y).unwrap();
+        tampered.share.y = y.add(&Fp521::one()).to_bytes();
+        assert!(commitments.verify(&tampered).unwrap_err().contains("fails verification"));
+
+        let mut moved = shares[0].clone();
+        moved.share.x = 4;
+        assert!(commitments.verify(&moved).is_err());
+
+        let mut other_key = shares[0].clone();
+        other_key.fingerprint = key_fingerprint(b"another key");
+        assert!(!commitments.covers(&other_key));
+        assert!(commitments.verify(&other_key).unwrap_err().contains("belongs to another key"));
+    }
+
+    #[test]
+    fn rejects_tampered_commitments() {
+        let (shares, commitments) = verifiable_split();
+        let (_, other) = verifiable_split();
+        let mut swapped = commitments.clone();
+        swapped.commitments[1] = other.commitments[1].clone();
+        assert!(swapped.verify(&shares[0]).unwrap_err().contains("fails verification"));
+
+        // 2 is in the group of order P - 1 but not in the subgroup of order q
+        let mut outside = commitments.clone();
+        outside.commitments[1] = "02".to_string();
+        assert_eq!(outside.verify(&shares[0]), Err("Commitment is not an element of the group".to_string()));
+
+        let mut truncated = commitments.clone();
+        truncated.commitments.pop();
+        assert_eq!(truncated.verify(&shares[0]), Err("Commitments count does not match the threshold".to_string()));
+    }
+
+    #[test]
+    fn refreshed_shares_verify_against_refreshed_commitments() {
+        let (shares, commitments) = verifiable_split();
+        let raw_shares: Vec<Share> = shares.iter().map(|s| s.share.clone()).collect();
+        let (refreshed, refreshed_commitments) = refresh_shares_verifiable(&raw_shares, &commitments).unwrap();
+        assert_eq!(refreshed_commitments.commitments[0], commitments.commitments[0]);
+        for (share, old) in refreshed.into_iter().zip(&shares) {
+            let share = EncodedShare { share, ..old.clone() };
+            assert_eq!(refreshed_commitments.verify(&share), Ok(()));
+            assert!(commitments.verify(&share).is_err());
+        }
+    }
+
+    #[test]
+    fn unreadable_commitments_are_an_error() {
+        let path = std::env::temp_dir().join(format!("bunkeys-feldman-{}.vss", std::process::id()));
+        assert_eq!(FeldmanCommitments::load_if_exists(&path), Ok(None));
+
+        let (_, commitments) = verifiable_split();
+        commitments.save(&path).unwrap();
+        assert_eq!(FeldmanCommitments::load_if_exists(&path), Ok(Some(commitments)));
+
+        fs::write(&path, "fingerprint: [").unwrap();
+        let loaded = FeldmanCommitments::load_if_exists(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(loaded.unwrap_err().contains("Failed to parse commitments file"));
+    }
+
+    #[test]
+    fn seeded_verifiable_split_and_refresh_are_deterministic() {
+        let split = |seed| split_secret_verifiable_with_rng(&SECRET, 2, 3, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+        assert_eq!(split(1), split(1));
+        assert_ne!(split(1), split(2));
+
+        let (shares, commitments) = split(1);
+        let refresh = |seed| refresh_shares_verifiable_with_rng(&shares, &commitments, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+        assert_eq!(refresh(1), refresh(1));
+        assert_ne!(refresh(1), refresh(2));
+    }
+}
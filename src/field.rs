@@ -0,0 +1,467 @@
+use std::fmt::{Debug, Display, Formatter};
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::RngCore;
+use subtle::ConstantTimeEq;
+
+lazy_static! {
+    // 521-bit prime modulus (2⁵²¹ - 1), large enough to hold a whole 256-bit key
+    pub static ref MODULUS_521: BigUint = {
+        let modulus = (BigUint::one() << 521usize) - BigUint::one();
+        assert_eq!(modulus.bits(), 521);
+        modulus
+    };
+}
+
+/// A finite field in which Shamir polynoms are built and evaluated
+pub trait Field: Sized + Clone + PartialEq + Debug {
+    /// Number of bytes needed to write any element of the field
+    const BYTES: usize;
+
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// Maps a share index to a field element
+    fn from_index(index: u8) -> Self;
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self;
+
+    fn add(&self, rhs: &Self) -> Self;
+    fn sub(&self, rhs: &Self) -> Self;
+    fn mul(&self, rhs: &Self) -> Self;
+    /// Multiplicative inverse, None for zero
+    fn inv(&self) -> Option<Self>;
+
+    /// Big-endian bytes, always exactly `BYTES` long
+    fn to_bytes(&self) -> Vec<u8>;
+    /// Parses `BYTES` big-endian bytes, None if out of the field range
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+/// Number of 58-bit limbs of an `Fp521`, the top one only holds 57 bits: 8 × 58 + 57 = 521
+const LIMBS: usize = 9;
+const LIMB_BITS: u32 = 58;
+const LIMB_MASK: u64 = (1 << LIMB_BITS) - 1;
+const TOP_LIMB_BITS: u32 = 57;
+const TOP_LIMB_MASK: u64 = (1 << TOP_LIMB_BITS) - 1;
+/// 2·(2⁵²¹ - 1) spread so that each limb exceeds any reduced limb, for borrow-free subtraction
+const TWICE_MODULUS_LIMBS: [u64; LIMBS] = [
+    2 * LIMB_MASK, 2 * LIMB_MASK, 2 * LIMB_MASK, 2 * LIMB_MASK,
+    2 * LIMB_MASK, 2 * LIMB_MASK, 2 * LIMB_MASK, 2 * LIMB_MASK,
+    2 * TOP_LIMB_MASK,
+];
+
+/// Element of the prime field GF(2⁵²¹ - 1), as fully reduced little-endian 58-bit limbs.
+/// Every operation runs in constant time: fixed-width limbs, Mersenne reduction (2⁵²¹ ≡ 1)
+/// and inversion by exponentiation, with no branch nor memory access depending on the values.
+#[derive(Debug, Clone, Copy)]
+pub struct Fp521([u64; LIMBS]);
+
+impl Fp521 {
+    pub fn from_biguint(value: BigUint) -> Option<Self> {
+        if value >= *MODULUS_521 {
+            return None;
+        }
+        let bytes = value.to_bytes_be();
+        let mut padded = vec![0u8; Self::BYTES - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        Self::from_bytes(&padded)
+    }
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.to_bytes())
+    }
+
+    /// Carries limbs of up to 125 bits into a fully reduced element
+    fn reduce(wide: [u128; LIMBS]) -> Self {
+        let mut limbs = [0u64; LIMBS];
+        let mut carry = 0u128;
+        for i in 0..LIMBS - 1 {
+            let value = wide[i] + carry;
+            limbs[i] = value as u64 & LIMB_MASK;
+            carry = value >> LIMB_BITS;
+        }
+        let value = wide[LIMBS - 1] + carry;
+        limbs[LIMBS - 1] = value as u64 & TOP_LIMB_MASK;
+        let mut overflow = value >> TOP_LIMB_BITS;
+
+        // Bits above 2⁵²¹ weigh 1 modulo p and are added back to the lowest limb.
+        // The first fold leaves at most one bit above 2⁵²¹ with small limbs below, the second one none.
+        for _ in 0..2 {
+            let mut carry = overflow;
+            for limb in limbs.iter_mut().take(LIMBS - 1) {
+                let value = *limb as u128 + carry;
+                *limb = value as u64 & LIMB_MASK;
+                carry = value >> LIMB_BITS;
+            }
+            let value = limbs[LIMBS - 1] as u128 + carry;
+            limbs[LIMBS - 1] = value as u64 & TOP_LIMB_MASK;
+            overflow = value >> TOP_LIMB_BITS;
+        }
+        Self::zero_if_modulus(limbs)
+    }
+
+    /// Same as `self.mul(self)` with 45 limb products instead of 81, the cross products being computed once
+    fn square(&self) -> Self {
+        let mut wide = [0u128; LIMBS];
+        for i in 0..LIMBS {
+            for j in i..LIMBS {
+                let mut product = self.0[i] as u128 * self.0[j] as u128;
+                if i != j {
+                    product <<= 1;
+                }
+                if i + j < LIMBS {
+                    wide[i + j] += product;
+                } else {
+                    wide[i + j - LIMBS] += product << 1;
+                }
+            }
+        }
+        Self::reduce(wide)
+    }
+
+    /// self^(2ⁿ) · rhs
+    fn square_n_mul(&self, n: usize, rhs: &Self) -> Self {
+        let mut result = *self;
+        for _ in 0..n {
+            result = result.square();
+        }
+        result.mul(rhs)
+    }
+
+    /// Maps p itself, the only unreduced value below 2⁵²¹, to 0
+    fn zero_if_modulus(limbs: [u64; LIMBS]) -> Self {
+        // p + 1 = 2⁵²¹ is the only value whose increment carries out of the top limb
+        let mut carry = 1u64;
+        for limb in &limbs[..LIMBS - 1] {
+            carry = (limb + carry) >> LIMB_BITS;
+        }
+        let is_modulus = (limbs[LIMBS - 1] + carry) >> TOP_LIMB_BITS;
+        let keep = is_modulus.wrapping_sub(1);
+        Fp521(limbs.map(|limb| limb & keep))
+    }
+}
+
+impl PartialEq for Fp521 {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+impl Eq for Fp521 {}
+
+impl Field for Fp521 {
+    const BYTES: usize = 66;
+
+    fn zero() -> Self {
+        Fp521([0; LIMBS])
+    }
+    fn one() -> Self {
+        Self::from_index(1)
+    }
+    fn from_index(index: u8) -> Self {
+        let mut limbs = [0; LIMBS];
+        limbs[0] = index as u64;
+        Fp521(limbs)
+    }
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+        // Rejection sampling of 521-bit values, only p itself is rejected
+        loop {
+            let mut bytes = [0u8; Self::BYTES];
+            rng.fill_bytes(&mut bytes);
+            bytes[0] &= 0x01;
+            if let Some(element) = Self::from_bytes(&bytes) {
+                return element;
+            }
+        }
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        Self::reduce(std::array::from_fn(|i| self.0[i] as u128 + rhs.0[i] as u128))
+    }
+    fn sub(&self, rhs: &Self) -> Self {
+        Self::reduce(std::array::from_fn(|i| {
+            (self.0[i] + TWICE_MODULUS_LIMBS[i] - rhs.0[i]) as u128
+        }))
+    }
+    /// Schoolbook product, the limbs past the 9th weigh 2⁵²² ≡ 2 and are folded back doubled
+    fn mul(&self, rhs: &Self) -> Self {
+        let mut wide = [0u128; LIMBS];
+        for i in 0..LIMBS {
+            for j in 0..LIMBS {
+                let product = self.0[i] as u128 * rhs.0[j] as u128;
+                if i + j < LIMBS {
+                    wide[i + j] += product;
+                } else {
+                    wide[i + j - LIMBS] += product << 1;
+                }
+            }
+        }
+        Self::reduce(wide)
+    }
+    /// a⁻¹ = a^(p - 2) where p - 2 = 2⁵²¹ - 3 = (2⁵¹⁹ - 1)·2² + 1,
+    /// through a fixed addition chain of 524 squarings and 13 multiplications
+    fn inv(&self) -> Option<Self> {
+        // Each tₖ is a^(2ᵏ - 1)
+        let t1 = *self;
+        let t2 = t1.square_n_mul(1, &t1);
+        let t3 = t2.square_n_mul(1, &t1);
+        let t4 = t2.square_n_mul(2, &t2);
+        let t7 = t4.square_n_mul(3, &t3);
+        let t8 = t4.square_n_mul(4, &t4);
+        let t16 = t8.square_n_mul(8, &t8);
+        let t32 = t16.square_n_mul(16, &t16);
+        let t64 = t32.square_n_mul(32, &t32);
+        let t128 = t64.square_n_mul(64, &t64);
+        let t256 = t128.square_n_mul(128, &t128);
+        let t512 = t256.square_n_mul(256, &t256);
+        let t519 = t512.square_n_mul(7, &t7);
+        let result = t519.square_n_mul(2, self);
+        if self.is_zero() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; Self::BYTES];
+        let (mut acc, mut acc_bits, mut next_limb) = (0u128, 0u32, 0usize);
+        for byte in bytes.iter_mut().rev() {
+            if acc_bits < 8 && next_limb < LIMBS {
+                acc |= (self.0[next_limb] as u128) << acc_bits;
+                acc_bits += LIMB_BITS;
+                next_limb += 1;
+            }
+            *byte = acc as u8;
+            acc >>= 8;
+            acc_bits = acc_bits.saturating_sub(8);
+        }
+        bytes
+    }
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        // 66 bytes hold 528 bits, the 7 above 2⁵²¹ must be clear
+        if bytes.len() != Self::BYTES || bytes[0] > 0x01 {
+            return None;
+        }
+        let mut limbs = [0u64; LIMBS];
+        let (mut acc, mut acc_bits, mut limb) = (0u128, 0u32, 0usize);
+        for byte in bytes.iter().rev() {
+            acc |= (*byte as u128) << acc_bits;
+            acc_bits += 8;
+            if acc_bits >= LIMB_BITS && limb < LIMBS - 1 {
+                limbs[limb] = acc as u64 & LIMB_MASK;
+                acc >>= LIMB_BITS;
+                acc_bits -= LIMB_BITS;
+                limb += 1;
+            }
+        }
+        limbs[LIMBS - 1] = acc as u64;
+        let element = Self::zero_if_modulus(limbs);
+        // p itself is out of the field range
+        if element.0 != limbs {
+            return None;
+        }
+        Some(element)
+    }
+}
+
+impl Display for Fp521 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_biguint())
+    }
+}
+
+/// Element of GF(2⁸) with the AES reduction polynom x⁸ + x⁴ + x³ + x + 1,
+/// as used by ssss, HashiCorp Vault and SLIP-0039.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gf256(pub u8);
+
+impl Field for Gf256 {
+    const BYTES: usize = 1;
+
+    fn zero() -> Self {
+        Gf256(0)
+    }
+    fn one() -> Self {
+        Gf256(1)
+    }
+    fn from_index(index: u8) -> Self {
+        Gf256(index)
+    }
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mut byte = [0u8; 1];
+        rng.fill_bytes(&mut byte);
+        Gf256(byte[0])
+    }
+
+    fn add(&self, rhs: &Self) -> Self {
+        Gf256(self.0 ^ rhs.0)
+    }
+    fn sub(&self, rhs: &Self) -> Self {
+        Gf256(self.0 ^ rhs.0)
+    }
+    /// Branch-free shift-and-add multiplication, so no secret-dependent table lookups
+    fn mul(&self, rhs: &Self) -> Self {
+        let mut a = self.0;
+        let mut b = rhs.0;
+        let mut product = 0u8;
+        for _ in 0..8 {
+            product ^= a & 0u8.wrapping_sub(b & 1);
+            let carry = 0u8.wrapping_sub(a >> 7);
+            a = (a << 1) ^ (0x1b & carry);
+            b >>= 1;
+        }
+        Gf256(product)
+    }
+    /// a⁻¹ = a²⁵⁴ since the multiplicative group has order 255
+    fn inv(&self) -> Option<Self> {
+        if self.0 == 0 {
+            return None;
+        }
+        let mut result = Gf256::one();
+        let mut base = *self;
+        let mut exponent = 254u8;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            base = base.mul(&base);
+            exponent >>= 1;
+        }
+        Some(result)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.0]
+    }
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [byte] => Some(Gf256(*byte)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Gf256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#04x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn big(value: &Fp521) -> BigUint {
+        value.to_biguint()
+    }
+
+    /// Edge values: 0, 1, p - 1, p - 2, 2⁵²⁰, and each side of every limb boundary
+    fn edge_values() -> Vec<BigUint> {
+        let p = &*MODULUS_521;
+        let mut values = vec![
+            BigUint::ZERO,
+            BigUint::one(),
+            BigUint::from(2u32),
+            p - 1u32,
+            p - 2u32,
+            BigUint::one() << 520usize,
+            (BigUint::one() << 520usize) - 1u32,
+            p >> 1usize,
+        ];
+        for limb in 1..LIMBS {
+            let boundary = BigUint::one() << (limb as u32 * LIMB_BITS);
+            values.push(&boundary - 1u32);
+            values.push(boundary.clone());
+            values.push(&boundary + 1u32);
+        }
+        values
+    }
+
+    fn test_values() -> Vec<BigUint> {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let mut values = edge_values();
+        values.extend((0..8).map(|_| big(&Fp521::random(&mut rng))));
+        values
+    }
+
+    fn element(value: &BigUint) -> Fp521 {
+        Fp521::from_biguint(value.clone()).unwrap()
+    }
+
+    #[test]
+    fn bytes_match_biguint() {
+        for value in test_values() {
+            let expected_limbs: [u64; LIMBS] =
+                std::array::from_fn(|i| ((&value >> (i as u32 * LIMB_BITS)) & BigUint::from(LIMB_MASK)).try_into().unwrap());
+            let mut bytes = value.to_bytes_be();
+            bytes.splice(0..0, vec![0u8; Fp521::BYTES - bytes.len()]);
+
+            let from_bytes = Fp521::from_bytes(&bytes).unwrap();
+            assert_eq!(from_bytes.0, expected_limbs, "{}", value);
+            assert_eq!(from_bytes.to_bytes(), bytes, "{}", value);
+            assert_eq!(element(&value), from_bytes);
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_bytes() {
+        let p = &*MODULUS_521;
+        assert_eq!(Fp521::from_biguint(p.clone()), None);
+        assert_eq!(Fp521::from_biguint(p + 1u32), None);
+        let modulus_bytes = p.to_bytes_be();
+        assert_eq!(modulus_bytes.len(), Fp521::BYTES);
+        assert_eq!(Fp521::from_bytes(&modulus_bytes), None);
+        let mut too_large = vec![0u8; Fp521::BYTES];
+        too_large[0] = 0x02;
+        assert_eq!(Fp521::from_bytes(&too_large), None);
+        assert_eq!(Fp521::from_bytes(&[0u8; 65]), None);
+        assert_eq!(Fp521::from_bytes(&[0u8; 67]), None);
+    }
+
+    #[test]
+    fn arithmetic_matches_biguint() {
+        let p = &*MODULUS_521;
+        let values = test_values();
+        for a in &values {
+            for b in &values {
+                let (x, y) = (element(a), element(b));
+                assert_eq!(big(&x.add(&y)), (a + b) % p, "{} + {}", a, b);
+                assert_eq!(big(&x.sub(&y)), (a + p - b) % p, "{} - {}", a, b);
+                assert_eq!(big(&x.mul(&y)), a * b % p, "{} * {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_matches_biguint() {
+        let p = &*MODULUS_521;
+        for value in test_values() {
+            let inverse = element(&value).inv();
+            if value == BigUint::ZERO {
+                assert_eq!(inverse, None);
+                continue;
+            }
+            let inverse = inverse.unwrap();
+            assert_eq!(big(&inverse), value.modinv(p).unwrap(), "{}", value);
+            assert_eq!(element(&value).mul(&inverse), Fp521::one());
+        }
+    }
+
+    #[test]
+    fn gf256_known_answers() {
+        // FIPS-197 examples: {57}·{83} = {c1}, {57}·{13} = {fe}, {53}⁻¹ = {ca}
+        assert_eq!(Gf256(0x57).mul(&Gf256(0x83)), Gf256(0xc1));
+        assert_eq!(Gf256(0x57).mul(&Gf256(0x13)), Gf256(0xfe));
+        assert_eq!(Gf256(0x53).inv(), Some(Gf256(0xca)));
+        assert_eq!(Gf256(0).inv(), None);
+        for a in 1..=255u8 {
+            assert_eq!(Gf256(a).mul(&Gf256(a).inv().unwrap()), Gf256::one());
+            assert_eq!(Gf256(a).add(&Gf256(a)), Gf256::zero());
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Secret sharing and encrypted store primitives behind the Bunkeys TUI
+
+pub mod age_shares;
+pub mod encrypt;
+pub mod envelope;
+pub mod feldman;
+pub mod field;
+pub mod policy;
+pub mod polynom;
+pub mod sealed_value;
+pub mod secret;
+pub mod shamir_secret_sharing;
+pub mod share_encoding;
+pub mod slip39;
+pub mod split_value;
@@ -1,15 +1,28 @@
 use color_eyre::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use ratatui::DefaultTerminal;
+use std::time::Duration;
 
 mod app;
-mod encrypt;
-mod polynom;
-mod shamir_secret_sharing;
 mod store;
+mod submit_share;
+
+/// Redraw period while no key is pressed, so that background work shows its progress
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+// The TUI reaches the library modules through `crate::`
+use bunkeys::{age_shares, encrypt, envelope, feldman, policy, sealed_value, secret, shamir_secret_sharing, share_encoding, slip39, split_value};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("submit-share") {
+        if let Err(e) = submit_share::run(&args[1..]) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     let terminal = ratatui::init();
     let result = run(terminal);
     ratatui::restore();
@@ -22,14 +35,18 @@ fn run(mut terminal: DefaultTerminal) -> Result<()> {
     loop {
         terminal.draw(|frame| app.render(frame))?;
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                // Global quit
-                if matches!(key.code, KeyCode::Char('q')) && matches!(app.state, app::state::AppState::MainMenu(_)) {
-                    break Ok(());
-                }
-                app.handle_key(key);
+        if !event::poll(TICK_RATE)? {
+            app.tick();
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            // Global quit
+            if matches!(key.code, KeyCode::Char('q')) && matches!(app.state, app::state::AppState::MainMenu(_)) {
+                break Ok(());
             }
+            app.handle_key(key);
         }
     }
 }
@@ -0,0 +1,584 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::shamir_secret_sharing::{recover_secret_robust, split_secret, ShamirBackend, Share};
+use crate::share_encoding::{key_fingerprint, EncodedShare, ShareFormat, MAX_LABEL_BYTES};
+
+/// Extension of the policy file published next to the store
+pub const POLICY_EXTENSION: &str = "policy";
+
+/// A recovery policy: nested groups of weighted members.
+/// Written as e.g. `1 of (founders: 2 of (alice, bob, carol), 2 of (1 of (alice, bob, carol), engineers: 2 of (dan, erin, frank, gina)))`
+/// or `3 of (alice*2, bob*2, carol, dan)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyNode {
+    /// A shareholder, holding `weight` shares of the enclosing group
+    Member { name: String, weight: u8 },
+    /// Members whose weights add up to `threshold` recover the group secret,
+    /// which is itself one share of the enclosing group
+    Group {
+        name: Option<String>,
+        threshold: u8,
+        members: Vec<PolicyNode>,
+    },
+}
+
+impl PolicyNode {
+    fn weight(&self) -> u8 {
+        match self {
+            PolicyNode::Member { weight, .. } => *weight,
+            PolicyNode::Group { .. } => 1,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            PolicyNode::Member { name, .. } => name,
+            PolicyNode::Group { name, .. } => name.as_deref().unwrap_or("group"),
+        }
+    }
+
+    /// Parses and validates a policy, whose root must be a group
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut position = 0;
+        let node = parse_node(&tokens, &mut position)?;
+        if position != tokens.len() {
+            return Err(format!("Unexpected '{}' after the end of the policy", tokens[position]));
+        }
+        if matches!(node, PolicyNode::Member { .. }) {
+            return Err("The policy must be a group, e.g. '2 of (alice, bob, carol)'".to_string());
+        }
+        node.validate()?;
+        Ok(node)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            PolicyNode::Member { name, weight } => {
+                if *weight == 0 {
+                    return Err(format!("Member '{}' must have a weight of at least 1", name));
+                }
+                if name.len() > MAX_LABEL_BYTES {
+                    return Err(format!("Member name '{}' is longer than {} bytes", name, MAX_LABEL_BYTES));
+                }
+                Ok(())
+            }
+            PolicyNode::Group { threshold, members, .. } => {
+                let total: usize = members.iter().map(|member| member.weight() as usize).sum();
+                if total > 255 {
+                    return Err(format!("Group '{}' members weigh more than 255 shares", self.name()));
+                }
+                if *threshold == 0 || *threshold as usize > total {
+                    return Err(format!(
+                        "Group '{}' threshold must be between 1 and its total weight ({})",
+                        self.name(),
+                        total
+                    ));
+                }
+                members.iter().try_for_each(PolicyNode::validate)
+            }
+        }
+    }
+
+    /// Node reached by following the member indices of `path` from this node
+    pub fn node_at(&self, path: &[u8]) -> Option<&PolicyNode> {
+        match (path.split_first(), self) {
+            (None, _) => Some(self),
+            (Some((index, rest)), PolicyNode::Group { members, .. }) => members.get(*index as usize)?.node_at(rest),
+            (Some(_), PolicyNode::Member { .. }) => None,
+        }
+    }
+
+    /// Share indices of the `index`-th member of this group: one per unit of weight, starting at 1.
+    /// Summed in usize, a group of total weight 255 ends past `u8::MAX`.
+    fn member_indices(&self, index: usize) -> std::ops::Range<usize> {
+        let PolicyNode::Group { members, .. } = self else {
+            return 0..0;
+        };
+        let start = 1 + members[..index].iter().map(|member| member.weight() as usize).sum::<usize>();
+        start..start + members[index].weight() as usize
+    }
+}
+
+impl Display for PolicyNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyNode::Member { name, weight: 1 } => write!(f, "{}", name),
+            PolicyNode::Member { name, weight } => write!(f, "{}*{}", name, weight),
+            PolicyNode::Group { name, threshold, members } => {
+                if let Some(name) = name {
+                    write!(f, "{}: ", name)?;
+                }
+                let members: Vec<String> = members.iter().map(|member| member.to_string()).collect();
+                write!(f, "{} of ({})", threshold, members.join(", "))
+            }
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | ',' | ':' | '*' => tokens.push(c.to_string()),
+            c if c.is_whitespace() => {}
+            c if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_' || next == '-' || next == '.') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(word);
+            }
+            c => return Err(format!("Unexpected character '{}' in the policy", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// node := [name ':'] threshold 'of' '(' node {',' node} ')' | name ['*' weight]
+fn parse_node(tokens: &[String], position: &mut usize) -> Result<PolicyNode, String> {
+    let token = |i: usize| tokens.get(i).map(String::as_str);
+    let word = token(*position).ok_or("Unexpected end of the policy")?.to_string();
+    if ["(", ")", ",", ":", "*"].contains(&word.as_str()) {
+        return Err(format!("Expected a member or a group, found '{}'", word));
+    }
+    *position += 1;
+
+    let name = if token(*position) == Some(":") {
+        *position += 1;
+        Some(word)
+    } else if token(*position) == Some("of") {
+        *position -= 1;
+        None
+    } else {
+        let weight = if token(*position) == Some("*") {
+            let weight = token(*position + 1).ok_or("Expected a weight after '*'")?;
+            *position += 2;
+            weight.parse::<u8>().map_err(|_| format!("Invalid weight '{}'", weight))?
+        } else {
+            1
+        };
+        return Ok(PolicyNode::Member { name: word, weight });
+    };
+
+    let threshold = token(*position).ok_or("Expected a group threshold")?;
+    let threshold = threshold.parse::<u8>().map_err(|_| format!("Invalid group threshold '{}'", threshold))?;
+    if token(*position + 1) != Some("of") || token(*position + 2) != Some("(") {
+        return Err("Expected '<threshold> of (...)'".to_string());
+    }
+    *position += 3;
+    let mut members = vec![parse_node(tokens, position)?];
+    loop {
+        match token(*position) {
+            Some(",") => {
+                *position += 1;
+                members.push(parse_node(tokens, position)?);
+            }
+            Some(")") => {
+                *position += 1;
+                break;
+            }
+            _ => return Err("Expected ',' or ')' in a group".to_string()),
+        }
+    }
+    Ok(PolicyNode::Group { name, threshold, members })
+}
+
+/// A share of a member of the policy, `path` locating the member from the root group
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyShare {
+    pub path: Vec<u8>,
+    pub share: EncodedShare,
+}
+
+impl PolicyShare {
+    /// Written as the dotted path followed by the share, e.g. `1.0 <share>`
+    pub fn encode(&self, format: ShareFormat) -> String {
+        format!("{} {}", path_to_string(&self.path), self.share.encode(format))
+    }
+
+    /// Share name followed by the member path, e.g. `1 (alice) at 1.0`
+    pub fn name(&self) -> String {
+        format!("{} at {}", self.share.name(), path_to_string(&self.path))
+    }
+
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let (path, share) = text
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or("Expected the member path followed by the share, e.g. '1.0 <share>'")?;
+        let path = path
+            .split('.')
+            .map(|index| index.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| format!("Invalid member path '{}'", path))?;
+        Ok(PolicyShare { path, share: EncodedShare::decode(share)? })
+    }
+
+    /// Checks that the share belongs to a member of `policy` and fits with the shares already collected
+    pub fn check(&self, policy: &PolicyNode, collected: &[PolicyShare]) -> Result<(), String> {
+        let Some((index, group_path)) = self.path.split_last() else {
+            return Err("Empty member path".to_string());
+        };
+        let group = policy.node_at(group_path).ok_or("The share path is not part of the policy")?;
+        match group.node_at(&[*index]) {
+            Some(PolicyNode::Member { .. }) => {}
+            _ => return Err("The share path does not lead to a member of the policy".to_string()),
+        }
+        if !group.member_indices(*index as usize).contains(&(self.share.share.x as usize)) {
+            return Err("The share does not belong to this member".to_string());
+        }
+        // Shares of the same group come from the same split
+        for other in collected.iter().filter(|other| other.path.split_last().map(|(_, p)| p) == Some(group_path)) {
+            other.share.check_same_split(&self.share)?;
+        }
+        Ok(())
+    }
+}
+
+fn path_to_string(path: &[u8]) -> String {
+    path.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(".")
+}
+
+/// Splits `secret` along the policy, over GF(2⁸) so that group secrets can be split again.
+/// Every member gets one share per unit of weight, labelled with its name.
+pub fn split_with_policy(policy: &PolicyNode, secret: &[u8]) -> Result<Vec<PolicyShare>, String> {
+    let mut shares = Vec::new();
+    split_node(policy, &[], secret, &mut shares)?;
+    Ok(shares)
+}
+
+fn split_node(node: &PolicyNode, path: &[u8], secret: &[u8], out: &mut Vec<PolicyShare>) -> Result<(), String> {
+    let PolicyNode::Group { threshold, members, .. } = node else {
+        return Ok(());
+    };
+    let total = members.iter().map(|member| member.weight() as usize).sum();
+    let shares = split_secret(ShamirBackend::Gf256, secret, *threshold as usize, total)?;
+    let shares = EncodedShare::wrap_all(shares, *threshold as usize, secret);
+
+    for (index, member) in members.iter().enumerate() {
+        let member_path = [path, &[index as u8]].concat();
+        let member_shares = node.member_indices(index).map(|x| shares[x - 1].clone());
+        match member {
+            PolicyNode::Member { name, .. } => {
+                for share in member_shares {
+                    out.push(PolicyShare {
+                        path: member_path.clone(),
+                        share: EncodedShare { label: Some(name.clone()), ..share },
+                    });
+                }
+            }
+            PolicyNode::Group { .. } => {
+                for share in member_shares {
+                    split_node(member, &member_path, &share.share.y, out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recovers the secret once the collected shares satisfy the policy, None while they do not
+pub fn recover_with_policy(policy: &PolicyNode, shares: &[PolicyShare], secret_len: usize) -> Result<Option<Vec<u8>>, String> {
+    recover_node(policy, &[], shares, secret_len)
+}
+
+fn recover_node(node: &PolicyNode, path: &[u8], shares: &[PolicyShare], secret_len: usize) -> Result<Option<Vec<u8>>, String> {
+    let PolicyNode::Group { threshold, members, .. } = node else {
+        return Ok(None);
+    };
+    let mut raw_shares: Vec<Share> = Vec::new();
+    for (index, member) in members.iter().enumerate() {
+        let member_path = [path, &[index as u8]].concat();
+        match member {
+            PolicyNode::Member { .. } => raw_shares.extend(
+                shares
+                    .iter()
+                    .filter(|share| share.path == member_path)
+                    .map(|share| share.share.share.clone()),
+            ),
+            PolicyNode::Group { .. } => {
+                if let Some(y) = recover_node(member, &member_path, shares, secret_len)? {
+                    let x = node.member_indices(index).start as u8;
+                    raw_shares.push(Share { backend: ShamirBackend::Gf256, x, y });
+                }
+            }
+        }
+    }
+    if raw_shares.len() < *threshold as usize {
+        return Ok(None);
+    }
+    Ok(Some(recover_secret_robust(&raw_shares, *threshold as usize, secret_len)?.secret))
+}
+
+/// One line per group and member, telling what is satisfied and what is still missing
+pub fn policy_status(policy: &PolicyNode, shares: &[PolicyShare]) -> Vec<String> {
+    let mut lines = Vec::new();
+    node_status(policy, &[], shares, 0, &mut lines);
+    lines
+}
+
+/// Pushes the status lines of `node` and returns the weight it contributes to its enclosing group
+fn node_status(node: &PolicyNode, path: &[u8], shares: &[PolicyShare], depth: usize, lines: &mut Vec<String>) -> u8 {
+    let indent = "  ".repeat(depth);
+    match node {
+        PolicyNode::Member { name, weight } => {
+            let collected = (shares.iter().filter(|share| share.path == path).count() as u8).min(*weight);
+            let mark = if collected == *weight { "✓" } else { "·" };
+            lines.push(format!("{}{} {} [{}]: {}/{} share(s)", indent, mark, name, path_to_string(path), collected, weight));
+            collected
+        }
+        PolicyNode::Group { threshold, members, .. } => {
+            let header_index = lines.len();
+            lines.push(String::new());
+            let collected: u8 = members
+                .iter()
+                .enumerate()
+                .map(|(index, member)| node_status(member, &[path, &[index as u8]].concat(), shares, depth + 1, lines))
+                .sum();
+            let total: u8 = members.iter().map(|member| member.weight()).sum();
+            lines[header_index] = if collected >= *threshold {
+                format!("{}✓ {} ({} of {}): satisfied", indent, node.name(), threshold, total)
+            } else {
+                format!(
+                    "{}✗ {} ({} of {}): {} more needed",
+                    indent,
+                    node.name(),
+                    threshold,
+                    total,
+                    threshold - collected
+                )
+            };
+            u8::from(collected >= *threshold)
+        }
+    }
+}
+
+/// Policy published next to the store, so that recovery knows the groups and can check the key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishedPolicy {
+    /// Hex fingerprint of the key, see `key_fingerprint`
+    pub fingerprint: String,
+    pub secret_len: u16,
+    pub policy: String,
+}
+
+impl PublishedPolicy {
+    pub fn new(policy: &PolicyNode, secret: &[u8]) -> Self {
+        PublishedPolicy {
+            fingerprint: hex::encode(key_fingerprint(secret)),
+            secret_len: secret.len() as u16,
+            policy: policy.to_string(),
+        }
+    }
+
+    /// Policy file matching a store file: `store.enc` -> `store.policy`
+    pub fn path_for_store(store_path: &Path) -> PathBuf {
+        store_path.with_extension(POLICY_EXTENSION)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let yaml = serde_yaml::to_string(self).map_err(|e| format!("Failed to serialize policy: {}", e))?;
+        fs::write(path, yaml).map_err(|e| format!("Failed to write policy file: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let yaml = fs::read(path).map_err(|e| format!("Failed to read policy file: {}", e))?;
+        serde_yaml::from_slice(&yaml).map_err(|e| format!("Failed to parse policy file: {}", e))
+    }
+
+    pub fn parse(&self) -> Result<PolicyNode, String> {
+        PolicyNode::parse(&self.policy)
+    }
+
+    /// Recovers the key and checks it against the published fingerprint
+    pub fn recover(&self, shares: &[PolicyShare]) -> Result<Option<Vec<u8>>, String> {
+        let Some(secret) = recover_with_policy(&self.parse()?, shares, self.secret_len as usize)? else {
+            return Ok(None);
+        };
+        if hex::encode(key_fingerprint(&secret)) != self.fingerprint {
+            return Err("Recovered key does not match the policy fingerprint, a share is corrupted".to_string());
+        }
+        Ok(Some(secret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NESTED: &str =
+        "1 of (founders: 2 of (alice, bob, carol), 2 of (1 of (alice, bob, carol), engineers: 2 of (dan, erin, frank, gina)))";
+    const SECRET: [u8; 32] = [0x42; 32];
+
+    fn shares_of<'a>(shares: &'a [PolicyShare], label: &str) -> Vec<&'a PolicyShare> {
+        shares.iter().filter(|share| share.share.label.as_deref() == Some(label)).collect()
+    }
+
+    fn recover(policy: &PolicyNode, shares: &[&PolicyShare]) -> Option<Vec<u8>> {
+        let shares: Vec<PolicyShare> = shares.iter().map(|share| (*share).clone()).collect();
+        recover_with_policy(policy, &shares, SECRET.len()).unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays_policies() {
+        for text in [NESTED, "3 of (alice*2, bob*2, carol, dan)", "team: 1 of (alice)"] {
+            let policy = PolicyNode::parse(text).unwrap();
+            assert_eq!(policy.to_string(), text);
+        }
+        assert_eq!(
+            PolicyNode::parse(" 2 of(alice *3,bob) ").unwrap(),
+            PolicyNode::Group {
+                name: None,
+                threshold: 2,
+                members: vec![
+                    PolicyNode::Member { name: "alice".to_string(), weight: 3 },
+                    PolicyNode::Member { name: "bob".to_string(), weight: 1 },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        for text in [
+            "",
+            "alice",
+            "2 of (alice, bob",
+            "2 of alice, bob",
+            "2 of (alice, bob) carol",
+            "2 of (alice, bob, )",
+            "x of (alice, bob)",
+            "3 of (alice, bob)",
+            "0 of (alice, bob)",
+            "1 of (alice*0)",
+            "1 of (alice*256)",
+            "1 of (alice*)",
+            "1 of (alice; bob)",
+            "1 of (this-member-name-is-longer-than-32-bytes)",
+        ] {
+            assert!(PolicyNode::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn limits_group_weight_to_255() {
+        assert!(PolicyNode::parse("1 of (alice*255)").is_ok());
+        assert!(PolicyNode::parse("2 of (alice*254, bob)").is_ok());
+        assert_eq!(
+            PolicyNode::parse("1 of (alice*255, bob)"),
+            Err("Group 'group' members weigh more than 255 shares".to_string())
+        );
+    }
+
+    #[test]
+    fn splits_and_recovers_at_the_weight_limit() {
+        let policy = PolicyNode::parse("1 of (alice*255)").unwrap();
+        let shares = split_with_policy(&policy, &SECRET).unwrap();
+        assert_eq!(shares.len(), 255);
+        assert_eq!(shares.last().unwrap().share.share.x, 255);
+        assert_eq!(recover(&policy, &[shares.last().unwrap()]), Some(SECRET.to_vec()));
+
+        let policy = PolicyNode::parse("2 of (alice*254, bob)").unwrap();
+        let shares = split_with_policy(&policy, &SECRET).unwrap();
+        let bob = shares_of(&shares, "bob");
+        assert_eq!(bob.len(), 1);
+        assert_eq!(bob[0].share.share.x, 255);
+        assert_eq!(bob[0].check(&policy, &[]), Ok(()));
+        assert_eq!(recover(&policy, &bob), None);
+        assert_eq!(recover(&policy, &[bob[0], &shares[0]]), Some(SECRET.to_vec()));
+    }
+
+    #[test]
+    fn splits_and_recovers_nested_policies() {
+        let policy = PolicyNode::parse(NESTED).unwrap();
+        let shares = split_with_policy(&policy, &SECRET).unwrap();
+        // alice, bob and carol appear in two groups, the engineers once each
+        assert_eq!(shares.len(), 10);
+
+        let founders: Vec<&PolicyShare> = shares.iter().filter(|share| share.path[..] == [0, 0] || share.path[..] == [0, 2]).collect();
+        assert_eq!(recover(&policy, &founders), Some(SECRET.to_vec()));
+        assert_eq!(recover(&policy, &founders[..1]), None);
+
+        let second: Vec<&PolicyShare> = shares
+            .iter()
+            .filter(|share| share.path[..] == [1, 0, 1] || share.path[..] == [1, 1, 0] || share.path[..] == [1, 1, 3])
+            .collect();
+        assert_eq!(recover(&policy, &second), Some(SECRET.to_vec()));
+        assert_eq!(recover(&policy, &second[1..]), None);
+
+        let weighted = PolicyNode::parse("3 of (alice*2, bob*2, carol, dan)").unwrap();
+        let shares = split_with_policy(&weighted, &SECRET).unwrap();
+        let alice_and_carol = [shares_of(&shares, "alice"), shares_of(&shares, "carol")].concat();
+        assert_eq!(recover(&weighted, &alice_and_carol), Some(SECRET.to_vec()));
+        assert_eq!(recover(&weighted, &shares_of(&shares, "bob")), None);
+    }
+
+    #[test]
+    fn encodes_and_checks_policy_shares() {
+        let policy = PolicyNode::parse(NESTED).unwrap();
+        let shares = split_with_policy(&policy, &SECRET).unwrap();
+        for share in &shares {
+            assert_eq!(PolicyShare::decode(&share.encode(ShareFormat::Hex)).as_ref(), Ok(share));
+            assert_eq!(share.check(&policy, &[]), Ok(()));
+        }
+        let mut moved = shares[0].clone();
+        moved.path = vec![0, 1];
+        assert_eq!(moved.check(&policy, &[]), Err("The share does not belong to this member".to_string()));
+        moved.path = vec![1, 1];
+        assert!(moved.check(&policy, &[]).is_err());
+        moved.path = vec![2, 0];
+        assert_eq!(moved.check(&policy, &[]), Err("The share path is not part of the policy".to_string()));
+        assert!(shares[0].check(&policy, &shares[..1]).unwrap_err().contains("already entered"));
+        assert!(PolicyShare::decode("x.0 abcd").is_err());
+        assert!(PolicyShare::decode("abcd").is_err());
+    }
+
+    #[test]
+    fn reports_status() {
+        let policy = PolicyNode::parse("2 of (alice*2, team: 1 of (bob, carol))").unwrap();
+        let shares = split_with_policy(&policy, &SECRET).unwrap();
+        let alice: Vec<PolicyShare> = shares_of(&shares, "alice").into_iter().cloned().collect();
+        assert_eq!(
+            policy_status(&policy, &alice[..1]),
+            [
+                "✗ group (2 of 3): 1 more needed",
+                "  · alice [0]: 1/2 share(s)",
+                "  ✗ team (1 of 2): 1 more needed",
+                "    · bob [1.0]: 0/1 share(s)",
+                "    · carol [1.1]: 0/1 share(s)",
+            ]
+        );
+        let mut collected = alice.clone();
+        collected.extend(shares_of(&shares, "carol").into_iter().cloned());
+        assert_eq!(
+            policy_status(&policy, &collected),
+            [
+                "✓ group (2 of 3): satisfied",
+                "  ✓ alice [0]: 2/2 share(s)",
+                "  ✓ team (1 of 2): satisfied",
+                "    · bob [1.0]: 0/1 share(s)",
+                "    ✓ carol [1.1]: 1/1 share(s)",
+            ]
+        );
+    }
+
+    #[test]
+    fn published_policy_checks_the_key() {
+        let policy = PolicyNode::parse("2 of (alice, bob, carol)").unwrap();
+        let published = PublishedPolicy::new(&policy, &SECRET);
+        assert_eq!(published.parse().unwrap(), policy);
+        let shares = split_with_policy(&policy, &SECRET).unwrap();
+        assert_eq!(published.recover(&shares[..2]).unwrap(), Some(SECRET.to_vec()));
+        assert_eq!(published.recover(&shares[..1]).unwrap(), None);
+        let other_key = PublishedPolicy::new(&policy, &[0x43; 32]);
+        assert!(other_key.recover(&shares[..2]).is_err());
+    }
+}
@@ -1,61 +1,152 @@
 use std::fmt::{Display, Formatter};
-use num_bigint::{BigUint, RandBigInt};
-use num_traits::FromPrimitive;
-use rand::rngs::OsRng;
-use crate::shamir_secret_sharing::MODULUS_128;
+use rand::RngCore;
+use crate::field::Field;
 
-pub struct Polynom {
-    pub coefficients: Vec<BigUint>,
+/// Polynom over the field F, `coefficients[i]` being the coefficient of xⁱ.
+/// Trailing zero coefficients are allowed and ignored: they change neither the degree, the values nor equality.
+/// The zero polynom has no non-zero coefficient and no degree.
+/// Operations run in time depending only on the number of coefficients, never on their values,
+/// except `degree` and `trimmed`, which reveal where the last non-zero coefficient is.
+#[derive(Debug, Clone)]
+pub struct Polynom<F: Field> {
+    pub coefficients: Vec<F>,
 }
 
-impl Polynom {
-    pub fn new(coefficients: Vec<BigUint>) -> Self {
+impl<F: Field> Polynom<F> {
+    pub fn new(coefficients: Vec<F>) -> Self {
         Polynom { coefficients }
     }
 
-    /// Creates a random polynom of degree deg
-    /// The coefficients are random non-zero BigUint numbers modulo MODULUS_128
-    pub fn new_random_of_degree_with_constant_term(deg: usize, constant_term: BigUint) -> Self {
-        let mut rng = OsRng::default();
-
+    /// Creates a random polynom of degree at most deg, with deg + 1 coefficients
+    /// The coefficients are uniformly random elements of the field F, zero included, drawn from `rng`:
+    /// excluding zero would bias the shares, e.g. a GF(2⁸) share of a 2-of-n split could never equal the secret
+    pub fn new_random_of_degree_with_constant_term<R: RngCore + ?Sized>(deg: usize, constant_term: F, rng: &mut R) -> Self {
         let mut coefficients = Vec::with_capacity(deg + 1);
         coefficients.push(constant_term);
         for _ in 1..=deg {
-            let mut coef = BigUint::ZERO;
-            while coef == BigUint::ZERO {
-                coef = rng.gen_biguint(128) % MODULUS_128.clone();
-            }
-            coefficients.push(coef);
+            coefficients.push(F::random(rng));
         }
-        Polynom { coefficients }
+        Polynom::new(coefficients)
     }
 
     /// Returns the first n points of the polynom: f(1), f(2), ..., f(n)
-    pub fn get_firsts_n_points(&self, n: usize) -> Vec<(BigUint, BigUint)> {
-        let mut points = Vec::with_capacity(n);
-        for x_us in 1..=n {
-            let x= BigUint::from_usize(x_us).unwrap();
+    /// n can not exceed 255 so that every field can hold the x coordinates
+    pub fn get_firsts_n_points(&self, n: u8) -> Vec<(F, F)> {
+        let mut points = Vec::with_capacity(n as usize);
+        for x_u8 in 1..=n {
+            let x = F::from_index(x_u8);
             let y = self.calculate_y(x.clone());
             points.push((x, y));
         }
         points
     }
 
-    pub fn calculate_y(&self, x: BigUint) -> BigUint {
-        let mut y = BigUint::ZERO;
-        let mut x_pow = BigUint::from_usize(1).unwrap(); // x^0 = 1
-        for coef in &self.coefficients {
-            y = (y + coef * &x_pow) % MODULUS_128.clone();
-            x_pow = (x_pow * &x) % MODULUS_128.clone(); // x^i = x^(i-1) * x
+    /// Horner evaluation: (((a_d·x + a_d-1)·x + ...)·x + a_0
+    pub fn calculate_y(&self, x: F) -> F {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(F::zero(), |y, coef| y.mul(&x).add(coef))
+    }
+
+    pub fn zero() -> Self {
+        Polynom { coefficients: Vec::new() }
+    }
+
+    /// Coefficients without the trailing zeros
+    pub fn trimmed(&self) -> &[F] {
+        let len = self.coefficients.iter().rposition(|coef| !coef.is_zero()).map_or(0, |i| i + 1);
+        &self.coefficients[..len]
+    }
+
+    /// Index of the highest non-zero coefficient, None for the zero polynom
+    pub fn degree(&self) -> Option<usize> {
+        self.trimmed().len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.degree().is_none()
+    }
+
+    /// Whether the polynom is of degree at most `deg`: a Shamir polynom of threshold t is of degree at most t - 1
+    pub fn has_degree_at_most(&self, deg: usize) -> bool {
+        self.degree().is_none_or(|d| d <= deg)
+    }
+
+    pub fn add(&self, rhs: &Self) -> Self {
+        let len = self.coefficients.len().max(rhs.coefficients.len());
+        let zero = F::zero();
+        let coefficients = (0..len)
+            .map(|i| {
+                let a = self.coefficients.get(i).unwrap_or(&zero);
+                a.add(rhs.coefficients.get(i).unwrap_or(&zero))
+            })
+            .collect();
+        Polynom { coefficients }
+    }
+
+    pub fn sub(&self, rhs: &Self) -> Self {
+        self.add(&rhs.scale(&F::zero().sub(&F::one())))
+    }
+
+    /// Product of the polynoms, of degree deg(self) + deg(rhs)
+    pub fn mul(&self, rhs: &Self) -> Self {
+        if self.coefficients.is_empty() || rhs.coefficients.is_empty() {
+            return Self::zero();
+        }
+        let mut coefficients = vec![F::zero(); self.coefficients.len() + rhs.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in rhs.coefficients.iter().enumerate() {
+                coefficients[i + j] = coefficients[i + j].add(&a.mul(b));
+            }
+        }
+        Polynom { coefficients }
+    }
+
+    /// Multiplies every coefficient by `factor`
+    pub fn scale(&self, factor: &F) -> Self {
+        Polynom { coefficients: self.coefficients.iter().map(|coef| coef.mul(factor)).collect() }
+    }
+
+    /// Lowest degree polynom going through all the points, of degree at most `points.len() - 1`.
+    /// Built from Newton's divided differences, the x coordinates must be distinct.
+    pub fn interpolate(points: &[(F, F)]) -> Result<Self, String> {
+        for (i, (x, _)) in points.iter().enumerate() {
+            if points[..i].iter().any(|(other, _)| other == x) {
+                return Err("Interpolation points must have distinct x coordinates".to_string());
+            }
+        }
+
+        // differences[i] ends up as f[x_0, ..., x_i], the coefficient of the i-th Newton basis polynom
+        let mut differences: Vec<F> = points.iter().map(|(_, y)| y.clone()).collect();
+        for order in 1..points.len() {
+            for i in (order..points.len()).rev() {
+                let numerator = differences[i].sub(&differences[i - 1]);
+                let denominator = points[i].0.sub(&points[i - order].0);
+                differences[i] = numerator.mul(&denominator.inv().unwrap());
+            }
+        }
+
+        // Nested Newton form: c_0 + (x - x_0)·(c_1 + (x - x_1)·(c_2 + ...))
+        let mut polynom = Self::zero();
+        for (i, difference) in differences.iter().enumerate().rev() {
+            let root = Polynom { coefficients: vec![F::zero().sub(&points[i].0), F::one()] };
+            polynom = polynom.mul(&root).add(&Polynom { coefficients: vec![difference.clone()] });
         }
-        y
+        Ok(polynom)
     }
 }
 
-impl Display for Polynom {
+impl<F: Field> PartialEq for Polynom<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl<F: Field + Display> Display for Polynom<F> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let str = self.coefficients.iter().enumerate().map(|(i, coef)| {
-            if (i == 0) {
+            if i == 0 {
                 coef.to_string()
             }else {
                 format!("{}x^{}", coef, i)
@@ -65,3 +156,147 @@ impl Display for Polynom {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Fp521, Gf256};
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn fp(value: u32) -> Fp521 {
+        Fp521::from_biguint(BigUint::from(value)).unwrap()
+    }
+
+    #[test]
+    fn evaluates_known_polynoms() {
+        // 42 + 7x + 3x²
+        let polynom = Polynom::new(vec![fp(42), fp(7), fp(3)]);
+        assert_eq!(polynom.calculate_y(fp(0)), fp(42));
+        assert_eq!(polynom.calculate_y(fp(2)), fp(68));
+        assert_eq!(polynom.calculate_y(fp(10)), fp(412));
+
+        // 0x53 + 0xca·x over GF(2⁸), 0xca being the inverse of 0x53
+        let polynom = Polynom::new(vec![Gf256(0x53), Gf256(0xca)]);
+        assert_eq!(polynom.calculate_y(Gf256(0x53)), Gf256(0x53 ^ 0x01));
+        assert_eq!(polynom.calculate_y(Gf256(1)), Gf256(0x99));
+    }
+
+    #[test]
+    fn first_points_start_at_one() {
+        let polynom = Polynom::new(vec![fp(1), fp(1)]);
+        let points = polynom.get_firsts_n_points(255);
+        assert_eq!(points.len(), 255);
+        assert_eq!(points[0], (fp(1), fp(2)));
+        assert_eq!(points[254], (fp(255), fp(256)));
+        assert!(polynom.get_firsts_n_points(0).is_empty());
+    }
+
+    #[test]
+    fn random_polynom_keeps_constant_term_and_degree() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        for deg in [0, 1, 5] {
+            let polynom = Polynom::new_random_of_degree_with_constant_term(deg, Gf256(0), &mut rng);
+            assert_eq!(polynom.coefficients.len(), deg + 1);
+            assert_eq!(polynom.coefficients[0], Gf256(0));
+        }
+    }
+
+    #[test]
+    fn gf256_share_is_uniform() {
+        // A single share of a 2-of-n split must tell nothing about the secret byte:
+        // over many splits it takes every value, the secret included, about equally often
+        const SECRET: Gf256 = Gf256(0x42);
+        const SPLITS_PER_VALUE: usize = 200;
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let mut counts = [0usize; 256];
+        for _ in 0..256 * SPLITS_PER_VALUE {
+            let polynom = Polynom::new_random_of_degree_with_constant_term(1, SECRET, &mut rng);
+            counts[polynom.calculate_y(Gf256(1)).0 as usize] += 1;
+        }
+        // Each count is binomial with a standard deviation of about 14, 6 of them away from the mean fails
+        for (value, count) in counts.iter().enumerate() {
+            assert!((115..=285).contains(count), "share {:#04x} drawn {} times", value, count);
+        }
+        assert!(counts[SECRET.0 as usize] > 0);
+        // Pearson's χ² with 255 degrees of freedom stays well below 400 for uniform draws
+        let expected = SPLITS_PER_VALUE as f64;
+        let chi_square: f64 = counts.iter().map(|count| (*count as f64 - expected).powi(2) / expected).sum();
+        assert!(chi_square < 400.0, "χ² = {}", chi_square);
+    }
+
+    #[test]
+    fn random_polynom_is_reproducible_from_a_seed() {
+        let random = |seed| {
+            Polynom::new_random_of_degree_with_constant_term(4, fp(5), &mut ChaCha20Rng::seed_from_u64(seed)).coefficients
+        };
+        assert_eq!(random(7), random(7));
+        assert_ne!(random(7), random(8));
+    }
+
+    fn fps(values: &[u32]) -> Polynom<Fp521> {
+        Polynom::new(values.iter().map(|v| fp(*v)).collect())
+    }
+
+    #[test]
+    fn arithmetic_on_known_polynoms() {
+        let a = fps(&[1, 2]); // 1 + 2x
+        let b = fps(&[3, 0, 1]); // 3 + x²
+        assert_eq!(a.add(&b), fps(&[4, 2, 1]));
+        assert_eq!(b.sub(&b), Polynom::zero());
+        assert_eq!(a.mul(&b), fps(&[3, 6, 1, 2]));
+        assert_eq!(a.scale(&fp(3)), fps(&[3, 6]));
+        assert_eq!(a.mul(&Polynom::zero()), Polynom::zero());
+        assert_eq!(Polynom::new(vec![Gf256(0x53)]).mul(&Polynom::new(vec![Gf256(0xca)])), Polynom::new(vec![Gf256(1)]));
+    }
+
+    #[test]
+    fn degree_ignores_trailing_zeros() {
+        assert_eq!(fps(&[1, 2, 0, 0]).degree(), Some(1));
+        assert_eq!(fps(&[1, 2, 0, 0]), fps(&[1, 2]));
+        assert_eq!(fps(&[0, 0]).degree(), None);
+        assert!(fps(&[]).is_zero());
+        assert_eq!(fps(&[5]).degree(), Some(0));
+        assert!(fps(&[1, 2, 3]).has_degree_at_most(2));
+        assert!(!fps(&[1, 2, 3]).has_degree_at_most(1));
+        assert!(Polynom::<Fp521>::zero().has_degree_at_most(0));
+    }
+
+    #[test]
+    fn interpolates_known_points() {
+        let points = [(fp(1), fp(52)), (fp(2), fp(68)), (fp(3), fp(90))];
+        assert_eq!(Polynom::interpolate(&points).unwrap(), fps(&[42, 7, 3]));
+        // Points on a line give a polynom of degree 1, however many there are
+        let line = fps(&[4, 1]).get_firsts_n_points(6);
+        assert_eq!(Polynom::interpolate(&line).unwrap().degree(), Some(1));
+        assert_eq!(Polynom::<Fp521>::interpolate(&[]).unwrap(), Polynom::zero());
+        assert!(Polynom::interpolate(&[(fp(1), fp(2)), (fp(1), fp(3))]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn interpolation_inverts_evaluation(
+            coefficients in prop::collection::vec(any::<u8>(), 1..=12),
+            extra in 0u8..=4,
+        ) {
+            let polynom = Polynom::new(coefficients.into_iter().map(Gf256).collect());
+            let points = polynom.get_firsts_n_points(polynom.coefficients.len() as u8 + extra);
+            let interpolated = Polynom::interpolate(&points).unwrap();
+            prop_assert_eq!(&interpolated, &polynom);
+            prop_assert!(interpolated.has_degree_at_most(polynom.coefficients.len() - 1));
+        }
+
+        #[test]
+        fn product_evaluates_to_product_of_values(
+            a in prop::collection::vec(any::<u32>(), 0..=6),
+            b in prop::collection::vec(any::<u32>(), 0..=6),
+            x in any::<u32>(),
+        ) {
+            let (a, b, x) = (fps(&a), fps(&b), fp(x));
+            prop_assert_eq!(a.mul(&b).calculate_y(x), a.calculate_y(x).mul(&b.calculate_y(x)));
+            prop_assert_eq!(a.add(&b).calculate_y(x), a.calculate_y(x).add(&b.calculate_y(x)));
+        }
+    }
+}
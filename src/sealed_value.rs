@@ -0,0 +1,147 @@
+use hkdf::Hkdf;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::Sha256;
+use crate::encrypt::{Cipher, KEY_BYTES};
+use crate::secret::{SecretBytes, SecretString};
+use crate::split_value::SPLIT_MARKER_PREFIX;
+
+/// Prefix of an entry value kept encrypted in the store and in memory
+pub const SEALED_VALUE_PREFIX: &str = "bunkeys-sealed:";
+/// Prefix escaping a plaintext value that starts like a sealed, split or escaped value
+pub const PLAIN_VALUE_PREFIX: &str = "bunkeys-plain:";
+/// Random salt of each value, so that every value is sealed under its own subkey
+const SALT_BYTES: usize = 16;
+const SUBKEY_INFO: &[u8] = b"bunkeys entry value";
+const VALUE_CIPHER: Cipher = Cipher::XChaCha20Poly1305;
+
+/// Whether an entry value is sealed rather than plaintext
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_VALUE_PREFIX)
+}
+
+/// Plaintext value as kept in the store, escaped when it would otherwise read as sealed or split
+pub fn escape_plain(value: &str) -> Option<String> {
+    [SEALED_VALUE_PREFIX, SPLIT_MARKER_PREFIX, PLAIN_VALUE_PREFIX]
+        .iter()
+        .any(|prefix| value.trim_start().starts_with(prefix))
+        .then(|| format!("{}{}", PLAIN_VALUE_PREFIX, value))
+}
+
+/// Plaintext of a value that is not sealed, undoing `escape_plain`
+pub fn unescape_plain(value: &str) -> &str {
+    value.strip_prefix(PLAIN_VALUE_PREFIX).unwrap_or(value)
+}
+
+/// HKDF-SHA256 subkey of the store data key for one value
+fn value_key(data_key: &[u8], salt: &[u8]) -> Result<SecretBytes, String> {
+    let mut key = SecretBytes::new(vec![0u8; KEY_BYTES]);
+    Hkdf::<Sha256>::new(Some(salt), data_key)
+        .expand(SUBKEY_INFO, key.expose_mut())
+        .map_err(|e| format!("Failed to derive the value key: {}", e))?;
+    Ok(key)
+}
+
+/// Seals an entry value under a subkey of the data key:
+/// `bunkeys-sealed:<salt hex>:<nonce hex>:<ciphertext hex>`
+pub fn seal_value(data_key: &[u8], value: &str) -> Result<String, String> {
+    let mut salt = [0u8; SALT_BYTES];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = VALUE_CIPHER.generate_nonce();
+    let ciphertext = VALUE_CIPHER.encrypt(value_key(data_key, &salt)?.expose(), &nonce, value.as_bytes(), &salt)?;
+    Ok(format!(
+        "{}{}:{}:{}",
+        SEALED_VALUE_PREFIX,
+        hex::encode(salt),
+        hex::encode(nonce),
+        hex::encode(ciphertext)
+    ))
+}
+
+/// Decrypts a value sealed by `seal_value` under the same data key
+pub fn open_value(data_key: &[u8], sealed: &str) -> Result<SecretString, String> {
+    let fields = sealed
+        .trim()
+        .strip_prefix(SEALED_VALUE_PREFIX)
+        .ok_or_else(|| "Value is not sealed".to_string())?;
+    let decoded = fields
+        .split(':')
+        .map(hex::decode)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid sealed value: {}", e))?;
+    let [salt, nonce, ciphertext] = decoded.as_slice() else {
+        return Err("Invalid sealed value: expected salt, nonce and ciphertext".to_string());
+    };
+    if salt.len() != SALT_BYTES || nonce.len() != VALUE_CIPHER.nonce_len() {
+        return Err("Invalid sealed value: wrong salt or nonce length".to_string());
+    }
+    let plaintext = VALUE_CIPHER
+        .decrypt(value_key(data_key, salt)?.expose(), nonce, ciphertext, salt)
+        .map_err(|_| "Failed to open the sealed value, wrong store key or tampered value".to_string())?;
+    String::from_utf8(plaintext).map(SecretString::new).map_err(|e| {
+        let error = format!("Sealed value is not valid UTF-8: {}", e.utf8_error());
+        drop(SecretBytes::new(e.into_bytes()));
+        error
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::share_encoding::FINGERPRINT_BYTES;
+    use crate::split_value::SplitMarker;
+
+    const DATA_KEY: [u8; 32] = [9; 32];
+
+    #[test]
+    fn sealed_value_round_trips() {
+        let sealed = seal_value(&DATA_KEY, "hunter2").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("hunter2"));
+        assert_eq!(open_value(&DATA_KEY, &sealed).unwrap().expose(), "hunter2");
+        assert_eq!(open_value(&DATA_KEY, &seal_value(&DATA_KEY, "").unwrap()).unwrap().expose(), "");
+    }
+
+    #[test]
+    fn each_value_sealed_under_its_own_subkey() {
+        let first = seal_value(&DATA_KEY, "same").unwrap();
+        let second = seal_value(&DATA_KEY, "same").unwrap();
+        assert_ne!(first, second);
+        assert_ne!(first.split(':').nth(1), second.split(':').nth(1));
+    }
+
+    #[test]
+    fn sealed_value_rejects_wrong_key_and_tampering() {
+        let sealed = seal_value(&DATA_KEY, "hunter2").unwrap();
+        assert!(open_value(&[8; 32], &sealed).is_err());
+
+        let mut fields: Vec<String> = sealed.split(':').map(str::to_string).collect();
+        fields[1] = hex::encode([0u8; SALT_BYTES]);
+        assert!(open_value(&DATA_KEY, &fields.join(":")).is_err());
+        assert!(open_value(&DATA_KEY, "bunkeys-sealed:00:11").is_err());
+        assert!(open_value(&DATA_KEY, "hunter2").is_err());
+    }
+
+    #[test]
+    fn plaintext_looking_sealed_is_escaped() {
+        for value in ["bunkeys-sealed:not really", "bunkeys-plain:", "bunkeys-plain:bunkeys-sealed:x"] {
+            let escaped = escape_plain(value).unwrap();
+            assert!(!is_sealed(&escaped));
+            assert_eq!(unescape_plain(&escaped), value);
+        }
+        assert_eq!(escape_plain("hunter2"), None);
+        assert_eq!(unescape_plain("hunter2"), "hunter2");
+        assert_eq!(escape_plain(""), None);
+    }
+
+    #[test]
+    fn plaintext_looking_split_is_escaped() {
+        let marker = SplitMarker { threshold: 2, share_count: 3, fingerprint: [0xab; FINGERPRINT_BYTES] }.to_value();
+        for value in [marker.clone(), format!(" {}", marker)] {
+            assert!(SplitMarker::parse(&value).is_some());
+            let escaped = escape_plain(&value).unwrap();
+            assert_eq!(SplitMarker::parse(&escaped), None);
+            assert_eq!(unescape_plain(&escaped), value);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+/// Locks the pages of a buffer in memory so that they are not swapped out, where the OS allows it.
+/// Pages are shared with other allocations, unlocking one buffer may unlock its neighbours too:
+/// locking is a best effort, wiping on drop is what guarantees secrets do not outlive their owner.
+fn lock(ptr: *const u8, len: usize) {
+    #[cfg(unix)]
+    if len > 0 {
+        // SAFETY: the range is a live allocation, mlock does not touch its contents
+        unsafe {
+            libc::mlock(ptr as *const libc::c_void, len);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (ptr, len);
+}
+
+fn unlock(ptr: *const u8, len: usize) {
+    #[cfg(unix)]
+    if len > 0 {
+        // SAFETY: same range as the one locked, still allocated
+        unsafe {
+            libc::munlock(ptr as *const libc::c_void, len);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (ptr, len);
+}
+
+/// Key material, wiped when dropped and never printed
+#[derive(Default)]
+pub struct SecretBytes {
+    bytes: Vec<u8>,
+}
+
+impl SecretBytes {
+    /// Takes ownership of the bytes, the buffer is never reallocated afterwards
+    pub fn new(bytes: Vec<u8>) -> Self {
+        lock(bytes.as_ptr(), bytes.capacity());
+        Self { bytes }
+    }
+    /// `len` random bytes from the OS generator
+    pub fn random(len: usize) -> Self {
+        let mut secret = Self::new(vec![0u8; len]);
+        OsRng.fill_bytes(secret.expose_mut());
+        secret
+    }
+    pub fn expose(&self) -> &[u8] {
+        &self.bytes
+    }
+    /// Lets the bytes be filled in place, without changing their length
+    pub fn expose_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl Clone for SecretBytes {
+    fn clone(&self) -> Self {
+        Self::new(self.bytes.clone())
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes.ct_eq(&other.bytes).into()
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretBytes([REDACTED])")
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        let (ptr, capacity) = (self.bytes.as_ptr(), self.bytes.capacity());
+        self.bytes.zeroize();
+        unlock(ptr, capacity);
+    }
+}
+
+/// Secret text such as an entry value, a mnemonic or a typed passphrase, wiped when dropped and never printed
+#[derive(Default)]
+pub struct SecretString {
+    text: String,
+}
+
+impl SecretString {
+    /// Takes ownership of the text, the buffer is never reallocated afterwards
+    pub fn new(text: String) -> Self {
+        lock(text.as_ptr(), text.capacity());
+        Self { text }
+    }
+    pub fn expose(&self) -> &str {
+        &self.text
+    }
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+    /// New secret with `c` inserted at byte index `at`, allocated once at its final size
+    pub fn with_insert(&self, at: usize, c: char) -> Self {
+        let mut text = String::with_capacity(self.text.len() + c.len_utf8());
+        text.push_str(&self.text[..at]);
+        text.push(c);
+        text.push_str(&self.text[at..]);
+        Self::new(text)
+    }
+    /// New secret without the char at byte index `at`, allocated once at its final size
+    pub fn with_remove(&self, at: usize) -> Self {
+        let removed = self.text[at..].chars().next().map_or(0, char::len_utf8);
+        let mut text = String::with_capacity(self.text.len() - removed);
+        text.push_str(&self.text[..at]);
+        text.push_str(&self.text[at + removed..]);
+        Self::new(text)
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(text: &str) -> Self {
+        Self::new(text.to_string())
+    }
+}
+
+impl Clone for SecretString {
+    fn clone(&self) -> Self {
+        Self::new(self.text.clone())
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        self.text.as_bytes().ct_eq(other.text.as_bytes()).into()
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        let (ptr, capacity) = (self.text.as_ptr(), self.text.capacity());
+        self.text.zeroize();
+        unlock(ptr, capacity);
+    }
+}
+
+/// Entry values are written as plain strings in the store YAML
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted_in_debug() {
+        let key = SecretBytes::new(vec![0x42; 32]);
+        let text = SecretString::from("hunter2");
+        assert_eq!(format!("{:?}", key), "SecretBytes([REDACTED])");
+        assert!(!format!("{:?}", Some(text.clone())).contains("hunter2"));
+        assert_eq!(key.clone(), key);
+        assert_ne!(SecretBytes::random(32), SecretBytes::random(32));
+    }
+
+    #[test]
+    fn secret_string_edits() {
+        let text = SecretString::from("héllo");
+        assert_eq!(text.with_insert(0, '¡').expose(), "¡héllo");
+        assert_eq!(text.with_remove(1).expose(), "hllo");
+        assert_eq!(text.with_remove(text.expose().len() - 1).expose(), "héll");
+    }
+}
@@ -1,89 +1,648 @@
-use bip39::Mnemonic;
-use lazy_static::lazy_static;
-use num_bigint::{BigUint, RandBigInt};
-use num_traits::FromPrimitive;
+use num_bigint::BigUint;
 use rand::rngs::OsRng;
+use rand::RngCore;
+use strum_macros::EnumIter;
+use crate::field::{Field, Fp521, Gf256};
 use crate::polynom;
 
-lazy_static! {
-    // 128-bit prime modulus (2¹²⁷ - 1)
-    pub static ref MODULUS_128: BigUint = {
-        let prime_128_str: &str = "170141183460469231731687303715884105727"; // 2¹²⁷ - 1
-        let modulus_128_parsed = BigUint::parse_bytes(prime_128_str.as_bytes(), 10).unwrap();
-        let modulus_128_calculated = BigUint::from_u64(2).unwrap().pow(127) - BigUint::from_u64(1).unwrap();
-        assert_eq!(modulus_128_parsed, modulus_128_calculated);
-        modulus_128_parsed
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum ShamirBackend {
+    /// A single polynom over GF(2⁵²¹ - 1), the secret must fit in 65 bytes
+    #[default]
+    PrimeField,
+    /// One polynom over GF(2⁸) per byte of the secret, for secrets of any length
+    Gf256,
+}
+
+impl ShamirBackend {
+    pub fn to_string(self) -> (&'static str, &'static str) {
+        match self {
+            ShamirBackend::PrimeField => ("Prime field GF(2⁵²¹ - 1)", "Whole key as a single 521-bit field element"),
+            ShamirBackend::Gf256 => ("Byte-wise GF(2⁸)", "Each byte split on its own, like ssss and HashiCorp Vault"),
+        }
+    }
+}
+
+/// A share of a secret: the x coordinate and the y value(s) of the backend polynom(s)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub backend: ShamirBackend,
+    pub x: u8,
+    /// PrimeField: one 66-byte field element, Gf256: one byte per secret byte
+    pub y: Vec<u8>,
+}
+
+/// Splits a secret (big-endian bytes) into `share_count` points of random polynom(s) of degree `threshold - 1`.
+/// Any `threshold` of the returned shares are enough to find the secret back.
+pub fn split_secret(
+    backend: ShamirBackend,
+    secret: &[u8],
+    threshold: usize,
+    share_count: usize,
+) -> Result<Vec<Share>, String> {
+    split_secret_with_rng(backend, secret, threshold, share_count, &mut OsRng)
+}
+
+/// `split_secret` drawing the polynom coefficients from `rng`, which must be a CSPRNG outside of tests
+pub fn split_secret_with_rng<R: RngCore + ?Sized>(
+    backend: ShamirBackend,
+    secret: &[u8],
+    threshold: usize,
+    share_count: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, String> {
+    let share_count = check_split_parameters(threshold, share_count)?;
+
+    match backend {
+        ShamirBackend::PrimeField => {
+            let polynom = prime_field_polynom(secret, threshold, rng)?;
+            Ok(prime_field_shares(&polynom, share_count))
+        }
+        ShamirBackend::Gf256 => {
+            let mut shares: Vec<Share> = (1..=share_count)
+                .map(|x| Share { backend, x, y: Vec::with_capacity(secret.len()) })
+                .collect();
+            for byte in secret {
+                let polynom = polynom::Polynom::new_random_of_degree_with_constant_term(threshold - 1, Gf256(*byte), rng);
+                for (share, (_, y)) in shares.iter_mut().zip(polynom.get_firsts_n_points(share_count)) {
+                    share.y.push(y.0);
+                }
+            }
+            Ok(shares)
+        }
+    }
+}
+
+/// Validates the threshold and share count, returning the share count as a share index
+pub(crate) fn check_split_parameters(threshold: usize, share_count: usize) -> Result<u8, String> {
+    if threshold == 0 {
+        return Err("Threshold must be at least 1".to_string());
+    }
+    if share_count < threshold {
+        return Err("Share count must be greater than or equal to the threshold".to_string());
+    }
+    if share_count > 255 {
+        return Err("Share count must be at most 255".to_string());
+    }
+    Ok(share_count as u8)
 }
 
-pub fn shamir_test() {
-    let mut rng = OsRng::default();
-    let secret = rng.gen_biguint(127);
-    let secret_mnemonic = Mnemonic::from_entropy(&secret.to_bytes_be()).unwrap().to_string();
-    let points_number: u64 = 6;
-    let threshold: usize = 3;
+/// Random polynom of degree `threshold - 1` over GF(2⁵²¹ - 1) whose constant term is the secret
+pub(crate) fn prime_field_polynom<R: RngCore + ?Sized>(
+    secret: &[u8],
+    threshold: usize,
+    rng: &mut R,
+) -> Result<polynom::Polynom<Fp521>, String> {
+    if secret.len() >= Fp521::BYTES {
+        return Err("Secret is too long for the prime field backend, use GF(2⁸) instead".to_string());
+    }
+    let secret = Fp521::from_biguint(BigUint::from_bytes_be(secret)).unwrap();
+    Ok(polynom::Polynom::new_random_of_degree_with_constant_term(threshold - 1, secret, rng))
+}
+
+pub(crate) fn prime_field_shares(polynom: &polynom::Polynom<Fp521>, share_count: u8) -> Vec<Share> {
+    polynom
+        .get_firsts_n_points(share_count)
+        .into_iter()
+        .zip(1..=share_count)
+        .map(|((_, y), x)| Share { backend: ShamirBackend::PrimeField, x, y: y.to_bytes() })
+        .collect()
+}
 
-    println!("Prime: {}", *MODULUS_128);
-    println!("Secret: {}", secret);
-    println!("Secret mnemonic: {}", secret_mnemonic);
+/// Rebuilds a secret of `secret_len` bytes from the given shares.
+/// The caller is responsible for providing at least `threshold` shares.
+pub fn recover_secret(shares: &[Share], secret_len: usize) -> Result<Vec<u8>, String> {
+    let first = check_shares(shares, secret_len)?;
 
-    // A polynom of degree threshold - 1 can be determined by threshold points.
-    let polynom = polynom::Polynom::new_random_of_degree_with_constant_term(threshold - 1, secret.clone());
-    let points = polynom.get_firsts_n_points(points_number as usize);
+    match first.backend {
+        ShamirBackend::PrimeField => {
+            let points = shares
+                .iter()
+                .map(|share| {
+                    Fp521::from_bytes(&share.y)
+                        .map(|y| (Fp521::from_index(share.x), y))
+                        .ok_or_else(|| format!("Share {} value is out of the field range", share.x))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let secret = get_polynom_constant_value(&points)?.to_bytes();
+            let (padding, secret) = secret.split_at(Fp521::BYTES - secret_len);
+            if padding.iter().any(|b| *b != 0) {
+                return Err("Recovered secret is too long, the shares do not match".to_string());
+            }
+            Ok(secret.to_vec())
+        }
+        ShamirBackend::Gf256 => {
+            (0..secret_len)
+                .map(|i| {
+                    let points: Vec<(Gf256, Gf256)> = shares
+                        .iter()
+                        .map(|share| (Gf256::from_index(share.x), Gf256(share.y[i])))
+                        .collect();
+                    get_polynom_constant_value(&points).map(|y| y.0)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Checks that the shares can be interpolated together, returning the first one
+fn check_shares(shares: &[Share], secret_len: usize) -> Result<&Share, String> {
+    let Some(first) = shares.first() else {
+        return Err("No share provided".to_string());
+    };
+    if first.backend == ShamirBackend::PrimeField && secret_len > Fp521::BYTES {
+        return Err(format!("A prime field secret holds at most {} bytes, not {}", Fp521::BYTES, secret_len));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.backend != first.backend {
+            return Err("Shares were not generated with the same backend".to_string());
+        }
+        if shares[..i].iter().any(|other| other.x == share.x) {
+            return Err(format!("Share {} was provided twice", share.x));
+        }
+        match share.backend {
+            ShamirBackend::PrimeField if Fp521::from_bytes(&share.y).is_none() => {
+                return Err(format!("Share {} value is out of the field range", share.x));
+            }
+            ShamirBackend::Gf256 if share.y.len() != secret_len => {
+                return Err(format!("Shares must hold exactly {} bytes", secret_len));
+            }
+            _ => {}
+        }
+    }
+    Ok(first)
+}
 
-    println!("Polynom: {}", polynom);
-    println!(
-        "Points: {}",
-        points
-            .iter()
-            .map(|(x, y)| format!("({}, {})", x, y))
-            .collect::<Vec<String>>()
-            .join(", ")
-    );
+/// Upper bound on the t-subsets tried by `recover_secret_robust`
+const MAX_ROBUST_SUBSETS: usize = 100_000;
 
-    // Generate back the secret
-    let ans = get_polynom_constant_value(&points);
-    println!("Reconstituted secret: {}", ans);
-    println!("Secret: {}", polynom.coefficients[0]);
+/// A secret rebuilt by `recover_secret_robust`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovery {
+    pub secret: Vec<u8>,
+    /// Indices of the shares that do not lie on the recovered polynom(s)
+    pub inconsistent: Vec<u8>,
+}
 
+/// Rebuilds a secret from `threshold` or more shares, tolerating wrong ones.
+/// t-subsets of the shares are tried until the polynom(s) they define agree with enough of the other shares:
+/// with e wrong shares out of n, the right polynom is the only one going through n - e ≥ t + e shares,
+/// so the secret is recovered, and the wrong shares named, as long as n ≥ t + 2e.
+pub fn recover_secret_robust(shares: &[Share], threshold: usize, secret_len: usize) -> Result<Recovery, String> {
+    check_shares(shares, secret_len)?;
+    if threshold == 0 {
+        return Err("Threshold must be at least 1".to_string());
+    }
+    if shares.len() < threshold {
+        return Err(format!("{} more share(s) needed", threshold - shares.len()));
+    }
 
-    // Testing that all combinations of threshold points are enough to find the secret
     use itertools::Itertools;
-    for combo in points.iter().combinations(threshold) {
-        let combo_points: Vec<(BigUint, BigUint)> = combo.into_iter().map(|(x, y)| (x.clone(), y.clone())).collect();
-        let secret_reconstituted = get_polynom_constant_value(&combo_points);
-        //println!("Secret recostituted from points {} is {}", combo_points.iter().map(|(x, y)| format!("({}, {})", x, y)).collect::<Vec<String>>().join(", "), secret_reconstituted);
-        assert_eq!(secret_reconstituted, secret);
-        let secret_mnemonic_reconstituted = Mnemonic::from_entropy(&secret_reconstituted.to_bytes_be()).unwrap().to_string();
-        assert_eq!(secret_mnemonic, secret_mnemonic_reconstituted);
+    let mut best_agreement = 0;
+    for (tried, subset) in (0..shares.len()).combinations(threshold).enumerate() {
+        if tried == MAX_ROBUST_SUBSETS {
+            return Err("Too many shares disagree to find the right ones".to_string());
+        }
+        let basis: Vec<Share> = subset.into_iter().map(|i| shares[i].clone()).collect();
+        let agrees = lie_on_same_polynoms(&basis, shares);
+        let agreement = agrees.iter().filter(|agree| **agree).count();
+        best_agreement = best_agreement.max(agreement);
+
+        if agreement >= threshold + (shares.len() - agreement) {
+            return Ok(Recovery {
+                secret: recover_secret(&basis, secret_len)?,
+                inconsistent: shares
+                    .iter()
+                    .zip(agrees)
+                    .filter(|(_, agree)| !agree)
+                    .map(|(share, _)| share.x)
+                    .collect(),
+            });
+        }
+    }
+    let disagreeing = shares.len() - best_agreement;
+    Err(format!(
+        "The shares disagree, {} more share(s) needed to tell which ones are wrong",
+        threshold + 2 * disagreeing - shares.len()
+    ))
+}
+
+/// Checks that at least `threshold` shares are given and that they all lie on the same polynom(s)
+pub(crate) fn check_consistent_shares(shares: &[Share], threshold: usize, secret_len: usize) -> Result<&Share, String> {
+    let first = check_shares(shares, secret_len)?;
+    if threshold == 0 || shares.len() < threshold {
+        return Err(format!("At least {} shares are needed", threshold.max(1)));
+    }
+    let disagreeing: Vec<String> = shares
+        .iter()
+        .zip(lie_on_same_polynoms(&shares[..threshold], shares))
+        .filter(|(_, agree)| !agree)
+        .map(|(share, _)| share.x.to_string())
+        .collect();
+    if !disagreeing.is_empty() {
+        return Err(format!("Shares {} disagree with the others", disagreeing.join(", ")));
+    }
+    Ok(first)
+}
+
+/// Proactively refreshes shares by adding to each of them the value of a random polynom of degree `threshold - 1`
+/// with a zero constant term. The secret is unchanged, but the refreshed shares no longer combine with the old ones,
+/// so the share of a custodian who left becomes useless once every remaining custodian holds a refreshed share.
+pub fn refresh_shares(shares: &[Share], threshold: usize, secret_len: usize) -> Result<Vec<Share>, String> {
+    refresh_shares_with_rng(shares, threshold, secret_len, &mut OsRng)
+}
+
+/// `refresh_shares` drawing the zero-constant polynoms from `rng`, which must be a CSPRNG outside of tests
+pub fn refresh_shares_with_rng<R: RngCore + ?Sized>(
+    shares: &[Share],
+    threshold: usize,
+    secret_len: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, String> {
+    let first = check_consistent_shares(shares, threshold, secret_len)?;
+    match first.backend {
+        ShamirBackend::PrimeField => {
+            let delta = polynom::Polynom::new_random_of_degree_with_constant_term(threshold - 1, Fp521::zero(), rng);
+            Ok(add_to_prime_field_shares(shares, &delta))
+        }
+        ShamirBackend::Gf256 => {
+            let mut refreshed = shares.to_vec();
+            for i in 0..secret_len {
+                let delta = polynom::Polynom::new_random_of_degree_with_constant_term(threshold - 1, Gf256::zero(), rng);
+                for share in refreshed.iter_mut() {
+                    share.y[i] ^= delta.calculate_y(Gf256::from_index(share.x)).0;
+                }
+            }
+            Ok(refreshed)
+        }
     }
 }
 
-// Reconstructs the constant term of a polynomial from shares using modular arithmetic.
-pub fn get_polynom_constant_value(points: &[(BigUint, BigUint)]) -> BigUint {
-    let mut secret = BigUint::ZERO;
-    for (i, (xi, yi)) in points.iter().enumerate() {
-        let mut numerator = BigUint::from_usize(1).unwrap();
-        let mut denominator = BigUint::from_usize(1).unwrap();
+/// Adds `delta(x)` to each prime field share, the shares must have passed `check_shares`
+pub(crate) fn add_to_prime_field_shares(shares: &[Share], delta: &polynom::Polynom<Fp521>) -> Vec<Share> {
+    shares
+        .iter()
+        .map(|share| {
+            let y = Fp521::from_bytes(&share.y).unwrap();
+            let y = y.add(&delta.calculate_y(Fp521::from_index(share.x)));
+            Share { backend: share.backend, x: share.x, y: y.to_bytes() }
+        })
+        .collect()
+}
 
-        // Compute Lagrange coefficients: ∏ (xj / (xj - xi)) for j ≠ i
+/// Re-splits the secret held by at least `threshold` shares with a new threshold and share count.
+/// The new shares are points of fresh polynom(s) and do not combine with the old ones.
+pub fn reshare(
+    shares: &[Share],
+    threshold: usize,
+    secret_len: usize,
+    new_threshold: usize,
+    new_share_count: usize,
+) -> Result<Vec<Share>, String> {
+    let recovery = recover_secret_robust(shares, threshold, secret_len)?;
+    split_secret(shares[0].backend, &recovery.secret, new_threshold, new_share_count)
+}
+
+/// Computes shares at the fresh x coordinates `new_xs` from at least `threshold` existing shares,
+/// by evaluating the polynom(s) they define there. The existing shares stay valid.
+pub fn extend_shares(shares: &[Share], threshold: usize, secret_len: usize, new_xs: &[u8]) -> Result<Vec<Share>, String> {
+    let first = check_consistent_shares(shares, threshold, secret_len)?;
+    let basis = &shares[..threshold];
+    for (i, x) in new_xs.iter().enumerate() {
+        if *x == 0 {
+            return Err("Share index 0 would reveal the secret".to_string());
+        }
+        if shares.iter().any(|share| share.x == *x) || new_xs[..i].contains(x) {
+            return Err(format!("Share {} already exists", x));
+        }
+    }
+
+    // The polynom(s) going through the basis, evaluated at each new x coordinate
+    let new_ys: Vec<Vec<u8>> = match first.backend {
+        ShamirBackend::PrimeField => {
+            let points: Vec<(Fp521, Fp521)> = basis
+                .iter()
+                .map(|share| (Fp521::from_index(share.x), Fp521::from_bytes(&share.y).unwrap()))
+                .collect();
+            let polynom = polynom::Polynom::interpolate(&points)?;
+            new_xs.iter().map(|x| polynom.calculate_y(Fp521::from_index(*x)).to_bytes()).collect()
+        }
+        ShamirBackend::Gf256 => {
+            let mut new_ys = vec![Vec::with_capacity(secret_len); new_xs.len()];
+            for i in 0..secret_len {
+                let points: Vec<(Gf256, Gf256)> = basis
+                    .iter()
+                    .map(|share| (Gf256::from_index(share.x), Gf256(share.y[i])))
+                    .collect();
+                let polynom = polynom::Polynom::interpolate(&points)?;
+                for (y, x) in new_ys.iter_mut().zip(new_xs) {
+                    y.push(polynom.calculate_y(Gf256::from_index(*x)).0);
+                }
+            }
+            new_ys
+        }
+    };
+    Ok(new_xs
+        .iter()
+        .zip(new_ys)
+        .map(|(x, y)| Share { backend: first.backend, x: *x, y })
+        .collect())
+}
+
+/// For each share, whether it lies on the polynom(s) going through the `basis` shares.
+/// All shares must have passed `check_shares`.
+fn lie_on_same_polynoms(basis: &[Share], shares: &[Share]) -> Vec<bool> {
+    match basis[0].backend {
+        ShamirBackend::PrimeField => {
+            let points: Vec<(Fp521, Fp521)> = basis
+                .iter()
+                .map(|share| (Fp521::from_index(share.x), Fp521::from_bytes(&share.y).unwrap()))
+                .collect();
+            let polynom = polynom::Polynom::interpolate(&points).unwrap();
+            shares
+                .iter()
+                .map(|share| Fp521::from_bytes(&share.y) == Some(polynom.calculate_y(Fp521::from_index(share.x))))
+                .collect()
+        }
+        ShamirBackend::Gf256 => {
+            let mut agrees = vec![true; shares.len()];
+            for i in 0..basis[0].y.len() {
+                let points: Vec<(Gf256, Gf256)> = basis
+                    .iter()
+                    .map(|b| (Gf256::from_index(b.x), Gf256(b.y[i])))
+                    .collect();
+                let polynom = polynom::Polynom::interpolate(&points).unwrap();
+                for (agree, share) in agrees.iter_mut().zip(shares) {
+                    *agree &= polynom.calculate_y(Gf256::from_index(share.x)) == Gf256(share.y[i]);
+                }
+            }
+            agrees
+        }
+    }
+}
+
+// Reconstructs the constant term of a polynomial from shares using the arithmetic of the field F.
+// Fails if two points share an x coordinate.
+pub fn get_polynom_constant_value<F: Field>(points: &[(F, F)]) -> Result<F, String> {
+    interpolate_at(points, &F::zero())
+}
+
+/// Evaluates at `x` the lowest degree polynom going through all the points (Lagrange interpolation).
+/// Fails if two points share an x coordinate.
+pub fn interpolate_at<F: Field>(points: &[(F, F)], x: &F) -> Result<F, String> {
+    let mut numerators = Vec::with_capacity(points.len());
+    let mut denominators = Vec::with_capacity(points.len());
+    for (i, (xi, _)) in points.iter().enumerate() {
+        let mut numerator = F::one();
+        let mut denominator = F::one();
+
+        // Compute Lagrange coefficients: ∏ ((x - xj) / (xi - xj)) for j ≠ i
         for (j, (xj, _)) in points.iter().enumerate() {
             if i != j {
-                // Numerator: ∏ (xj)
-                numerator = (numerator * xj) % &*MODULUS_128;
+                // Numerator: ∏ (x - xj)
+                numerator = numerator.mul(&x.sub(xj));
 
-                // Denominator: ∏ (xj - xi)
-                let diff = (xj + (&*MODULUS_128 - xi)) % &*MODULUS_128;
-                denominator = (denominator * diff) % &*MODULUS_128;
+                // Denominator: ∏ (xi - xj)
+                denominator = denominator.mul(&xi.sub(xj));
             }
         }
+        numerators.push(numerator);
+        denominators.push(denominator);
+    }
+    let denominators_inv = invert_all(&denominators).ok_or_else(|| "Points must have distinct x coordinates".to_string())?;
+
+    let mut y = F::zero();
+    for (((_, yi), numerator), denominator_inv) in points.iter().zip(&numerators).zip(&denominators_inv) {
+        // Lagrange coefficient: yi * (numerator / denominator)
+        let lagrange_coef = yi.mul(numerator).mul(denominator_inv);
+
+        // Accumulate: y += lagrange_coef
+        y = y.add(&lagrange_coef);
+    }
+    Ok(y)
+}
 
-        // Lagrange coefficient: yi * (numerator / denominator) mod modulus
-        let denominator_inv = &denominator.modinv(&MODULUS_128).unwrap();
-        let lagrange_coef = (yi * numerator * denominator_inv) % &*MODULUS_128;
+/// Inverts all the values with a single field inversion (Montgomery's trick), None if any is zero
+fn invert_all<F: Field>(values: &[F]) -> Option<Vec<F>> {
+    // prefixes[i] = v0 · v1 · ... · v(i-1)
+    let mut prefixes = Vec::with_capacity(values.len());
+    let mut product = F::one();
+    for value in values {
+        prefixes.push(product.clone());
+        product = product.mul(value);
+    }
+    // Walking back, `inverse` is (v0 · ... · vi)⁻¹ before handling vi
+    let mut inverse = product.inv()?;
+    let mut inverses = vec![F::zero(); values.len()];
+    for i in (0..values.len()).rev() {
+        inverses[i] = inverse.mul(&prefixes[i]);
+        inverse = inverse.mul(&values[i]);
+    }
+    Some(inverses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::MODULUS_521;
+    use proptest::prelude::*;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const BACKENDS: [ShamirBackend; 2] = [ShamirBackend::PrimeField, ShamirBackend::Gf256];
+
+    fn prime_share(x: u8, y: BigUint) -> Share {
+        Share { backend: ShamirBackend::PrimeField, x, y: Fp521::from_biguint(y).unwrap().to_bytes() }
+    }
+
+    fn gf256_share(x: u8, y: &[u8]) -> Share {
+        Share { backend: ShamirBackend::Gf256, x, y: y.to_vec() }
+    }
 
-        // Accumulate the secret: secret += lagrange_coef
-        secret = (secret + lagrange_coef) % &*MODULUS_128;
+    // Known answers computed independently for f(x) = s + a·x + b·x² over GF(2⁸) with the AES polynom,
+    // s = [53 00 ff 10], a = [ca 02 80 01], b = [01 03 7f 01]
+    #[test]
+    fn gf256_known_answer() {
+        let shares = [
+            gf256_share(1, &[0x98, 0x01, 0x00, 0x10]),
+            gf256_share(2, &[0xd8, 0x08, 0x03, 0x16]),
+            gf256_share(3, &[0x13, 0x09, 0xfc, 0x16]),
+            gf256_share(4, &[0x46, 0x38, 0x78, 0x04]),
+        ];
+        let secret = vec![0x53, 0x00, 0xff, 0x10];
+        assert_eq!(recover_secret(&shares[..3], 4).unwrap(), secret);
+        assert_eq!(recover_secret(&shares[1..], 4).unwrap(), secret);
+        assert_eq!(recover_secret(&[shares[3].clone(), shares[0].clone(), shares[2].clone()], 4).unwrap(), secret);
+    }
+
+    // f(x) = 42 + 7x + 3x² over GF(2⁵²¹ - 1)
+    #[test]
+    fn prime_field_known_answer() {
+        let shares = [
+            prime_share(1, BigUint::from(52u32)),
+            prime_share(2, BigUint::from(68u32)),
+            prime_share(3, BigUint::from(90u32)),
+        ];
+        assert_eq!(recover_secret(&shares, 1).unwrap(), vec![42]);
+        assert_eq!(recover_secret(&shares, 2).unwrap(), vec![0, 42]);
+    }
+
+    // f(x) = 1 - x, so that f(2) wraps around the modulus
+    #[test]
+    fn prime_field_known_answer_wraps_around_modulus() {
+        let shares = [
+            prime_share(1, BigUint::from(0u32)),
+            prime_share(2, &*MODULUS_521 - BigUint::from(1u32)),
+        ];
+        assert_eq!(recover_secret(&shares, 1).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn seeded_split_is_deterministic() {
+        for backend in BACKENDS {
+            let secret = [7u8; 32];
+            let split = |seed| split_secret_with_rng(backend, &secret, 3, 5, &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+            assert_eq!(split(1), split(1));
+            assert_ne!(split(1), split(2));
+
+            let shares = split(1);
+            let refresh = |seed| refresh_shares_with_rng(&shares, 3, secret.len(), &mut ChaCha20Rng::seed_from_u64(seed)).unwrap();
+            assert_eq!(refresh(1), refresh(1));
+            assert_ne!(refresh(1), refresh(2));
+            assert_eq!(recover_secret(&refresh(1)[..3], secret.len()).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn threshold_one_shares_hold_the_secret() {
+        let secret = [0xde, 0xad, 0xbe, 0xef];
+        for backend in BACKENDS {
+            let shares = split_secret(backend, &secret, 1, 4).unwrap();
+            for share in &shares {
+                assert_eq!(recover_secret(std::slice::from_ref(share), secret.len()).unwrap(), secret);
+            }
+        }
+        for share in split_secret(ShamirBackend::Gf256, &secret, 1, 4).unwrap() {
+            assert_eq!(share.y, secret);
+        }
+    }
+
+    #[test]
+    fn threshold_equal_to_share_count_needs_every_share() {
+        let secret = [0x5a; 32];
+        for backend in BACKENDS {
+            let mut rng = ChaCha20Rng::seed_from_u64(3);
+            let shares = split_secret_with_rng(backend, &secret, 5, 5, &mut rng).unwrap();
+            assert_eq!(recover_secret(&shares, secret.len()).unwrap(), secret);
+            let recovered = recover_secret(&shares[..4], secret.len());
+            assert_ne!(recovered, Ok(secret.to_vec()));
+        }
+    }
+
+    #[test]
+    fn zero_secret_round_trips() {
+        for backend in BACKENDS {
+            for secret in [vec![0u8], vec![0u8; 32]] {
+                let shares = split_secret(backend, &secret, 2, 3).unwrap();
+                assert_eq!(recover_secret(&shares[1..], secret.len()).unwrap(), secret);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_x_coordinates_are_rejected() {
+        for backend in BACKENDS {
+            let shares = split_secret(backend, &[1, 2, 3], 2, 3).unwrap();
+            let duplicated = [shares[0].clone(), shares[0].clone()];
+            assert_eq!(recover_secret(&duplicated, 3), Err("Share 1 was provided twice".to_string()));
+            assert!(recover_secret_robust(&duplicated, 2, 3).is_err());
+        }
+        let point = (Gf256(1), Gf256(2));
+        assert_eq!(get_polynom_constant_value(&[point, point]), Err("Points must have distinct x coordinates".to_string()));
+        assert_eq!(interpolate_at(&[point, point], &Gf256(3)), Err("Points must have distinct x coordinates".to_string()));
+    }
+
+    #[test]
+    fn invalid_split_parameters_are_rejected() {
+        for backend in BACKENDS {
+            assert!(split_secret(backend, &[1], 0, 3).is_err());
+            assert!(split_secret(backend, &[1], 4, 3).is_err());
+            assert!(split_secret(backend, &[1], 2, 256).is_err());
+        }
+        assert!(split_secret(ShamirBackend::PrimeField, &[1; Fp521::BYTES], 2, 3).is_err());
+        assert!(split_secret(ShamirBackend::Gf256, &[1; Fp521::BYTES], 2, 3).is_ok());
+        assert_eq!(recover_secret(&[], 1), Err("No share provided".to_string()));
+    }
+
+    #[test]
+    fn prime_field_secret_len_is_bounded() {
+        let shares = split_secret(ShamirBackend::PrimeField, &[1, 2, 3], 2, 2).unwrap();
+        assert_eq!(recover_secret(&shares, Fp521::BYTES).unwrap().len(), Fp521::BYTES);
+        assert_eq!(
+            recover_secret(&shares, Fp521::BYTES + 1),
+            Err(format!("A prime field secret holds at most {} bytes, not {}", Fp521::BYTES, Fp521::BYTES + 1))
+        );
+        assert!(recover_secret_robust(&shares, 2, Fp521::BYTES + 1).is_err());
+    }
+
+    #[test]
+    fn mixed_backends_are_rejected() {
+        let prime = split_secret(ShamirBackend::PrimeField, &[9], 2, 2).unwrap();
+        let gf256 = split_secret(ShamirBackend::Gf256, &[9], 2, 2).unwrap();
+        assert!(recover_secret(&[prime[0].clone(), gf256[1].clone()], 1).is_err());
+    }
+
+    #[test]
+    fn robust_recovery_names_the_corrupted_share() {
+        let secret = [0x42; 16];
+        for backend in BACKENDS {
+            let mut shares = split_secret(backend, &secret, 2, 5).unwrap();
+            shares[3].y[0] ^= 1;
+            let recovery = recover_secret_robust(&shares, 2, secret.len()).unwrap();
+            assert_eq!(recovery.secret, secret);
+            assert_eq!(recovery.inconsistent, vec![4]);
+        }
+    }
+
+    fn backend() -> impl Strategy<Value = ShamirBackend> {
+        prop::sample::select(BACKENDS.to_vec())
+    }
+
+    proptest! {
+        #[test]
+        fn any_threshold_subset_recovers_the_secret(
+            backend in backend(),
+            secret in prop::collection::vec(any::<u8>(), 1..Fp521::BYTES),
+            threshold in 1usize..=6,
+            extra in 0usize..=6,
+            seed in any::<u64>(),
+        ) {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let mut shares = split_secret_with_rng(backend, &secret, threshold, threshold + extra, &mut rng).unwrap();
+            prop_assert_eq!(shares.len(), threshold + extra);
+            shares.shuffle(&mut rng);
+            prop_assert_eq!(recover_secret(&shares[..threshold], secret.len()).unwrap(), secret.clone());
+            prop_assert_eq!(recover_secret_robust(&shares, threshold, secret.len()).unwrap().secret, secret);
+        }
+
+        #[test]
+        fn refreshed_and_extended_shares_keep_the_secret(
+            backend in backend(),
+            secret in prop::collection::vec(any::<u8>(), 1..=32),
+            threshold in 1usize..=5,
+            extra in 0usize..=3,
+            seed in any::<u64>(),
+        ) {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let shares = split_secret_with_rng(backend, &secret, threshold, threshold + extra, &mut rng).unwrap();
+            let refreshed = refresh_shares(&shares, threshold, secret.len()).unwrap();
+            prop_assert_eq!(recover_secret(&refreshed[extra..], secret.len()).unwrap(), secret.clone());
+
+            // The new shares combine with the existing ones
+            let mut mixed = extend_shares(&shares, threshold, secret.len(), &[200, 201]).unwrap();
+            mixed.extend_from_slice(&shares);
+            prop_assert_eq!(recover_secret(&mixed[..threshold], secret.len()).unwrap(), secret);
+        }
     }
-    secret
 }
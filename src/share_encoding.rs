@@ -0,0 +1,483 @@
+use bip39::Language;
+use data_encoding::BASE32_NOPAD;
+use sha2::{Digest, Sha256};
+use strum_macros::EnumIter;
+use crate::feldman::{refresh_shares_verifiable, FeldmanCommitments};
+use crate::shamir_secret_sharing::{extend_shares, recover_secret_robust, refresh_shares, Recovery, ShamirBackend, Share};
+
+/// Current version of the share encoding, v2 added labels
+pub const SHARE_ENCODING_VERSION: u8 = 2;
+pub const FINGERPRINT_BYTES: usize = 4;
+/// Labels are written down with the share, so they are kept short
+pub const MAX_LABEL_BYTES: usize = 32;
+const CHECKSUM_BYTES: usize = 4;
+/// version, backend, x, threshold, share count, secret length (2 bytes) and fingerprint
+const HEADER_BYTES: usize = 7 + FINGERPRINT_BYTES;
+
+/// Textual forms in which an encoded share can be written down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumIter)]
+pub enum ShareFormat {
+    #[default]
+    Words,
+    Hex,
+    Base32,
+}
+
+impl ShareFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShareFormat::Words => "BIP39 words",
+            ShareFormat::Hex => "Hex",
+            ShareFormat::Base32 => "Base32",
+        }
+    }
+}
+
+/// A share along with the metadata needed to check it before recovery.
+/// Binary layout (v2):
+/// [version | backend | x | threshold | share count | secret length (u16 BE) | fingerprint (4) | label length | label | y | checksum (4)]
+/// where the checksum is the first 4 bytes of the SHA-256 of everything before it.
+/// v1 shares have no label length nor label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedShare {
+    pub share: Share,
+    pub threshold: u8,
+    pub share_count: u8,
+    pub secret_len: u16,
+    pub fingerprint: [u8; FINGERPRINT_BYTES],
+    /// Free text naming the custodian of the share
+    pub label: Option<String>,
+}
+
+/// Short fingerprint identifying a protected key, without revealing it
+pub fn key_fingerprint(secret: &[u8]) -> [u8; FINGERPRINT_BYTES] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"bunkeys-key-fingerprint");
+    hasher.update(secret);
+    let digest = hasher.finalize();
+    let mut fingerprint = [0u8; FINGERPRINT_BYTES];
+    fingerprint.copy_from_slice(&digest[..FINGERPRINT_BYTES]);
+    fingerprint
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_BYTES] {
+    let digest = Sha256::digest(data);
+    let mut checksum = [0u8; CHECKSUM_BYTES];
+    checksum.copy_from_slice(&digest[..CHECKSUM_BYTES]);
+    checksum
+}
+
+impl EncodedShare {
+    /// Wraps freshly split shares of `secret` with their metadata
+    pub fn wrap_all(shares: Vec<Share>, threshold: usize, secret: &[u8]) -> Vec<EncodedShare> {
+        let share_count = shares.len() as u8;
+        let fingerprint = key_fingerprint(secret);
+        shares
+            .into_iter()
+            .map(|share| EncodedShare {
+                share,
+                threshold: threshold as u8,
+                share_count,
+                secret_len: secret.len() as u16,
+                fingerprint,
+                label: None,
+            })
+            .collect()
+    }
+
+    /// Issues new shares of the same split at the given indices, from at least `threshold` existing shares.
+    /// The new shares hold the same metadata, share count included, so that they combine with the existing ones.
+    pub fn extend(shares: &[EncodedShare], new_shares: &[(u8, Option<String>)]) -> Result<Vec<EncodedShare>, String> {
+        let Some(first) = shares.first() else {
+            return Err("No share provided".to_string());
+        };
+        for share in &shares[1..] {
+            first.check_same_split(share)?;
+        }
+        for (_, label) in new_shares {
+            check_label(label)?;
+        }
+        // Make sure the shares really hold the key before deriving new ones from them
+        recover_encoded_secret(shares)?;
+
+        let raw_shares: Vec<Share> = shares.iter().map(|s| s.share.clone()).collect();
+        let new_xs: Vec<u8> = new_shares.iter().map(|(x, _)| *x).collect();
+        let extended = extend_shares(&raw_shares, first.threshold as usize, first.secret_len as usize, &new_xs)?;
+        Ok(extended
+            .into_iter()
+            .zip(new_shares)
+            .map(|(share, (_, label))| EncodedShare {
+                share,
+                label: label.clone(),
+                ..first.clone()
+            })
+            .collect())
+    }
+
+    /// Refreshes shares of the same split, see `refresh_shares`. Indices and labels are kept.
+    /// Commitments published for the split are refreshed along, the old ones no longer match.
+    pub fn refresh(
+        shares: &[EncodedShare],
+        commitments: Option<&FeldmanCommitments>,
+    ) -> Result<(Vec<EncodedShare>, Option<FeldmanCommitments>), String> {
+        let Some(first) = shares.first() else {
+            return Err("No share provided".to_string());
+        };
+        for share in &shares[1..] {
+            first.check_same_split(share)?;
+        }
+        // Make sure the shares really hold the key before refreshing them
+        recover_encoded_secret(shares)?;
+
+        let raw_shares: Vec<Share> = shares.iter().map(|s| s.share.clone()).collect();
+        let (refreshed, commitments) = match commitments.filter(|commitments| commitments.covers(first)) {
+            Some(commitments) => {
+                let (refreshed, commitments) = refresh_shares_verifiable(&raw_shares, commitments)?;
+                (refreshed, Some(commitments))
+            }
+            None => (
+                refresh_shares(&raw_shares, first.threshold as usize, first.secret_len as usize)?,
+                None,
+            ),
+        };
+        let refreshed = refreshed
+            .into_iter()
+            .zip(shares)
+            .map(|(share, old)| EncodedShare { share, ..old.clone() })
+            .collect();
+        Ok((refreshed, commitments))
+    }
+
+    /// Share index, followed by its label if any
+    pub fn name(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", self.share.x, label),
+            None => self.share.x.to_string(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + self.share.y.len() + CHECKSUM_BYTES);
+        bytes.push(SHARE_ENCODING_VERSION);
+        bytes.push(match self.share.backend {
+            ShamirBackend::PrimeField => 0,
+            ShamirBackend::Gf256 => 1,
+        });
+        bytes.push(self.share.x);
+        bytes.push(self.threshold);
+        bytes.push(self.share_count);
+        bytes.extend_from_slice(&self.secret_len.to_be_bytes());
+        bytes.extend_from_slice(&self.fingerprint);
+        let label = self.label.as_deref().unwrap_or_default().as_bytes();
+        bytes.push(label.len() as u8);
+        bytes.extend_from_slice(label);
+        bytes.extend_from_slice(&self.share.y);
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_BYTES + CHECKSUM_BYTES {
+            return Err("Share is too short".to_string());
+        }
+        let (data, expected_checksum) = bytes.split_at(bytes.len() - CHECKSUM_BYTES);
+        if checksum(data) != expected_checksum {
+            return Err("Invalid share checksum, the share is corrupted or mistyped".to_string());
+        }
+        let version = data[0];
+        if version == 0 || version > SHARE_ENCODING_VERSION {
+            return Err(format!("Unsupported share format version {}", version));
+        }
+        let backend = match data[1] {
+            0 => ShamirBackend::PrimeField,
+            1 => ShamirBackend::Gf256,
+            b => return Err(format!("Unknown share backend {}", b)),
+        };
+        let (x, threshold, share_count) = (data[2], data[3], data[4]);
+        if x == 0 || threshold == 0 || threshold > share_count {
+            return Err("Invalid share index or threshold".to_string());
+        }
+        let secret_len = u16::from_be_bytes([data[5], data[6]]);
+        let mut fingerprint = [0u8; FINGERPRINT_BYTES];
+        fingerprint.copy_from_slice(&data[7..HEADER_BYTES]);
+
+        let (label, y) = if version == 1 {
+            (None, &data[HEADER_BYTES..])
+        } else {
+            let label_len = *data.get(HEADER_BYTES).ok_or("Share is too short")? as usize;
+            let y_start = HEADER_BYTES + 1 + label_len;
+            if data.len() < y_start {
+                return Err("Share is too short".to_string());
+            }
+            let label = String::from_utf8(data[HEADER_BYTES + 1..y_start].to_vec())
+                .map_err(|_| "Invalid share label".to_string())?;
+            (Some(label).filter(|label| !label.is_empty()), &data[y_start..])
+        };
+
+        Ok(EncodedShare {
+            share: Share { backend, x, y: y.to_vec() },
+            threshold,
+            share_count,
+            secret_len,
+            fingerprint,
+            label,
+        })
+    }
+
+    pub fn encode(&self, format: ShareFormat) -> String {
+        let bytes = self.to_bytes();
+        match format {
+            ShareFormat::Words => bytes_to_words(&bytes),
+            ShareFormat::Hex => hex::encode(bytes),
+            ShareFormat::Base32 => BASE32_NOPAD.encode(&bytes),
+        }
+    }
+
+    /// Parses a share written in any of the `ShareFormat`s
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if text.contains(' ') {
+            return Self::from_bytes(&words_to_bytes(text)?);
+        }
+        // Hex and base32 alphabets overlap, the checksum tells which one was meant
+        let from_hex = hex::decode(text).map_err(|e| e.to_string()).and_then(|b| Self::from_bytes(&b));
+        from_hex.or_else(|hex_error| {
+            match BASE32_NOPAD.decode(text.to_uppercase().as_bytes()) {
+                Ok(bytes) => Self::from_bytes(&bytes),
+                Err(_) => Err(format!("Invalid share, neither words, hex nor base32 ({})", hex_error)),
+            }
+        })
+    }
+
+    /// Checks that `other` belongs to the same split as this share
+    pub fn check_same_split(&self, other: &EncodedShare) -> Result<(), String> {
+        if self.fingerprint != other.fingerprint {
+            return Err(format!(
+                "Share belongs to another key (fingerprint {} instead of {})",
+                hex::encode(other.fingerprint),
+                hex::encode(self.fingerprint)
+            ));
+        }
+        if self.threshold != other.threshold
+            || self.share_count != other.share_count
+            || self.secret_len != other.secret_len
+            || self.share.backend != other.share.backend
+            || self.share.y.len() != other.share.y.len()
+        {
+            return Err("Share does not belong to the same split (threshold, count or backend differ)".to_string());
+        }
+        if self.share.x == other.share.x {
+            return Err(format!("Share {} was already entered", other.share.x));
+        }
+        Ok(())
+    }
+}
+
+fn check_label(label: &Option<String>) -> Result<(), String> {
+    match label {
+        Some(label) if label.len() > MAX_LABEL_BYTES => {
+            Err(format!("Label '{}' is longer than {} bytes", label, MAX_LABEL_BYTES))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Recovers the secret once `threshold` shares of the same split are collected,
+/// and checks it against the fingerprint carried by the shares.
+/// Extra shares let the recovery tolerate and name wrong ones, see `recover_secret_robust`.
+pub fn recover_encoded_secret(shares: &[EncodedShare]) -> Result<Recovery, String> {
+    let Some(first) = shares.first() else {
+        return Err("No share provided".to_string());
+    };
+    for share in &shares[1..] {
+        first.check_same_split(share)?;
+    }
+    let raw_shares: Vec<Share> = shares.iter().map(|s| s.share.clone()).collect();
+    let recovery = recover_secret_robust(&raw_shares, first.threshold as usize, first.secret_len as usize)?;
+    if key_fingerprint(&recovery.secret) != first.fingerprint {
+        return Err("Recovered key does not match the share fingerprint, a share is corrupted".to_string());
+    }
+    Ok(recovery)
+}
+
+/// Writes bytes as BIP39 English words, 11 bits per word, left-padded with zero bits
+fn bytes_to_words(bytes: &[u8]) -> String {
+    let word_list = Language::English.word_list();
+    let word_count = (bytes.len() * 8).div_ceil(11);
+    let mut words = Vec::with_capacity(word_count);
+    let (mut acc, mut acc_bits) = (0u32, word_count * 11 - bytes.len() * 8);
+    for byte in bytes {
+        acc = (acc << 8) | *byte as u32;
+        acc_bits += 8;
+        while acc_bits >= 11 {
+            acc_bits -= 11;
+            words.push(word_list[((acc >> acc_bits) & 0x7ff) as usize]);
+        }
+        acc &= (1 << acc_bits) - 1;
+    }
+    words.join(" ")
+}
+
+fn words_to_bytes(text: &str) -> Result<Vec<u8>, String> {
+    let indices = text
+        .split_whitespace()
+        .map(|word| {
+            Language::English
+                .find_word(&word.to_lowercase())
+                .ok_or_else(|| format!("'{}' is not a BIP39 word", word))
+        })
+        .collect::<Result<Vec<u16>, String>>()?;
+
+    // Skip the left padding so that the bytes end on the last bit of the last word
+    let mut padding_left = (indices.len() * 11) % 8;
+    let mut bytes = Vec::with_capacity(indices.len() * 11 / 8);
+    let (mut acc, mut acc_bits) = (0u32, 0usize);
+    for index in indices {
+        acc = (acc << 11) | index as u32;
+        acc_bits += 11;
+        if padding_left > 0 {
+            acc_bits -= padding_left;
+            acc &= (1 << acc_bits) - 1;
+            padding_left = 0;
+        }
+        while acc_bits >= 8 {
+            acc_bits -= 8;
+            bytes.push((acc >> acc_bits) as u8);
+        }
+        acc &= (1 << acc_bits) - 1;
+    }
+    // A padding of 8 bits or more leaves a leading zero byte, the version byte never is zero
+    if bytes.first() == Some(&0) {
+        bytes.remove(0);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+    use crate::shamir_secret_sharing::split_secret;
+
+    const SECRET: [u8; 32] = [0x42; 32];
+
+    fn encoded_shares(backend: ShamirBackend) -> Vec<EncodedShare> {
+        let shares = split_secret(backend, &SECRET, 2, 3).unwrap();
+        let mut encoded = EncodedShare::wrap_all(shares, 2, &SECRET);
+        encoded[0].label = Some("alice".to_string());
+        encoded
+    }
+
+    /// Bytes of `share` with the version byte replaced and a matching checksum
+    fn with_version(share: &EncodedShare, version: u8) -> Vec<u8> {
+        let bytes = share.to_bytes();
+        let mut data = bytes[..bytes.len() - CHECKSUM_BYTES].to_vec();
+        data[0] = version;
+        let checksum = checksum(&data);
+        data.extend_from_slice(&checksum);
+        data
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        for backend in [ShamirBackend::PrimeField, ShamirBackend::Gf256] {
+            for share in encoded_shares(backend) {
+                for format in ShareFormat::iter() {
+                    assert_eq!(EncodedShare::decode(&share.encode(format)).unwrap(), share, "{}", format.name());
+                }
+                assert_eq!(EncodedShare::from_bytes(&share.to_bytes()).unwrap(), share);
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_label_and_metadata() {
+        let shares = encoded_shares(ShamirBackend::Gf256);
+        let decoded = EncodedShare::decode(&shares[0].encode(ShareFormat::Words)).unwrap();
+        assert_eq!(decoded.label.as_deref(), Some("alice"));
+        assert_eq!(decoded.name(), "1 (alice)");
+        assert_eq!((decoded.threshold, decoded.share_count, decoded.secret_len), (2, 3, 32));
+        assert_eq!(decoded.fingerprint, key_fingerprint(&SECRET));
+        assert_eq!(EncodedShare::decode(&shares[1].encode(ShareFormat::Hex)).unwrap().label, None);
+    }
+
+    #[test]
+    fn decodes_hex_then_base32_in_any_case() {
+        let share = &encoded_shares(ShamirBackend::Gf256)[1];
+        let hex = share.encode(ShareFormat::Hex);
+        let base32 = share.encode(ShareFormat::Base32);
+        for text in [hex.clone(), hex.to_uppercase(), base32.clone(), base32.to_lowercase()] {
+            assert_eq!(EncodedShare::decode(&format!("  {}\n", text)).unwrap(), *share);
+        }
+        assert!(EncodedShare::decode("not a share").is_err());
+        assert!(EncodedShare::decode("!!!!").unwrap_err().starts_with("Invalid share, neither words, hex nor base32"));
+    }
+
+    #[test]
+    fn reads_v1_shares_without_label() {
+        let share = &encoded_shares(ShamirBackend::Gf256)[1];
+        let v2 = share.to_bytes();
+        let mut v1 = v2[..HEADER_BYTES].to_vec();
+        v1[0] = 1;
+        v1.extend_from_slice(&share.share.y);
+        let checksum = checksum(&v1);
+        v1.extend_from_slice(&checksum);
+        assert_eq!(EncodedShare::from_bytes(&v1).unwrap(), *share);
+    }
+
+    #[test]
+    fn rejects_flipped_bits() {
+        let share = &encoded_shares(ShamirBackend::PrimeField)[0];
+        let bytes = share.to_bytes();
+        for i in [0, HEADER_BYTES, bytes.len() - CHECKSUM_BYTES - 1, bytes.len() - 1] {
+            let mut flipped = bytes.clone();
+            flipped[i] ^= 1;
+            assert_eq!(
+                EncodedShare::from_bytes(&flipped),
+                Err("Invalid share checksum, the share is corrupted or mistyped".to_string())
+            );
+            assert!(EncodedShare::decode(&bytes_to_words(&flipped)).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let share = &encoded_shares(ShamirBackend::Gf256)[0];
+        for version in [0, SHARE_ENCODING_VERSION + 1] {
+            assert_eq!(
+                EncodedShare::from_bytes(&with_version(share, version)),
+                Err(format!("Unsupported share format version {}", version))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_shares() {
+        let share = &encoded_shares(ShamirBackend::Gf256)[0];
+        let bytes = share.to_bytes();
+        assert_eq!(EncodedShare::from_bytes(&bytes[..HEADER_BYTES]), Err("Share is too short".to_string()));
+        assert!(EncodedShare::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let words = share.encode(ShareFormat::Words);
+        let truncated = &words[..words.rfind(' ').unwrap()];
+        assert!(EncodedShare::decode(truncated).is_err());
+        let hex = share.encode(ShareFormat::Hex);
+        assert!(EncodedShare::decode(&hex[..hex.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn recovers_and_checks_the_fingerprint() {
+        for backend in [ShamirBackend::PrimeField, ShamirBackend::Gf256] {
+            let shares = encoded_shares(backend);
+            assert_eq!(recover_encoded_secret(&shares[1..]).unwrap().secret, SECRET);
+
+            let mut other_key = shares.clone();
+            other_key[1].fingerprint = key_fingerprint(b"another key");
+            assert!(recover_encoded_secret(&other_key).unwrap_err().starts_with("Share belongs to another key"));
+            let mut same_index = shares.clone();
+            same_index[1].share.x = same_index[0].share.x;
+            assert!(recover_encoded_secret(&same_index[..2]).is_err());
+        }
+    }
+}
@@ -0,0 +1,650 @@
+//! SLIP-0039 "Shamir's Secret-Sharing for Mnemonic Codes", as specified in
+//! https://github.com/satoshilabs/slips/blob/master/slip-0039.md
+//!
+//! Shares are interoperable with Trezor wallets and the reference `shamir-mnemonic` implementation.
+
+mod wordlist;
+
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use crate::field::Gf256;
+use crate::shamir_secret_sharing::interpolate_at;
+use wordlist::WORDLIST;
+
+const RADIX_BITS: usize = 10;
+const ID_LENGTH_BITS: usize = 15;
+const ITERATION_EXP_LENGTH_BITS: usize = 4;
+const CHECKSUM_LENGTH_WORDS: usize = 3;
+/// Identifier/exponent (2 words), group and member parameters (2 words) and checksum (3 words)
+const METADATA_LENGTH_WORDS: usize = 7;
+const MIN_STRENGTH_BYTES: usize = 16;
+const MIN_MNEMONIC_LENGTH_WORDS: usize = METADATA_LENGTH_WORDS + (MIN_STRENGTH_BYTES * 8).div_ceil(RADIX_BITS);
+
+const CUSTOMIZATION_STRING_ORIG: &[u8] = b"shamir";
+const CUSTOMIZATION_STRING_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+const BASE_ITERATION_COUNT: u32 = 10000;
+const ROUND_COUNT: u8 = 4;
+
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const DIGEST_LENGTH_BYTES: usize = 4;
+
+/// Iteration exponent used for new mnemonics, as in the reference implementation
+pub const DEFAULT_ITERATION_EXPONENT: u8 = 1;
+
+/// A group of the two-level scheme: `member_threshold` of `member_count` mnemonics recover the group
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slip39Group {
+    pub member_threshold: u8,
+    pub member_count: u8,
+}
+
+/// A single decoded SLIP-0039 mnemonic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slip39Share {
+    pub identifier: u16,
+    pub extendable: bool,
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Vec<u8>,
+}
+
+impl Slip39Share {
+    pub fn to_mnemonic(&self) -> String {
+        let mut indices = Vec::new();
+
+        let id_exp = ((self.identifier as u64) << (ITERATION_EXP_LENGTH_BITS + 1))
+            | ((self.extendable as u64) << ITERATION_EXP_LENGTH_BITS)
+            | self.iteration_exponent as u64;
+        indices.extend(int_to_indices(id_exp, 2));
+
+        let mut params = self.group_index as u64;
+        params = (params << 4) | (self.group_threshold - 1) as u64;
+        params = (params << 4) | (self.group_count - 1) as u64;
+        params = (params << 4) | self.member_index as u64;
+        params = (params << 4) | (self.member_threshold - 1) as u64;
+        indices.extend(int_to_indices(params, 2));
+
+        indices.extend(bytes_to_indices(&self.value));
+
+        let checksum = rs1024_create_checksum(customization_string(self.extendable), &indices);
+        indices.extend(checksum);
+
+        indices.iter().map(|i| WORDLIST[*i as usize]).collect::<Vec<_>>().join(" ")
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Result<Self, String> {
+        let indices = mnemonic
+            .split_whitespace()
+            .map(word_index)
+            .collect::<Result<Vec<u16>, String>>()?;
+
+        if indices.len() < MIN_MNEMONIC_LENGTH_WORDS {
+            return Err(format!(
+                "Invalid mnemonic length. It must be at least {} words long",
+                MIN_MNEMONIC_LENGTH_WORDS
+            ));
+        }
+        let padding_len = (RADIX_BITS * (indices.len() - METADATA_LENGTH_WORDS)) % 16;
+        if padding_len > 8 {
+            return Err("Invalid mnemonic length".to_string());
+        }
+
+        let id_exp = indices_to_int(&indices[..2]);
+        let extendable = (id_exp >> ITERATION_EXP_LENGTH_BITS) & 1 == 1;
+        if !rs1024_verify_checksum(customization_string(extendable), &indices) {
+            return Err("Invalid mnemonic checksum, a word may be mistyped".to_string());
+        }
+
+        let params = indices_to_int(&indices[2..4]);
+        let share = Slip39Share {
+            identifier: (id_exp >> (ITERATION_EXP_LENGTH_BITS + 1)) as u16,
+            extendable,
+            iteration_exponent: (id_exp & 0xf) as u8,
+            group_index: (params >> 16) as u8,
+            group_threshold: ((params >> 12) & 0xf) as u8 + 1,
+            group_count: ((params >> 8) & 0xf) as u8 + 1,
+            member_index: ((params >> 4) & 0xf) as u8,
+            member_threshold: (params & 0xf) as u8 + 1,
+            value: indices_to_bytes(&indices[4..indices.len() - CHECKSUM_LENGTH_WORDS], padding_len)?,
+        };
+        if share.group_count < share.group_threshold {
+            return Err("Invalid mnemonic, group threshold cannot be greater than group count".to_string());
+        }
+        Ok(share)
+    }
+}
+
+/// Splits `master_secret` into mnemonics, one list per group, protected by `passphrase`.
+/// Any `group_threshold` groups, each with its member threshold of mnemonics, recover the secret.
+pub fn generate_mnemonics(
+    group_threshold: u8,
+    groups: &[Slip39Group],
+    master_secret: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+) -> Result<Vec<Vec<String>>, String> {
+    if master_secret.len() < MIN_STRENGTH_BYTES || !master_secret.len().is_multiple_of(2) {
+        return Err(format!(
+            "The master secret must be at least {} bytes long and have an even length",
+            MIN_STRENGTH_BYTES
+        ));
+    }
+    if groups.is_empty() || groups.len() > 16 {
+        return Err("There must be between 1 and 16 groups".to_string());
+    }
+    if group_threshold == 0 || group_threshold as usize > groups.len() {
+        return Err("The group threshold must be between 1 and the number of groups".to_string());
+    }
+    for group in groups {
+        if group.member_count == 0 || group.member_count > 16 {
+            return Err("Each group must have between 1 and 16 members".to_string());
+        }
+        if group.member_threshold == 0 || group.member_threshold > group.member_count {
+            return Err("Member thresholds must be between 1 and the group member count".to_string());
+        }
+        if group.member_threshold == 1 && group.member_count > 1 {
+            return Err("A 1-of-n group is not allowed, use 1-of-1 instead".to_string());
+        }
+    }
+    if iteration_exponent >= 1 << ITERATION_EXP_LENGTH_BITS {
+        return Err("Iteration exponent is too large".to_string());
+    }
+    let passphrase = check_passphrase(passphrase)?;
+
+    let mut rng = OsRng;
+    let identifier = (rng.next_u32() & ((1 << ID_LENGTH_BITS) - 1)) as u16;
+    let extendable = true;
+
+    let encrypted_master_secret = encrypt(master_secret, passphrase, iteration_exponent, identifier, extendable);
+    let group_shares = split_secret(group_threshold, groups.len() as u8, &encrypted_master_secret)?;
+
+    groups
+        .iter()
+        .zip(group_shares)
+        .map(|(group, (group_index, group_secret))| {
+            Ok(split_secret(group.member_threshold, group.member_count, &group_secret)?
+                .into_iter()
+                .map(|(member_index, value)| {
+                    Slip39Share {
+                        identifier,
+                        extendable,
+                        iteration_exponent,
+                        group_index,
+                        group_threshold,
+                        group_count: groups.len() as u8,
+                        member_index,
+                        member_threshold: group.member_threshold,
+                        value,
+                    }
+                    .to_mnemonic()
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Describes what is still missing to recover the secret, None once enough shares are collected.
+/// Fails if the shares do not belong to the same split.
+pub fn missing_shares(shares: &[Slip39Share]) -> Result<Option<String>, String> {
+    let Some(first) = shares.first() else {
+        return Ok(Some("No mnemonic entered yet".to_string()));
+    };
+    for (i, share) in shares.iter().enumerate() {
+        if share.identifier != first.identifier
+            || share.extendable != first.extendable
+            || share.iteration_exponent != first.iteration_exponent
+        {
+            return Err("All mnemonics must begin with the same identifier words".to_string());
+        }
+        if share.group_threshold != first.group_threshold || share.group_count != first.group_count {
+            return Err("All mnemonics must have the same group threshold and group count".to_string());
+        }
+        if share.value.len() != first.value.len() {
+            return Err("All mnemonics must have the same length".to_string());
+        }
+        for other in &shares[..i] {
+            if other.group_index == share.group_index {
+                if other.member_threshold != share.member_threshold {
+                    return Err("Mnemonics of the same group must have the same member threshold".to_string());
+                }
+                if other.member_index == share.member_index {
+                    return Err(format!(
+                        "Mnemonic {} of group {} was entered twice",
+                        share.member_index + 1,
+                        share.group_index + 1
+                    ));
+                }
+            }
+        }
+    }
+
+    let groups = group_shares(shares);
+    let complete = groups
+        .iter()
+        .filter(|group| group.len() >= group[0].member_threshold as usize)
+        .count();
+    if complete >= first.group_threshold as usize {
+        return Ok(None);
+    }
+
+    let mut description = format!("{} of {} groups complete", complete, first.group_threshold);
+    for group in groups.iter().filter(|group| group.len() < group[0].member_threshold as usize) {
+        description.push_str(&format!(
+            ", group {} needs {} more",
+            group[0].group_index + 1,
+            group[0].member_threshold as usize - group.len()
+        ));
+    }
+    Ok(Some(description))
+}
+
+/// Recovers the master secret from enough mnemonics of a split
+pub fn combine_shares(shares: &[Slip39Share], passphrase: &str) -> Result<Vec<u8>, String> {
+    if let Some(missing) = missing_shares(shares)? {
+        return Err(format!("Not enough mnemonics: {}", missing));
+    }
+    let passphrase = check_passphrase(passphrase)?;
+    let first = &shares[0];
+
+    let group_secrets = group_shares(shares)
+        .into_iter()
+        .filter(|group| group.len() >= group[0].member_threshold as usize)
+        .take(first.group_threshold as usize)
+        .map(|group| {
+            let member_threshold = group[0].member_threshold;
+            let members: Vec<(u8, Vec<u8>)> = group
+                .iter()
+                .take(member_threshold as usize)
+                .map(|share| (share.member_index, share.value.clone()))
+                .collect();
+            Ok((group[0].group_index, recover_secret(member_threshold, &members)?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let encrypted_master_secret = recover_secret(first.group_threshold, &group_secrets)?;
+    Ok(decrypt(
+        &encrypted_master_secret,
+        passphrase,
+        first.iteration_exponent,
+        first.identifier,
+        first.extendable,
+    ))
+}
+
+fn group_shares(shares: &[Slip39Share]) -> Vec<Vec<&Slip39Share>> {
+    let mut groups: Vec<Vec<&Slip39Share>> = Vec::new();
+    for share in shares {
+        match groups.iter_mut().find(|group| group[0].group_index == share.group_index) {
+            Some(group) => group.push(share),
+            None => groups.push(vec![share]),
+        }
+    }
+    groups
+}
+
+fn check_passphrase(passphrase: &str) -> Result<&[u8], String> {
+    if passphrase.bytes().all(|b| (32..=126).contains(&b)) {
+        Ok(passphrase.as_bytes())
+    } else {
+        Err("The passphrase must only contain printable ASCII characters".to_string())
+    }
+}
+
+// --- Shamir layer -------------------------------------------------------------------------------
+
+fn interpolate(shares: &[(u8, Vec<u8>)], x: u8) -> Result<Vec<u8>, String> {
+    (0..shares[0].1.len())
+        .map(|i| {
+            let points: Vec<(Gf256, Gf256)> = shares
+                .iter()
+                .map(|(xi, value)| (Gf256(*xi), Gf256(value[i])))
+                .collect();
+            interpolate_at(&points, &Gf256(x)).map(|y| y.0)
+        })
+        .collect()
+}
+
+fn create_digest(random_data: &[u8], shared_secret: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(random_data).expect("HMAC accepts any key length");
+    mac.update(shared_secret);
+    mac.finalize().into_bytes()[..DIGEST_LENGTH_BYTES].to_vec()
+}
+
+/// SLIP-0039 flavour of Shamir: the secret sits at x = 255 and a digest of it at x = 254,
+/// shares are the points x = 0, 1, ..., share_count - 1
+fn split_secret(threshold: u8, share_count: u8, shared_secret: &[u8]) -> Result<Vec<(u8, Vec<u8>)>, String> {
+    if threshold == 0 || threshold > share_count || share_count > 16 {
+        return Err("Invalid threshold or share count".to_string());
+    }
+    if threshold == 1 {
+        return Ok((0..share_count).map(|i| (i, shared_secret.to_vec())).collect());
+    }
+
+    let mut rng = OsRng;
+    let random_share_count = threshold - 2;
+    let mut shares: Vec<(u8, Vec<u8>)> = (0..random_share_count)
+        .map(|i| {
+            let mut value = vec![0u8; shared_secret.len()];
+            rng.fill_bytes(&mut value);
+            (i, value)
+        })
+        .collect();
+
+    let mut random_part = vec![0u8; shared_secret.len() - DIGEST_LENGTH_BYTES];
+    rng.fill_bytes(&mut random_part);
+    let mut digest_share = create_digest(&random_part, shared_secret);
+    digest_share.extend_from_slice(&random_part);
+
+    let mut base_shares = shares.clone();
+    base_shares.push((DIGEST_INDEX, digest_share));
+    base_shares.push((SECRET_INDEX, shared_secret.to_vec()));
+
+    for i in random_share_count..share_count {
+        shares.push((i, interpolate(&base_shares, i)?));
+    }
+    Ok(shares)
+}
+
+fn recover_secret(threshold: u8, shares: &[(u8, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    if threshold == 1 {
+        return Ok(shares[0].1.clone());
+    }
+    let shared_secret = interpolate(shares, SECRET_INDEX)?;
+    let digest_share = interpolate(shares, DIGEST_INDEX)?;
+    let (digest, random_part) = digest_share.split_at(DIGEST_LENGTH_BYTES);
+    if digest != create_digest(random_part, &shared_secret) {
+        return Err("Invalid digest of the shared secret, the mnemonics do not match".to_string());
+    }
+    Ok(shared_secret)
+}
+
+// --- Encryption of the master secret ------------------------------------------------------------
+
+fn round_function(i: u8, passphrase: &[u8], iteration_exponent: u8, salt: &[u8], r: &[u8]) -> Vec<u8> {
+    let mut password = vec![i];
+    password.extend_from_slice(passphrase);
+    let mut salted = salt.to_vec();
+    salted.extend_from_slice(r);
+    let rounds = (BASE_ITERATION_COUNT << iteration_exponent) / ROUND_COUNT as u32;
+
+    let mut output = vec![0u8; r.len()];
+    pbkdf2::pbkdf2_hmac::<Sha256>(&password, &salted, rounds, &mut output);
+    output
+}
+
+fn get_salt(identifier: u16, extendable: bool) -> Vec<u8> {
+    if extendable {
+        return Vec::new();
+    }
+    let mut salt = CUSTOMIZATION_STRING_ORIG.to_vec();
+    salt.extend_from_slice(&identifier.to_be_bytes());
+    salt
+}
+
+/// Four rounds Feistel network keyed by the passphrase
+fn feistel(data: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool, rounds: &[u8]) -> Vec<u8> {
+    let (l, r) = data.split_at(data.len() / 2);
+    let (mut l, mut r) = (l.to_vec(), r.to_vec());
+    let salt = get_salt(identifier, extendable);
+    for i in rounds {
+        let f = round_function(*i, passphrase, iteration_exponent, &salt, &r);
+        let new_r: Vec<u8> = l.iter().zip(f).map(|(a, b)| a ^ b).collect();
+        l = r;
+        r = new_r;
+    }
+    r.extend_from_slice(&l);
+    r
+}
+
+fn encrypt(master_secret: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool) -> Vec<u8> {
+    feistel(master_secret, passphrase, iteration_exponent, identifier, extendable, &[0, 1, 2, 3])
+}
+
+fn decrypt(encrypted_master_secret: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool) -> Vec<u8> {
+    feistel(encrypted_master_secret, passphrase, iteration_exponent, identifier, extendable, &[3, 2, 1, 0])
+}
+
+// --- Word encoding and RS1024 checksum ----------------------------------------------------------
+
+fn word_index(word: &str) -> Result<u16, String> {
+    let word = word.to_lowercase();
+    // Words are uniquely identified by their first four letters
+    let prefix: String = word.chars().take(4).collect();
+    WORDLIST
+        .iter()
+        .position(|w| *w == word || (word.len() == 4 && w.starts_with(&prefix)))
+        .map(|i| i as u16)
+        .ok_or_else(|| format!("'{}' is not a SLIP-0039 word", word))
+}
+
+fn customization_string(extendable: bool) -> &'static [u8] {
+    if extendable {
+        CUSTOMIZATION_STRING_EXTENDABLE
+    } else {
+        CUSTOMIZATION_STRING_ORIG
+    }
+}
+
+fn rs1024_polymod(values: impl Iterator<Item = u32>) -> u32 {
+    const GEN: [u32; 10] = [
+        0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009, 0x1C0C2412, 0x38086C24, 0x3090FC48,
+        0x21B1F890, 0x3F3F120,
+    ];
+    let mut chk = 1u32;
+    for v in values {
+        let b = chk >> 20;
+        chk = ((chk & 0xFFFFF) << 10) ^ v;
+        for (i, g) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn rs1024_create_checksum(customization: &[u8], data: &[u16]) -> Vec<u16> {
+    let values = customization
+        .iter()
+        .map(|b| *b as u32)
+        .chain(data.iter().map(|i| *i as u32))
+        .chain([0, 0, 0]);
+    let polymod = rs1024_polymod(values) ^ 1;
+    (0..CHECKSUM_LENGTH_WORDS)
+        .map(|i| ((polymod >> (10 * (2 - i))) & 1023) as u16)
+        .collect()
+}
+
+fn rs1024_verify_checksum(customization: &[u8], data: &[u16]) -> bool {
+    let values = customization
+        .iter()
+        .map(|b| *b as u32)
+        .chain(data.iter().map(|i| *i as u32));
+    rs1024_polymod(values) == 1
+}
+
+fn int_to_indices(value: u64, length: usize) -> Vec<u16> {
+    (0..length)
+        .rev()
+        .map(|i| ((value >> (i * RADIX_BITS)) & 1023) as u16)
+        .collect()
+}
+
+fn indices_to_int(indices: &[u16]) -> u64 {
+    indices.iter().fold(0, |acc, i| (acc << RADIX_BITS) | *i as u64)
+}
+
+/// Big-endian 10-bit words, zero-padded on the left
+fn bytes_to_indices(bytes: &[u8]) -> Vec<u16> {
+    let word_count = (bytes.len() * 8).div_ceil(RADIX_BITS);
+    let padding = word_count * RADIX_BITS - bytes.len() * 8;
+    let mut indices = Vec::with_capacity(word_count);
+    let (mut acc, mut acc_bits) = (0u32, padding);
+    for byte in bytes {
+        acc = (acc << 8) | *byte as u32;
+        acc_bits += 8;
+        while acc_bits >= RADIX_BITS {
+            acc_bits -= RADIX_BITS;
+            indices.push(((acc >> acc_bits) & 1023) as u16);
+        }
+        acc &= (1 << acc_bits) - 1;
+    }
+    indices
+}
+
+fn indices_to_bytes(indices: &[u16], padding_len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity((indices.len() * RADIX_BITS - padding_len) / 8);
+    let (mut acc, mut acc_bits) = (0u32, 0usize);
+    let mut padding_left = padding_len;
+    for index in indices {
+        acc = (acc << RADIX_BITS) | *index as u32;
+        acc_bits += RADIX_BITS;
+        if padding_left > 0 {
+            acc_bits -= padding_left;
+            if acc >> acc_bits != 0 {
+                return Err("Invalid mnemonic padding".to_string());
+            }
+            padding_left = 0;
+        }
+        while acc_bits >= 8 {
+            acc_bits -= 8;
+            bytes.push((acc >> acc_bits) as u8);
+        }
+        acc &= (1 << acc_bits) - 1;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cases from the official SLIP-0039 test vectors (vectors.json), all with the passphrase "TREZOR"
+    const PASSPHRASE: &str = "TREZOR";
+
+    fn combine(mnemonics: &[&str]) -> Result<String, String> {
+        let shares = mnemonics
+            .iter()
+            .map(|mnemonic| Slip39Share::from_mnemonic(mnemonic))
+            .collect::<Result<Vec<_>, _>>()?;
+        combine_shares(&shares, PASSPHRASE).map(hex::encode)
+    }
+
+    #[test]
+    fn combines_valid_vectors() {
+        // 1. Valid mnemonic without sharing (128 bits)
+        assert_eq!(
+            combine(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"]),
+            Ok("bb54aac4b89dc868ba37d9cc21b2cece".to_string())
+        );
+        // 4. Basic sharing 2-of-3 (128 bits)
+        assert_eq!(
+            combine(&[
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+                "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+            ]),
+            Ok("b43ceb7e57a0ea8766221624d01b0864".to_string())
+        );
+        // Threshold number of groups and members in each group (128 bits)
+        assert_eq!(
+            combine(&[
+                "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+                "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+                "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+                "eraser senior decision smug corner ruin rescue cubic angel tackle skin skunk program roster trash rumor slush angel flea amazing",
+            ]),
+            Ok("7c3397a292a5941682d7a4ae2d898d11".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_checksum_and_padding() {
+        // 2. Mnemonic with invalid checksum (128 bits)
+        assert_eq!(
+            combine(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney"]),
+            Err("Invalid mnemonic checksum, a word may be mistyped".to_string())
+        );
+        // 3. Mnemonic with invalid padding (128 bits)
+        assert_eq!(
+            combine(&["duckling enlarge academic academic email result length solution fridge kidney coal piece deal husband erode duke ajar music cargo fitness"]),
+            Err("Invalid mnemonic padding".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_group_threshold_errors() {
+        // Group threshold greater than the group count
+        assert_eq!(
+            combine(&[
+                "music husband acrobat acid artist finance center either graduate swimming object bike medical clothes station aspect spider maiden bulb welcome",
+                "music husband acrobat agency advance hunting bike corner density careful material civil evil tactics remind hawk discuss hobo voice rainbow",
+            ]),
+            Err("Invalid mnemonic, group threshold cannot be greater than group count".to_string())
+        );
+        // 5. Basic sharing 2-of-3, insufficient number of mnemonics (128 bits)
+        assert!(combine(&["shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed"])
+            .unwrap_err()
+            .starts_with("Not enough mnemonics"));
+        // One complete group when two groups are required
+        assert_eq!(
+            combine(&[
+                "eraser senior decision scared cargo theory device idea deliver modify curly include pancake both news skin realize vitamins away join",
+                "eraser senior decision roster beard treat identify grumpy salt index fake aviation theater cubic bike cause research dragon emphasis counter",
+            ]),
+            Err("Not enough mnemonics: 1 of 2 groups complete".to_string())
+        );
+        // Two members of a group that needs three, in a split that needs two groups
+        assert_eq!(
+            combine(&[
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+                "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+            ]),
+            Err("Not enough mnemonics: 0 of 2 groups complete, group 3 needs 1 more".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_mnemonics_of_different_splits() {
+        // 6. Mnemonics with different identifiers (128 bits)
+        assert_eq!(
+            combine(&[
+                "adequate smoking academic acid debut wine petition glen cluster slow rhyme slow simple epidemic rumor junk tracks treat olympic tolerate",
+                "adequate stay academic agency agency formal party ting frequent learn upstairs remember smear leaf damage anatomy ladle market hush corner",
+            ]),
+            Err("All mnemonics must begin with the same identifier words".to_string())
+        );
+        // 7. Mnemonics with different iteration exponents (128 bits)
+        assert_eq!(
+            combine(&[
+                "peasant leaves academic acid desert exact olympic math alive axle trial tackle drug deny decent smear dominant desert bucket remind",
+                "peasant leader academic agency cultural blessing percent network envelope medal junk primary human pumps jacket fragment payroll ticket evoke voice",
+            ]),
+            Err("All mnemonics must begin with the same identifier words".to_string())
+        );
+    }
+
+    #[test]
+    fn generated_mnemonics_round_trip() {
+        let groups = [
+            Slip39Group { member_threshold: 1, member_count: 1 },
+            Slip39Group { member_threshold: 2, member_count: 3 },
+        ];
+        let secret = [0x5a; 32];
+        let mnemonics = generate_mnemonics(2, &groups, &secret, PASSPHRASE, DEFAULT_ITERATION_EXPONENT).unwrap();
+        let shares = [&mnemonics[0][0], &mnemonics[1][0], &mnemonics[1][2]]
+            .iter()
+            .map(|mnemonic| Slip39Share::from_mnemonic(mnemonic).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(combine_shares(&shares, PASSPHRASE).unwrap(), secret);
+        assert_ne!(combine_shares(&shares, "").unwrap(), secret);
+    }
+}
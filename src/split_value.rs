@@ -0,0 +1,88 @@
+use rand::RngCore;
+use rand::rngs::OsRng;
+use crate::secret::{SecretBytes, SecretString};
+use crate::shamir_secret_sharing::{split_secret, ShamirBackend};
+use crate::share_encoding::{recover_encoded_secret, EncodedShare, FINGERPRINT_BYTES};
+
+/// Prefix of the marker left in place of a split entry value
+pub const SPLIT_MARKER_PREFIX: &str = "bunkeys-split:";
+/// Random bytes split along with the value, so that a single share's fingerprint
+/// cannot be used to brute-force a short value such as a PIN
+const SALT_BYTES: usize = 16;
+
+/// Stands in the store for an entry value that was split into shares:
+/// `bunkeys-split:<threshold>/<share count>:<fingerprint hex>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitMarker {
+    pub threshold: u8,
+    pub share_count: u8,
+    pub fingerprint: [u8; FINGERPRINT_BYTES],
+}
+
+impl SplitMarker {
+    /// Reads a marker back from an entry value, `None` if the value is not split
+    pub fn parse(value: &str) -> Option<Self> {
+        let (counts, fingerprint) = value.trim().strip_prefix(SPLIT_MARKER_PREFIX)?.split_once(':')?;
+        let (threshold, share_count) = counts.split_once('/')?;
+        let fingerprint = hex::decode(fingerprint).ok()?.try_into().ok()?;
+        Some(SplitMarker {
+            threshold: threshold.parse().ok()?,
+            share_count: share_count.parse().ok()?,
+            fingerprint,
+        })
+    }
+
+    pub fn to_value(&self) -> String {
+        format!(
+            "{}{}/{}:{}",
+            SPLIT_MARKER_PREFIX,
+            self.threshold,
+            self.share_count,
+            hex::encode(self.fingerprint)
+        )
+    }
+
+    /// Whether the share was split from the value this marker stands for
+    pub fn covers(&self, share: &EncodedShare) -> bool {
+        share.fingerprint == self.fingerprint
+            && share.threshold == self.threshold
+            && share.share_count == self.share_count
+    }
+}
+
+/// Splits an entry value into `share_count` shares, any `threshold` of which recombine it.
+/// The byte-wise GF(2⁸) backend is used so that values of any length can be split.
+pub fn split_value(value: &str, threshold: usize, share_count: usize) -> Result<(SplitMarker, Vec<EncodedShare>), String> {
+    if value.is_empty() {
+        return Err("An empty value cannot be split".to_string());
+    }
+    if SALT_BYTES + value.len() > u16::MAX as usize {
+        return Err(format!("Value is too long to be split, at most {} bytes", u16::MAX as usize - SALT_BYTES));
+    }
+    // Allocated at its final size, so that the plaintext is never left behind by a reallocation
+    let mut secret = SecretBytes::new(vec![0u8; SALT_BYTES + value.len()]);
+    OsRng.fill_bytes(&mut secret.expose_mut()[..SALT_BYTES]);
+    secret.expose_mut()[SALT_BYTES..].copy_from_slice(value.as_bytes());
+
+    let shares = split_secret(ShamirBackend::Gf256, secret.expose(), threshold, share_count)?;
+    let shares = EncodedShare::wrap_all(shares, threshold, secret.expose());
+    let marker = SplitMarker {
+        threshold: threshold as u8,
+        share_count: shares.len() as u8,
+        fingerprint: shares[0].fingerprint,
+    };
+    Ok((marker, shares))
+}
+
+/// Recombines the value a marker stands for from at least `threshold` of its shares
+pub fn recombine_value(marker: &SplitMarker, shares: &[EncodedShare]) -> Result<SecretString, String> {
+    if let Some(share) = shares.iter().find(|share| !marker.covers(share)) {
+        return Err(format!("Share {} was not split from this value", share.name()));
+    }
+    let secret = SecretBytes::new(recover_encoded_secret(shares)?.secret);
+    let value = secret
+        .expose()
+        .get(SALT_BYTES..)
+        .ok_or_else(|| "Recovered value is too short, the shares do not match".to_string())?;
+    std::str::from_utf8(value).map(SecretString::from).map_err(|_| "Recovered value is not valid text".to_string())
+}
@@ -1,19 +1,47 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use indexmap::IndexMap;
 use crate::app::data::{Entry, Section};
-use crate::encrypt::{decrypt_string, encrypt_string};
+use crate::encrypt::{
+    decrypt_string, encrypt_string, is_container, rewrite_slots, Cipher, ContainerHeader, Kdf, KeyProtection,
+    KeySlot, SlotKind, UnlockInput,
+};
+use crate::secret::{SecretBytes, SecretString};
+
+/// A store read from disk, with what its container says about it
+pub struct LoadedStore {
+    pub store: Store,
+    /// Plaintext label of the container
+    pub label: Option<String>,
+    /// Random key encrypting the store, None for unencrypted files
+    pub data_key: Option<SecretBytes>,
+    /// Key of the slot that opened the store
+    pub slot_key: Option<SecretBytes>,
+    pub slots: Vec<KeySlot>,
+    /// Cipher of the contents, kept when saving again
+    pub cipher: Cipher,
+    /// Written in a format without key slots, upgraded on the next save
+    pub legacy: bool,
+}
+
+/// Cipher and keys an encrypted store is written with
+pub struct Encryption<'a> {
+    pub cipher: Cipher,
+    pub data_key: &'a [u8],
+    pub slots: Vec<KeySlot>,
+}
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Store {
     /// Section name -> Entry name -> Entry value
     #[serde(flatten)]
-    sections: IndexMap<String, IndexMap<String, String>>,
+    sections: IndexMap<String, IndexMap<String, SecretString>>,
 }
 
 impl Store {
-    pub fn from_sections(sections: &Vec<Section>) -> Self {
+    pub fn from_sections(sections: &[Section]) -> Self {
         let sections = sections
             .iter()
             .map(|section| {
@@ -27,7 +55,7 @@ impl Store {
             .collect();
         Self { sections }
     }
-    pub fn to_sections(self) -> Vec<Section> {
+    pub fn into_sections(self) -> Vec<Section> {
         self.sections
             .into_iter()
             .map(|(name, entries_map)| Section {
@@ -40,50 +68,158 @@ impl Store {
             .collect()
     }
 
-    pub fn load(encryption_key: Option<Vec<u8>>, path: PathBuf) -> Result<Store, String> {
+    /// Reads the container header of an encrypted store without decrypting it, None for legacy files
+    pub fn read_header(path: &PathBuf) -> Result<Option<ContainerHeader>, String> {
+        let data = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        if !is_container(&data) {
+            return Ok(None);
+        }
+        ContainerHeader::parse(&data).map(|(header, _)| Some(header))
+    }
+
+    /// Loads a store, trying every key slot that `unlock` fits, or reads it unencrypted when None.
+    /// Stores written without key slots get a fresh data key and a slot opening with the same input.
+    pub fn load(unlock: Option<UnlockInput>, path: PathBuf) -> Result<LoadedStore, String> {
         // Read the encrypted data from the file
         let encrypted_data = match fs::read(path) {
             Ok(encrypted_data) => encrypted_data,
             Err(e) => return Err(format!("Failed to read file: {}", e)),
         };
 
+        let Some(unlock) = unlock else {
+            let store = serde_yaml::from_slice::<Store>(&encrypted_data)
+                .map_err(|e| format!("Failed to parse store data: {}", e))?;
+            return Ok(LoadedStore {
+                store,
+                label: None,
+                data_key: None,
+                slot_key: None,
+                slots: Vec::new(),
+                cipher: Cipher::default(),
+                legacy: false,
+            });
+        };
+
         // Decrypt the YAML data
-        let yaml_data = if let Some(key) = encryption_key {
-            match decrypt_string(key, encrypted_data) {
-                Ok(data) => data,
-                Err(e) => return Err(format!("Failed to decrypt store data: {}", e)),
-            }
-        } else {
-            encrypted_data
+        let decrypted = match decrypt_string(&unlock, &encrypted_data) {
+            Ok(decrypted) => decrypted,
+            Err(e) => return Err(format!("Failed to decrypt store data: {}", e)),
         };
 
         // Deserialize the YAML data into a Store object
-        serde_yaml::from_slice::<Store>(&yaml_data)
-            .map_err(|e| format!("Failed to parse store data: {}", e))
+        let store = serde_yaml::from_slice::<Store>(decrypted.plaintext.expose())
+            .map_err(|e| format!("Failed to parse store data: {}", e))?;
+
+        let (label, cipher, keys) = match decrypted.header {
+            Some(header) => (header.label, header.cipher, Some(header.keys)),
+            // Legacy files are AES-256-GCM
+            None => (None, Cipher::Aes256Gcm, None),
+        };
+        let (data_key, slots, legacy) = match keys {
+            Some(KeyProtection::Slots(slots)) => (decrypted.data_key, slots, false),
+            keys => {
+                let kdf = match keys {
+                    Some(KeyProtection::Direct(kdf)) => kdf,
+                    _ => Kdf::Raw,
+                };
+                let kind = match kdf {
+                    Kdf::Raw => SlotKind::Key,
+                    Kdf::Argon2id(_) => SlotKind::Passphrase,
+                    Kdf::KeyFile => SlotKind::KeyFile,
+                };
+                let data_key = SecretBytes::random(32);
+                let slot = KeySlot::wrap(kind, kdf, decrypted.slot_key.expose(), data_key.expose())?;
+                (data_key, vec![slot], true)
+            }
+        };
+        Ok(LoadedStore {
+            store,
+            label,
+            data_key: Some(data_key),
+            slot_key: Some(decrypted.slot_key),
+            slots,
+            cipher,
+            legacy,
+        })
+    }
+
+    /// Replaces the key slots of the store file without re-encrypting its contents
+    pub fn rewrite_slots(path: &PathBuf, data_key: &[u8], slots: Vec<KeySlot>) -> Result<(), String> {
+        let data = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let data = rewrite_slots(&data, data_key, slots)?;
+        Self::write_atomically(path, &data)
     }
 
     // Save
-    pub fn save(&self, encryption_key: Option<Vec<u8>>, path: PathBuf) -> Result<String, String> {
+    pub fn save(
+        &self,
+        encryption: Option<Encryption>,
+        label: Option<String>,
+        path: PathBuf,
+    ) -> Result<String, String> {
+        let encrypted_data = self.to_file_data(encryption, label)?;
+
+        // A failed save leaves the previous file as it was
+        Self::write_atomically(&path, &encrypted_data)?;
+        Ok("Store saved successfully!".to_string())
+    }
+
+    /// Writes the store encrypted under new keys in place of the file at `path`, without a moment
+    /// where the file is half written. The previous file is copied to `<path>.bak` first,
+    /// returning the backup path if there was one.
+    pub fn replace(&self, encryption: Encryption, label: Option<String>, path: &Path) -> Result<Option<PathBuf>, String> {
+        let encrypted_data = self.to_file_data(Some(encryption), label)?;
+
+        let backup = if path.exists() {
+            let backup = Self::sibling_path(path, "bak");
+            fs::copy(path, &backup).map_err(|e| format!("Failed to back up the store file: {}", e))?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        Self::write_atomically(path, &encrypted_data)?;
+        Ok(backup)
+    }
+
+    /// Writes next to the store then renames, which replaces the file at once
+    fn write_atomically(path: &Path, data: &[u8]) -> Result<(), String> {
+        let temp = Self::sibling_path(path, "tmp");
+        let written = fs::File::create(&temp)
+            .and_then(|mut file| {
+                file.write_all(data)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temp, path));
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(format!("Failed to write file: {}", e));
+        }
+        Ok(())
+    }
+
+    /// `store.enc` -> `store.enc.<extension>`
+    fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(extension);
+        path.with_file_name(name)
+    }
+
+    fn to_file_data(&self, encryption: Option<Encryption>, label: Option<String>) -> Result<Vec<u8>, String> {
         // Serialize the store data into YAML
         let yaml_data = match serde_yaml::to_string(self) {
-            Ok(data) => data.into_bytes(),
+            Ok(data) => SecretString::new(data),
             Err(e) => return Err(format!("Failed to serialize store data: {}", e)),
         };
 
         // Encrypt the YAML data
-        let encrypted_data = if let Some(key) = encryption_key {
-            match encrypt_string(key, yaml_data) {
-                Ok(data) => data,
-                Err(e) => return Err(format!("Failed to encrypt store data: {}", e)),
+        match encryption {
+            Some(Encryption { cipher, data_key, slots }) => {
+                encrypt_string(cipher, data_key, yaml_data.expose().as_bytes().to_vec(), slots, label)
+                    .map_err(|e| format!("Failed to encrypt store data: {}", e))
             }
-        } else {
-            yaml_data
-        };
-
-        // Write the encrypted data to the file
-        match fs::write(&path, encrypted_data) {
-            Ok(_) => Ok("Store saved successfully!".to_string()),
-            Err(e) => Err(format!("Failed to write file: {}", e)),
+            None => Ok(yaml_data.expose().as_bytes().to_vec()),
         }
     }
 }
@@ -0,0 +1,43 @@
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+use crate::age_shares::resolve_share_input;
+use crate::envelope::seal_envelope;
+use crate::share_encoding::EncodedShare;
+
+const USAGE: &str = "Usage: bunkeys submit-share <request code | request file> [--out <envelope file>]";
+
+/// `bunkeys submit-share`: seals the holder's share to the coordinator's request code.
+/// The share is read from stdin so that it stays out of the shell history.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (request, out) = match args {
+        [request] => (request, None),
+        [request, flag, path] if flag == "--out" => (request, Some(path)),
+        _ => return Err(USAGE.to_string()),
+    };
+    // The coordinator may have sent the request code as a file
+    let request_code = if Path::new(request).is_file() {
+        fs::read_to_string(request).map_err(|e| format!("Failed to read request file: {}", e))?
+    } else {
+        request.clone()
+    };
+
+    eprintln!("Enter your share (words, hex, base32, or '<file.age> <identity file>'):");
+    let mut input = String::new();
+    io::stdin().lock().read_line(&mut input).map_err(|e| format!("Failed to read the share: {}", e))?;
+    let share_text = resolve_share_input(input.trim())?;
+    let share = EncodedShare::decode(&share_text)?;
+
+    let envelope = seal_envelope(&request_code, &share_text)?;
+    match out {
+        Some(path) => {
+            fs::write(path, format!("{}\n", envelope)).map_err(|e| format!("Failed to write envelope file: {}", e))?;
+            eprintln!("Envelope for share {} written to {}, send it to the coordinator", share.name(), path);
+        }
+        None => {
+            eprintln!("Envelope for share {}, send this line to the coordinator:", share.name());
+            println!("{}", envelope);
+        }
+    }
+    Ok(())
+}